@@ -2,16 +2,14 @@ use kuber_sim::*;
 
 /// This example shows that when pods on the kubelet try to consume more memory than is available on the node, an eviction process occurs.
 /// Eviction works only for memory. This mechanism is not required for CPU due to the presence of throttling.
-fn main() {
+fn main() -> Result<(), KuberSimError> {
     // Read input
-    let mut init_config = InitConfig::from_yaml(&"./in_eviction.yaml".to_string());
-    let mut init_nodes = InitNodes::from_yaml(&"./in_eviction.yaml".to_string());
-    let mut init_trace = InitTrace::from_file(&"./in_eviction.yaml".to_string());
+    let mut init_config = InitConfig::from_yaml(&"./in_eviction.yaml".to_string())?;
+    let init_nodes = InitNodes::from_yaml(&"./in_eviction.yaml".to_string())?;
+    let init_trace = InitTrace::from_file(&"./in_eviction.yaml".to_string())?;
 
     // Prepare input
-    init_config.prepare();
-    init_nodes.prepare();
-    init_trace.prepare();
+    init_config.prepare()?;
 
     // Prepare scheduler pipeline config
     let pipeline_config = PipelineConfig::new(
@@ -30,13 +28,15 @@ fn main() {
         &init_config,
         &init_nodes,
         &init_trace,
-        &pipeline_config,
+        &[(DEFAULT_SCHEDULER_NAME, &pipeline_config)],
         123,
         false,
         false,
         false,
-    );
+    )?;
 
     // Work with simulation
     sim.step_for_duration(50.0);
+
+    return Ok(());
 }
@@ -1,16 +1,14 @@
 use kuber_sim::*;
 
 /// This example shows how to run simulation multithreaded
-fn main() {
+fn main() -> Result<(), KuberSimError> {
     // Read input
-    let mut init_config = InitConfig::from_yaml(&"./in_multithread.yaml".to_string());
-    let mut init_nodes = InitNodes::from_yaml(&"./in_multithread.yaml".to_string());
-    let mut init_trace = InitTrace::from_file(&"./in_multithread.yaml".to_string());
+    let mut init_config = InitConfig::from_yaml(&"./in_multithread.yaml".to_string())?;
+    let mut init_nodes = InitNodes::from_yaml(&"./in_multithread.yaml".to_string())?;
+    let init_trace = InitTrace::from_file(&"./in_multithread.yaml".to_string())?;
 
     // Prepare input
-    init_config.prepare();
-    init_nodes.prepare();
-    init_trace.prepare();
+    init_config.prepare()?;
 
     // Prepare scheduler pipeline config
     let mut pipeline_config = PipelineConfig::new(
@@ -32,7 +30,7 @@ fn main() {
         &init_config,
         &init_nodes,
         &init_trace,
-        &pipeline_config,
+        &[(DEFAULT_SCHEDULER_NAME, &pipeline_config)],
         123,
         false,
         false,
@@ -54,10 +52,8 @@ fn main() {
     // Change cluster node resources
     init_nodes.nodes[0].node.spec.installed_cpu = 200;
 
-    // After any changes to the init_config, init_nodes or init_trace it is important to prepare changed again
-    init_config.prepare();
-    init_nodes.prepare();
-    // pipeline_config does not need to be prepared again.
+    // After any changes to the init_config it is important to prepare it again.
+    // init_nodes and init_trace are prepared by simulation, pipeline_config does not need to be prepared.
 
     // Add second simulation
     experiment.add_simulation(
@@ -65,7 +61,7 @@ fn main() {
         &init_config,
         &init_nodes,
         &init_trace,
-        &pipeline_config,
+        &[(DEFAULT_SCHEDULER_NAME, &pipeline_config)],
         123,
         false,
         false,
@@ -79,4 +75,6 @@ fn main() {
     experiment.spawn_all();
     // And wait until all the simulations are finished
     experiment.join_all();
+
+    return Ok(());
 }
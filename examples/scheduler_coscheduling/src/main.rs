@@ -0,0 +1,43 @@
+use kuber_sim::*;
+
+/// This example shows gang scheduling of pod groups with min_member.
+/// Pods of a gang are placed all-or-nothing, partially placed gangs release their nodes after timeout.
+fn main() -> Result<(), KuberSimError> {
+    // Read input
+    let mut init_config = InitConfig::from_yaml(&"./in_scheduler_coscheduling.yaml".to_string())?;
+    let init_nodes = InitNodes::from_yaml(&"./in_scheduler_coscheduling.yaml".to_string())?;
+    let init_trace = InitTrace::from_file(&"./in_scheduler_coscheduling.yaml".to_string())?;
+
+    // Prepare input
+    init_config.prepare()?;
+
+    // Prepare scheduler pipeline config
+    let mut pipeline_config = PipelineConfig::new(
+        Box::new(ActiveQDefault::default()),
+        Box::new(BackOffQConstant::new(1.0)),
+        vec![FilterRequestedResourcesAvailable.clone()],
+        vec![],
+        vec![Box::new(ScoreLeastAllocated::default())],
+        vec![ScoreNormalizeSkip.clone()],
+        vec![1],
+    );
+    pipeline_config.enable_coscheduling(5.0)?;
+
+    // Create simulation
+    let mut sim = Simulation::new(
+        "./out_scheduler_coscheduling".to_string(),
+        &init_config,
+        &init_nodes,
+        &init_trace,
+        &[(DEFAULT_SCHEDULER_NAME, &pipeline_config)],
+        123,
+        false,
+        false,
+        false,
+    )?;
+
+    // Work with simulation
+    sim.step_for_duration(50.0);
+
+    return Ok(());
+}
@@ -0,0 +1,52 @@
+use kuber_sim::*;
+
+/// This example shows how to write scheduling decisions to a JSON lines log
+/// and how to ask the simulation why a pod is still pending.
+fn main() -> Result<(), KuberSimError> {
+    // Read input
+    let mut init_config = InitConfig::from_yaml(&"./in_scheduler_decision_log.yaml".to_string())?;
+    let init_nodes = InitNodes::from_yaml(&"./in_scheduler_decision_log.yaml".to_string())?;
+    let init_trace = InitTrace::from_file(&"./in_scheduler_decision_log.yaml".to_string())?;
+
+    // Prepare input
+    init_config.prepare()?;
+
+    // Prepare scheduler pipeline config
+    let pipeline_config = PipelineConfig::new(
+        Box::new(ActiveQDefault::default()),
+        Box::new(BackOffQConstant::new(1.0)),
+        vec![
+            FilterRequestedResourcesAvailable.clone(),
+            FilterNodeSelector.clone(),
+            FilterTaintsTolerations.clone(),
+        ],
+        vec![],
+        vec![Box::new(ScoreLeastAllocated::default())],
+        vec![ScoreNormalizeSkip.clone()],
+        vec![1],
+    );
+
+    // Create simulation
+    let mut sim = Simulation::new(
+        "./out_scheduler_decision_log".to_string(),
+        &init_config,
+        &init_nodes,
+        &init_trace,
+        &[(DEFAULT_SCHEDULER_NAME, &pipeline_config)],
+        123,
+        false,
+        false,
+        false,
+    )?;
+
+    // Write decisions with the 3 best scored nodes to ./out_scheduler_decision_log_decisions.jsonl
+    sim.enable_decision_log(3);
+
+    // Work with simulation
+    sim.step_for_duration(10.0);
+    sim.disable_decision_log();
+
+    println!("Pod 3: {:?}", sim.last_failure_reason(3));
+
+    return Ok(());
+}
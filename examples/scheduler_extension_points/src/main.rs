@@ -0,0 +1,82 @@
+use kuber_sim::objects::node::Node;
+use kuber_sim::objects::pod::Pod;
+use kuber_sim::*;
+use std::collections::HashMap;
+
+/// Custom filter plugin which limits the number of running pods of each pod group in PreFilter.
+struct FilterGroupQuota {
+    max_running: usize,
+}
+
+impl IFilterPlugin for FilterGroupQuota {
+    fn name(&self) -> String {
+        return "FilterGroupQuota".to_string();
+    }
+
+    fn pre_filter(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        pod: &Pod,
+    ) -> bool {
+        let running = running_pods
+            .values()
+            .filter(|other| other.metadata.group_uid == pod.metadata.group_uid)
+            .count();
+        return running < self.max_running;
+    }
+
+    fn filter(&self, _: &HashMap<u64, Pod>, _: &HashMap<u64, Pod>, _: &HashMap<u64, Node>, _: &Pod, _: &Node) -> bool {
+        return true;
+    }
+
+    fn clone(&self) -> Box<dyn IFilterPlugin + Send> {
+        return Box::new(FilterGroupQuota {
+            max_running: self.max_running,
+        });
+    }
+}
+
+/// This example shows how to plug a custom policy into the scheduler pipeline via extension points
+fn main() -> Result<(), KuberSimError> {
+    // Read input
+    let mut init_config = InitConfig::from_yaml(&"./in_scheduler_extension_points.yaml".to_string())?;
+    let init_nodes = InitNodes::from_yaml(&"./in_scheduler_extension_points.yaml".to_string())?;
+    let init_trace = InitTrace::from_file(&"./in_scheduler_extension_points.yaml".to_string())?;
+
+    // Prepare input
+    init_config.prepare()?;
+
+    // Prepare scheduler pipeline config. Quota is checked in PreFilter of FilterGroupQuota
+    let pipeline_config = PipelineConfig::new(
+        Box::new(ActiveQDefault::default()),
+        Box::new(BackOffQConstant::new(1.0)),
+        vec![
+            FilterRequestedResourcesAvailable.clone(),
+            Box::new(FilterGroupQuota { max_running: 3 }),
+        ],
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+    );
+
+    // Create simulation
+    let mut sim = Simulation::new(
+        "./out_scheduler_extension_points".to_string(),
+        &init_config,
+        &init_nodes,
+        &init_trace,
+        &[(DEFAULT_SCHEDULER_NAME, &pipeline_config)],
+        123,
+        false,
+        false,
+        false,
+    )?;
+
+    // Work with simulation
+    sim.step_for_duration(50.0);
+
+    return Ok(());
+}
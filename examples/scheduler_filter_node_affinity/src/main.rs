@@ -1,16 +1,14 @@
 use kuber_sim::*;
 
 /// This example shows how filter affinity works in scheduler pipeline
-fn main() {
+fn main() -> Result<(), KuberSimError> {
     // Read input
-    let mut init_config = InitConfig::from_yaml(&"./in_scheduler_filter_node_affinity.yaml".to_string());
-    let mut init_nodes = InitNodes::from_yaml(&"./in_scheduler_filter_node_affinity.yaml".to_string());
-    let mut init_trace = InitTrace::from_file(&"./in_scheduler_filter_node_affinity.yaml".to_string());
+    let mut init_config = InitConfig::from_yaml(&"./in_scheduler_filter_node_affinity.yaml".to_string())?;
+    let init_nodes = InitNodes::from_yaml(&"./in_scheduler_filter_node_affinity.yaml".to_string())?;
+    let init_trace = InitTrace::from_file(&"./in_scheduler_filter_node_affinity.yaml".to_string())?;
 
     // Prepare input
-    init_config.prepare();
-    init_nodes.prepare();
-    init_trace.prepare();
+    init_config.prepare()?;
 
     // Prepare scheduler pipeline config
     let pipeline_config = PipelineConfig::new(
@@ -29,13 +27,15 @@ fn main() {
         &init_config,
         &init_nodes,
         &init_trace,
-        &pipeline_config,
+        &[(DEFAULT_SCHEDULER_NAME, &pipeline_config)],
         123,
         false,
         false,
         false,
-    );
+    )?;
 
     // Work with simulation
     sim.step_for_duration(50.0);
+
+    return Ok(());
 }
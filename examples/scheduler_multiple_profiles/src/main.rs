@@ -0,0 +1,56 @@
+use kuber_sim::*;
+
+/// This example shows how to run several scheduler profiles on the same cluster.
+/// Pods choose a profile by spec.scheduler_name, pods without it go to the default scheduler.
+fn main() -> Result<(), KuberSimError> {
+    // Read input
+    let mut init_config = InitConfig::from_yaml(&"./in_scheduler_multiple_profiles.yaml".to_string())?;
+    let init_nodes = InitNodes::from_yaml(&"./in_scheduler_multiple_profiles.yaml".to_string())?;
+    let init_trace = InitTrace::from_file(&"./in_scheduler_multiple_profiles.yaml".to_string())?;
+
+    // Prepare input
+    init_config.prepare()?;
+
+    // Default profile spreads pods over the least allocated nodes
+    let default_config = PipelineConfig::new(
+        Box::new(ActiveQDefault::default()),
+        Box::new(BackOffQConstant::new(1.0)),
+        vec![FilterRequestedResourcesAvailable.clone()],
+        vec![],
+        vec![Box::new(ScoreLeastAllocated::default())],
+        vec![ScoreNormalizeSkip.clone()],
+        vec![1],
+    );
+
+    // Batch profile packs pods to the most allocated nodes
+    let batch_config = PipelineConfig::new(
+        Box::new(ActiveQDefault::default()),
+        Box::new(BackOffQConstant::new(1.0)),
+        vec![FilterRequestedResourcesAvailable.clone()],
+        vec![],
+        vec![Box::new(ScoreMostAllocated::default())],
+        vec![ScoreNormalizeSkip.clone()],
+        vec![1],
+    );
+
+    // Create simulation
+    let mut sim = Simulation::new(
+        "./out_scheduler_multiple_profiles".to_string(),
+        &init_config,
+        &init_nodes,
+        &init_trace,
+        &[
+            (DEFAULT_SCHEDULER_NAME, &default_config),
+            ("batch-scheduler", &batch_config),
+        ],
+        123,
+        false,
+        false,
+        false,
+    )?;
+
+    // Work with simulation
+    sim.step_for_duration(50.0);
+
+    return Ok(());
+}
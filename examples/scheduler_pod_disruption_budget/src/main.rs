@@ -0,0 +1,43 @@
+use kuber_sim::*;
+
+/// This example shows how PodDisruptionBudget of a pod group affects preemption.
+/// Nodes where preemption does not violate the budget are preferred, and the budget is violated
+/// only if there is no other way to place a higher priority pod.
+fn main() -> Result<(), KuberSimError> {
+    // Read input
+    let mut init_config = InitConfig::from_yaml(&"./in_scheduler_pod_disruption_budget.yaml".to_string())?;
+    let init_nodes = InitNodes::from_yaml(&"./in_scheduler_pod_disruption_budget.yaml".to_string())?;
+    let init_trace = InitTrace::from_file(&"./in_scheduler_pod_disruption_budget.yaml".to_string())?;
+
+    // Prepare input
+    init_config.prepare()?;
+
+    // Prepare scheduler pipeline config
+    let pipeline_config = PipelineConfig::new(
+        Box::new(ActiveQDefault::default()),
+        Box::new(BackOffQConstant::new(1.0)),
+        vec![FilterRequestedResourcesAvailable.clone()],
+        vec![FilterPreemption.clone()],
+        vec![Box::new(ScoreLeastAllocated::default())],
+        vec![ScoreNormalizeSkip.clone()],
+        vec![1],
+    );
+
+    // Create simulation
+    let mut sim = Simulation::new(
+        "./out_scheduler_pod_disruption_budget".to_string(),
+        &init_config,
+        &init_nodes,
+        &init_trace,
+        &[(DEFAULT_SCHEDULER_NAME, &pipeline_config)],
+        123,
+        false,
+        false,
+        false,
+    )?;
+
+    // Work with simulation
+    sim.step_for_duration(30.0);
+
+    return Ok(());
+}
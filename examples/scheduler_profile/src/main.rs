@@ -0,0 +1,33 @@
+use kuber_sim::*;
+
+/// This example shows how to load scheduler pipeline from yaml profile
+fn main() -> Result<(), KuberSimError> {
+    // Read input
+    let mut init_config = InitConfig::from_yaml(&"./in_scheduler_profile.yaml".to_string())?;
+    let init_nodes = InitNodes::from_yaml(&"./in_scheduler_profile.yaml".to_string())?;
+    let init_trace = InitTrace::from_file(&"./in_scheduler_profile.yaml".to_string())?;
+
+    // Prepare input
+    init_config.prepare()?;
+
+    // Read scheduler pipeline config from the same file
+    let pipeline_config = PipelineConfig::from_yaml(&"./in_scheduler_profile.yaml".to_string())?;
+
+    // Create simulation
+    let mut sim = Simulation::new(
+        "./out_scheduler_profile".to_string(),
+        &init_config,
+        &init_nodes,
+        &init_trace,
+        &[(DEFAULT_SCHEDULER_NAME, &pipeline_config)],
+        123,
+        false,
+        false,
+        false,
+    )?;
+
+    // Work with simulation
+    sim.step_for_duration(50.0);
+
+    return Ok(());
+}
@@ -0,0 +1,45 @@
+use kuber_sim::*;
+
+/// This example shows how unschedulable pods are moved back to ActiveQ on cluster events
+/// (pod termination, taint removal) instead of waiting for unschedulable_queue_backoff_delay.
+fn main() -> Result<(), KuberSimError> {
+    // Read input
+    let mut init_config = InitConfig::from_yaml(&"./in_scheduler_queueing_hints.yaml".to_string())?;
+    let init_nodes = InitNodes::from_yaml(&"./in_scheduler_queueing_hints.yaml".to_string())?;
+    let init_trace = InitTrace::from_file(&"./in_scheduler_queueing_hints.yaml".to_string())?;
+
+    // Prepare input
+    init_config.prepare()?;
+
+    // Prepare scheduler pipeline config
+    let pipeline_config = PipelineConfig::new(
+        Box::new(ActiveQDefault::default()),
+        Box::new(BackOffQConstant::new(1.0)),
+        vec![
+            FilterRequestedResourcesAvailable.clone(),
+            FilterTaintsTolerations.clone(),
+        ],
+        vec![],
+        vec![Box::new(ScoreLeastAllocated::default())],
+        vec![ScoreNormalizeSkip.clone()],
+        vec![1],
+    );
+
+    // Create simulation
+    let mut sim = Simulation::new(
+        "./out_scheduler_queueing_hints".to_string(),
+        &init_config,
+        &init_nodes,
+        &init_trace,
+        &[(DEFAULT_SCHEDULER_NAME, &pipeline_config)],
+        123,
+        false,
+        false,
+        false,
+    )?;
+
+    // Work with simulation
+    sim.step_for_duration(50.0);
+
+    return Ok(());
+}
@@ -1,10 +1,14 @@
+use crate::api_server::disruption_budgets::DisruptionBudgets;
 use crate::api_server::events::*;
 use crate::common_imports::dsc;
 use crate::dp_api_server;
 use crate::objects::pod::PodPhase;
+use crate::scheduler::scheduler::DEFAULT_SCHEDULER_NAME;
 use crate::simulation::init_config::InitConfig;
+use crate::simulation::monitoring::Monitoring;
+use crate::simulation::uid_allocator::UidAllocator;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::rc::Rc;
 
 /// The component of the Kubernetes responsible for the interactions between the other components.
@@ -12,8 +16,8 @@ pub struct APIServer {
     /// DSLab-Core simulation context of API-Server.
     pub ctx: dsc::SimulationContext,
 
-    /// Scheduler simulation DSlab-Core Id.
-    scheduler_sim_id: dsc::Id,
+    /// Scheduler simulation DSlab-Core Id of each scheduler profile.
+    schedulers: BTreeMap<String, dsc::Id>, // BTreeMap<scheduler_name, scheduler_sim_id>
     /// Cluster-Autoscaler simulation DSlab-Core Id.
     ca_sim_id: Option<dsc::Id>,
     /// Horizontal-Pod-Autoscaler simulation DSlab-Core Id.
@@ -28,30 +32,48 @@ pub struct APIServer {
     kubelets: HashMap<u64, dsc::Id>, // HashMap<node_uid, kubelet_sim_id>
     /// Which pod belongs to which pod group.
     pod2group: HashMap<u64, u64>, // HashMap<pod_uid, group_uid>
+    /// Which pod is placed by which scheduler.
+    pod2scheduler: HashMap<u64, dsc::Id>, // HashMap<pod_uid, scheduler_sim_id>
+    /// PodDisruptionBudgets of pod groups. Shared with components which disrupt pods.
+    disruption_budgets: Rc<RefCell<DisruptionBudgets>>,
+    /// Records pod submission for pod lifecycle statistics.
+    monitoring: Rc<RefCell<Monitoring>>,
+    /// Uids of pods created from pod groups.
+    uids: Rc<RefCell<UidAllocator>>,
 }
 
 impl APIServer {
-    pub fn new(ctx: dsc::SimulationContext, init_config: Rc<RefCell<InitConfig>>) -> Self {
+    pub fn new(
+        ctx: dsc::SimulationContext,
+        init_config: Rc<RefCell<InitConfig>>,
+        disruption_budgets: Rc<RefCell<DisruptionBudgets>>,
+        monitoring: Rc<RefCell<Monitoring>>,
+        uids: Rc<RefCell<UidAllocator>>,
+    ) -> Self {
         Self {
             ctx,
-            scheduler_sim_id: dsc::Id::MAX,
+            schedulers: BTreeMap::new(),
             ca_sim_id: None,
             hpa_sim_id: None,
             vpa_sim_id: None,
             init_config,
             kubelets: HashMap::new(),
             pod2group: HashMap::new(),
+            pod2scheduler: HashMap::new(),
+            disruption_budgets,
+            monitoring,
+            uids,
         }
     }
 
     pub fn prepare(
         &mut self,
-        scheduler_sim_id: dsc::Id,
+        schedulers: BTreeMap<String, dsc::Id>,
         ca_sim_id: Option<dsc::Id>,
         hpa_sim_id: Option<dsc::Id>,
         vpa_sim_id: Option<dsc::Id>,
     ) {
-        self.scheduler_sim_id = scheduler_sim_id;
+        self.schedulers = schedulers;
         self.ca_sim_id = ca_sim_id;
         self.vpa_sim_id = vpa_sim_id;
         self.hpa_sim_id = hpa_sim_id;
@@ -77,12 +99,21 @@ impl APIServer {
         }
     }
 
-    fn notify_scheduler<T: dsc::EventData>(&self, event: T) {
-        self.ctx.emit(
-            event,
-            self.scheduler_sim_id,
-            self.init_config.borrow().network_delays.api2scheduler,
-        );
+    /// Notify scheduler which places the pod. Does nothing if pod has no scheduler.
+    fn notify_pod_scheduler<T: dsc::EventData>(&self, event: T, pod_uid: u64) {
+        if let Some(&scheduler_sim_id) = self.pod2scheduler.get(&pod_uid) {
+            self.ctx.emit(
+                event,
+                scheduler_sim_id,
+                self.init_config.borrow().network_delays.api2scheduler,
+            );
+        }
+    }
+
+    fn notify_all_schedulers<T: dsc::EventData + Clone>(&self, event: T, delay: f64) {
+        for &scheduler_sim_id in self.schedulers.values() {
+            self.ctx.emit(event.clone(), scheduler_sim_id, delay);
+        }
     }
 
     fn notify_kubelet<T: dsc::EventData>(&self, event: T, kubelet_sim_id: dsc::Id) {
@@ -137,10 +168,13 @@ impl dsc::EventHandler for APIServer {
                     }
                     None => {
                         // If kubelet turned off (not in routing) -> Notify scheduler returning this pod
-                        self.notify_scheduler(EventPodUpdateToScheduler {
+                        self.notify_pod_scheduler(
+                            EventPodUpdateToScheduler {
+                                pod_uid,
+                                current_phase: PodPhase::Pending,
+                            },
                             pod_uid,
-                            current_phase: PodPhase::Pending,
-                        });
+                        );
 
                         dp_api_server!(
                             "{:.3} api_server INNER EventUpdatePodFromScheduler pod_uid:{:?} node_uid:{:?} new_phase:{:?} NOT IN ROUTE",
@@ -161,17 +195,26 @@ impl dsc::EventHandler for APIServer {
                     self.ctx.time(), pod_uid, current_phase, current_cpu, current_memory
                 );
 
+                // Track pod availability for disruption budgets
+                let group_uid = *self.pod2group.get(&pod_uid).unwrap();
+                self.disruption_budgets
+                    .borrow_mut()
+                    .on_pod_phase(pod_uid, group_uid, current_phase.clone());
+
                 // Notify scheduler if pod not in Running phase
                 if current_phase != PodPhase::Running {
-                    self.notify_scheduler(EventPodUpdateToScheduler {
+                    self.notify_pod_scheduler(
+                        EventPodUpdateToScheduler {
+                            pod_uid,
+                            current_phase: current_phase.clone(),
+                        },
                         pod_uid,
-                        current_phase: current_phase.clone(),
-                    });
+                    );
                 }
 
                 // Post pod metrics to HPA and VPA
                 self.notify_hpa_and_vpa(EventPodMetricsPost {
-                    group_uid: *self.pod2group.get(&pod_uid).unwrap(),
+                    group_uid,
                     pod_uid,
                     current_phase: current_phase.clone(),
                     current_cpu,
@@ -179,7 +222,7 @@ impl dsc::EventHandler for APIServer {
                 });
             }
 
-            EventAddPod { pod } => {
+            EventAddPod { mut pod } => {
                 dp_api_server!("{:.3} api_server EventAddPod pod:{:?}", self.ctx.time(), pod);
 
                 // Check that pod was properly prepared
@@ -188,11 +231,28 @@ impl dsc::EventHandler for APIServer {
 
                 // Create mapping pod_uid to group_uid
                 self.pod2group.insert(pod.metadata.uid, pod.metadata.group_uid);
+                self.disruption_budgets
+                    .borrow_mut()
+                    .on_pod_added(pod.metadata.uid, pod.metadata.group_uid);
+                self.monitoring
+                    .borrow_mut()
+                    .api_on_pod_submitted(pod.metadata.uid, pod.metadata.group_uid);
+
+                // Pods without scheduler_name are placed by the default scheduler
+                if pod.spec.scheduler_name.is_empty() {
+                    pod.spec.scheduler_name = DEFAULT_SCHEDULER_NAME.to_string();
+                }
+
+                // Create mapping pod_uid to scheduler_sim_id.
+                // Scheduler names of the trace are checked by InitTrace::resolve_references.
+                let scheduler_sim_id = self.schedulers[&pod.spec.scheduler_name];
+                self.pod2scheduler.insert(pod.metadata.uid, scheduler_sim_id);
 
                 // Notify HPA and VPA
                 self.notify_hpa_and_vpa(EventAddPod { pod: pod.clone() });
                 // Notify scheduler
-                self.notify_scheduler(EventAddPod { pod });
+                let pod_uid = pod.metadata.uid;
+                self.notify_pod_scheduler(EventAddPod { pod }, pod_uid);
             }
 
             EvenAddPodGroup { pod_group } => {
@@ -202,6 +262,11 @@ impl dsc::EventHandler for APIServer {
                     pod_group
                 );
 
+                // Track group's disruption budget
+                if let Some(pdb) = &pod_group.pdb {
+                    self.disruption_budgets.borrow_mut().add_group(pod_group.group_uid, pdb);
+                }
+
                 // Notify HPA and VPA
                 self.notify_hpa_and_vpa(EvenAddPodGroup {
                     pod_group: pod_group.clone(),
@@ -212,7 +277,7 @@ impl dsc::EventHandler for APIServer {
                     // Get pod template
                     let mut pod = pod_group.pod.clone();
                     // Prepare pod template
-                    pod.prepare(pod_group.group_uid);
+                    pod.prepare(pod_group.group_uid, &mut self.uids.borrow_mut());
 
                     // Emit new pod
                     self.ctx.emit_self_now(EventAddPod { pod: pod.clone() });
@@ -222,8 +287,12 @@ impl dsc::EventHandler for APIServer {
             EventRemovePod { pod_uid } => {
                 dp_api_server!("{:.3} api_server EventRemovePod pod_uid:{:?}", self.ctx.time(), pod_uid);
 
+                if let Some(&group_uid) = self.pod2group.get(&pod_uid) {
+                    self.disruption_budgets.borrow_mut().on_pod_removed(pod_uid, group_uid);
+                }
+
                 // Notify scheduler
-                self.notify_scheduler(EventRemovePod { pod_uid });
+                self.notify_pod_scheduler(EventRemovePod { pod_uid }, pod_uid);
                 // Here, it is enough to notify the scheduler. Scheduler will notify kubelets.
             }
 
@@ -234,13 +303,17 @@ impl dsc::EventHandler for APIServer {
                     group_uid
                 );
 
+                self.disruption_budgets.borrow_mut().remove_group(group_uid);
+
                 // Notify HPA and VPA
                 self.notify_hpa_and_vpa(EventRemovePodGroup { group_uid });
-                // Notify Scheduler
-                self.notify_scheduler(EventRemovePodGroup { group_uid });
-                self.ctx.emit(
+                // Notify Schedulers
+                self.notify_all_schedulers(
+                    EventRemovePodGroup { group_uid },
+                    self.init_config.borrow().network_delays.api2scheduler,
+                );
+                self.notify_all_schedulers(
                     EventRemovePodGroup { group_uid },
-                    self.scheduler_sim_id,
                     self.init_config.borrow().network_delays.max_delay * 5.0,
                 );
                 // Here, it is enough to notify the scheduler. Scheduler will notify kubelets.
@@ -256,8 +329,37 @@ impl dsc::EventHandler for APIServer {
                 // Add routing [node_uid] -> [kubelet_sim_id]
                 self.kubelets.insert(node.metadata.uid, kubelet_sim_id);
 
-                // Notify scheduler
-                self.notify_scheduler(EventAddNode { kubelet_sim_id, node });
+                // Notify schedulers. They share node cache, but each one watches node updates.
+                self.notify_all_schedulers(
+                    EventAddNode { kubelet_sim_id, node },
+                    self.init_config.borrow().network_delays.api2scheduler,
+                );
+            }
+
+            EventUpdateNode {
+                node_uid,
+                labels,
+                taints,
+            } => {
+                dp_api_server!("{:.3} api_server EventUpdateNode node:{:?}", self.ctx.time(), node_uid);
+
+                if self.kubelets.contains_key(&node_uid) {
+                    // Notify schedulers. Kubelet does not use labels and taints.
+                    self.notify_all_schedulers(
+                        EventUpdateNode {
+                            node_uid,
+                            labels,
+                            taints,
+                        },
+                        self.init_config.borrow().network_delays.api2scheduler,
+                    );
+                } else {
+                    dp_api_server!(
+                        "{:.3} api_server INNER EventUpdateNode node:{:?} NOT IN ROUTE",
+                        self.ctx.time(),
+                        node_uid
+                    );
+                }
             }
 
             EventRemoveNode { node_uid } => {
@@ -266,8 +368,11 @@ impl dsc::EventHandler for APIServer {
                 // Remove node_uid from routing
                 match self.kubelets.remove(&node_uid) {
                     Some(kubelet_sim_id) => {
-                        // Notify scheduler
-                        self.notify_scheduler(EventRemoveNode { node_uid });
+                        // Notify schedulers
+                        self.notify_all_schedulers(
+                            EventRemoveNode { node_uid },
+                            self.init_config.borrow().network_delays.api2scheduler,
+                        );
                         // Notify kubelet
                         self.notify_kubelet(EventRemoveNode { node_uid }, kubelet_sim_id)
                     }
@@ -0,0 +1,171 @@
+use crate::objects::pod::PodPhase;
+use crate::objects::pod_disruption_budget::PodDisruptionBudget;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Debug, Clone, Default)]
+struct GroupBudgetState {
+    pdb: PodDisruptionBudget,
+    /// Pods of the group which are not finished.
+    pods: BTreeSet<u64>,
+    /// Pods of the group reported Running by kubelets.
+    healthy: BTreeSet<u64>,
+    /// Healthy pods which are already chosen for disruption, but are still reported Running.
+    disrupted: BTreeSet<u64>,
+}
+
+/// PodDisruptionBudgets of pod groups and the state of their pods, tracked by API-Server.
+/// It is shared with components which disrupt pods: schedulers, CA and VPA.
+#[derive(Debug, Default)]
+pub struct DisruptionBudgets {
+    groups: HashMap<u64, GroupBudgetState>, // HashMap<group_uid, state>
+}
+
+impl DisruptionBudgets {
+    pub fn new() -> Self {
+        Self { groups: HashMap::new() }
+    }
+
+    ////////////////// Updates from API-Server //////////////////
+
+    pub fn add_group(&mut self, group_uid: u64, pdb: &PodDisruptionBudget) {
+        self.groups.insert(
+            group_uid,
+            GroupBudgetState {
+                pdb: pdb.clone(),
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn remove_group(&mut self, group_uid: u64) {
+        self.groups.remove(&group_uid);
+    }
+
+    pub fn on_pod_added(&mut self, pod_uid: u64, group_uid: u64) {
+        if let Some(state) = self.groups.get_mut(&group_uid) {
+            state.pods.insert(pod_uid);
+        }
+    }
+
+    pub fn on_pod_phase(&mut self, pod_uid: u64, group_uid: u64, phase: PodPhase) {
+        let state = match self.groups.get_mut(&group_uid) {
+            Some(state) => state,
+            None => return,
+        };
+
+        match phase {
+            PodPhase::Running => {
+                state.healthy.insert(pod_uid);
+            }
+            PodPhase::Pending | PodPhase::Evicted | PodPhase::Preempted => {
+                // Pod will be rescheduled, so it is still expected
+                state.healthy.remove(&pod_uid);
+                state.disrupted.remove(&pod_uid);
+            }
+            PodPhase::Succeeded | PodPhase::Failed | PodPhase::Removed => {
+                self.on_pod_removed(pod_uid, group_uid);
+            }
+        }
+    }
+
+    pub fn on_pod_removed(&mut self, pod_uid: u64, group_uid: u64) {
+        if let Some(state) = self.groups.get_mut(&group_uid) {
+            state.pods.remove(&pod_uid);
+            state.healthy.remove(&pod_uid);
+            state.disrupted.remove(&pod_uid);
+        }
+    }
+
+    ////////////////// Queries from disrupting components //////////////////
+
+    /// How many more pods of group may be disrupted. None if group has no budget.
+    pub fn allowed_disruptions(&self, group_uid: u64) -> Option<u64> {
+        let state = self.groups.get(&group_uid)?;
+        let healthy = (state.healthy.len() - state.disrupted.len()) as u64;
+        return Some(state.pdb.allowed_disruptions(state.pods.len() as u64, healthy));
+    }
+
+    /// Whether all pods may be disrupted together without violating any budget.
+    pub fn is_disruption_allowed(&self, pods: &[(u64, u64)]) -> bool {
+        return self.count_violations(pods) == 0;
+    }
+
+    /// How many of pods disrupted together exceed the budgets of their groups.
+    pub fn count_violations(&self, pods: &[(u64, u64)]) -> u64 {
+        let mut requested: HashMap<u64, u64> = HashMap::new(); // HashMap<group_uid, disruption_count>
+        for &(pod_uid, group_uid) in pods.iter() {
+            let state = match self.groups.get(&group_uid) {
+                Some(state) => state,
+                None => continue,
+            };
+            // Not running or already disrupted pods do not decrease availability any more
+            if !state.healthy.contains(&pod_uid) || state.disrupted.contains(&pod_uid) {
+                continue;
+            }
+            *requested.entry(group_uid).or_default() += 1;
+        }
+
+        return requested
+            .iter()
+            .map(|(&group_uid, &count)| count.saturating_sub(self.allowed_disruptions(group_uid).unwrap()))
+            .sum();
+    }
+
+    /// Marks pods as disrupted, so they are not counted as available until kubelet reports them stopped.
+    pub fn record_disruption(&mut self, pods: &[(u64, u64)]) {
+        for &(pod_uid, group_uid) in pods.iter() {
+            if let Some(state) = self.groups.get_mut(&group_uid) {
+                if state.healthy.contains(&pod_uid) {
+                    state.disrupted.insert(pod_uid);
+                }
+            }
+        }
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disruption_budgets() {
+        let mut budgets = DisruptionBudgets::new();
+        budgets.add_group(1, &PodDisruptionBudget { min_available: Some(2), max_unavailable: None });
+        for pod_uid in 10..13 {
+            budgets.on_pod_added(pod_uid, 1);
+            budgets.on_pod_phase(pod_uid, 1, PodPhase::Running);
+        }
+
+        // Group without budget is never blocked
+        assert_eq!(budgets.allowed_disruptions(2), None);
+        assert!(budgets.is_disruption_allowed(&[(20, 2), (21, 2)]));
+
+        assert_eq!(budgets.allowed_disruptions(1), Some(1));
+        assert!(budgets.is_disruption_allowed(&[(10, 1)]));
+        assert!(!budgets.is_disruption_allowed(&[(10, 1), (11, 1)]));
+        assert_eq!(budgets.count_violations(&[(10, 1), (11, 1), (12, 1), (20, 2)]), 2);
+
+        // Disrupted pod is not available any more, but it may be disrupted again
+        budgets.record_disruption(&[(10, 1)]);
+        assert_eq!(budgets.allowed_disruptions(1), Some(0));
+        assert!(!budgets.is_disruption_allowed(&[(11, 1)]));
+        assert!(budgets.is_disruption_allowed(&[(10, 1)]));
+
+        // Preempted pod is restarted
+        budgets.on_pod_phase(10, 1, PodPhase::Preempted);
+        assert_eq!(budgets.allowed_disruptions(1), Some(0));
+        budgets.on_pod_phase(10, 1, PodPhase::Running);
+        assert_eq!(budgets.allowed_disruptions(1), Some(1));
+
+        // Pending pods are not available
+        budgets.on_pod_added(13, 1);
+        assert!(!budgets.is_disruption_allowed(&[(10, 1), (11, 1)]));
+        assert!(budgets.is_disruption_allowed(&[(10, 1), (13, 1)]));
+
+        budgets.on_pod_removed(10, 1);
+        assert_eq!(budgets.allowed_disruptions(1), Some(0));
+    }
+}
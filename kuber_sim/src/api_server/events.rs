@@ -2,6 +2,8 @@ use crate::common_imports::*;
 use crate::objects::node::Node;
 use crate::objects::pod::{Pod, PodPhase};
 use crate::objects::pod_group::PodGroup;
+use crate::scheduler::features::taints_tolerations::Taint;
+use std::collections::BTreeMap;
 
 /////////////////////////////////////////// API ////////////////////////////////////////////////////
 
@@ -41,6 +43,18 @@ pub struct EventAddNode {
     pub node: Node,
 }
 
+// [Emit]:      { Init } -> Api
+// [Consume]:   Api -> { Scheduler }
+/// Replaces labels and (or) taints of node. None fields are left unchanged.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EventUpdateNode {
+    pub node_uid: u64,
+    #[serde(default)]
+    pub labels: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    pub taints: Option<Vec<Taint>>,
+}
+
 // [Emit]:      { CA } -> Api
 // [Consume]:   Api -> { Kubelet | Scheduler }
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -119,6 +133,12 @@ pub struct EventKubeletNextChange {
     pub pod_uid: u64,
 }
 
+/////////////////////////////////////////// Monitoring /////////////////////////////////////////////
+
+// [Emit self]:      Monitoring
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EventSampleNodeUtilization {}
+
 ///////////////////////////////////////// CA ///////////////////////////////////////////////////////
 
 // [Emit self]:      { CA }
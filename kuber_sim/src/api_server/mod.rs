@@ -1,2 +1,3 @@
 pub mod api;
+pub mod disruption_budgets;
 pub mod events;
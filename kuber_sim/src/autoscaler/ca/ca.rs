@@ -1,15 +1,18 @@
+use crate::api_server::disruption_budgets::DisruptionBudgets;
 use crate::api_server::events::*;
 use crate::common_imports::*;
 use crate::dp_ca;
 use crate::kubelet::kubelet::Kubelet;
 use crate::objects::node::Node;
 use crate::objects::node_group::NodeGroup;
+use crate::scheduler::cache::SchedulerCache;
 use crate::scheduler::scheduler::Scheduler;
 use crate::simulation::init_config::InitConfig;
 use crate::simulation::init_nodes::InitNodes;
 use crate::simulation::monitoring::Monitoring;
+use crate::simulation::uid_allocator::UidAllocator;
 use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::rc::Rc;
 
 /// The component of the Kubernetes responsible for cluster autoscaling.
@@ -18,8 +21,16 @@ pub struct CA {
     ctx: dsc::SimulationContext,
     /// Configuration constants of components in simulation.
     init_config: Rc<RefCell<InitConfig>>,
-    /// Scheduler.
-    scheduler: Rc<RefCell<Scheduler>>,
+    /// Schedulers of all profiles.
+    schedulers: Vec<Rc<RefCell<Scheduler>>>,
+    /// Cluster state shared by schedulers.
+    scheduler_cache: Rc<RefCell<SchedulerCache>>,
+    /// PodDisruptionBudgets tracked by API-Server.
+    disruption_budgets: Rc<RefCell<DisruptionBudgets>>,
+    /// Monitoring of the simulation.
+    monitoring: Rc<RefCell<Monitoring>>,
+    /// Uids of nodes added by CA.
+    uids: Rc<RefCell<UidAllocator>>,
     /// API-Server simulation DSlab-Core Id.
     api_sim_id: dsc::Id,
 
@@ -34,6 +45,8 @@ pub struct CA {
     used_nodes: BTreeMap<u64, (dsc::Id, Rc<RefCell<Kubelet>>, u64)>, // BTreeMap<node_uid, (kubelet_sim_id, kubelet, group_uid)>
     /// Node candidates on removal
     low_utilization: BTreeMap<u64, u64>, // BTreeMap<node_uid, cycle_counter>
+    /// Node candidates on removal which are kept because of PodDisruptionBudget
+    blocked_nodes: BTreeSet<u64>, // BTreeSet<node_uid>
 }
 
 impl CA {
@@ -42,14 +55,21 @@ impl CA {
         ctx: dsc::SimulationContext,
         init_config: Rc<RefCell<InitConfig>>,
         init_nodes: Rc<RefCell<InitNodes>>,
-        scheduler: Rc<RefCell<Scheduler>>,
+        schedulers: Vec<Rc<RefCell<Scheduler>>>,
+        scheduler_cache: Rc<RefCell<SchedulerCache>>,
+        disruption_budgets: Rc<RefCell<DisruptionBudgets>>,
         monitoring: Rc<RefCell<Monitoring>>,
+        uids: Rc<RefCell<UidAllocator>>,
         api_sim_id: dsc::Id,
     ) -> Self {
         let mut ca = Self {
             ctx,
             init_config: init_config.clone(),
-            scheduler: scheduler.clone(),
+            schedulers,
+            scheduler_cache,
+            disruption_budgets,
+            monitoring: monitoring.clone(),
+            uids: uids.clone(),
             api_sim_id,
 
             // CA is created in turned off state
@@ -60,6 +80,7 @@ impl CA {
             free_nodes_by_group: BTreeMap::new(),
             used_nodes: BTreeMap::new(),
             low_utilization: BTreeMap::new(),
+            blocked_nodes: BTreeSet::new(),
         };
 
         // Prepare kubelet pool from nodes_config ca nodes
@@ -93,7 +114,7 @@ impl CA {
                 .get_mut(&group.group_uid)
                 .unwrap()
                 .node
-                .prepare(group.group_uid);
+                .prepare(group.group_uid, &mut uids.borrow_mut());
         }
 
         return ca;
@@ -134,12 +155,14 @@ impl CA {
             // If utilization is not low -> remove its cycle count and skip
             if cpu > min_cpu || memory > min_memory {
                 self.low_utilization.remove(&node_uid);
+                self.blocked_nodes.remove(&node_uid);
                 continue;
             }
 
             // If node is not in used -> remove its cycle count and skip
             if !self.used_nodes.contains_key(&node_uid) {
                 self.low_utilization.remove(&node_uid);
+                self.blocked_nodes.remove(&node_uid);
                 continue;
             }
 
@@ -149,6 +172,21 @@ impl CA {
 
             // If cycle count more than remove threshold
             if *cycles >= self.init_config.borrow().ca.remove_node_cycle_delay {
+                // Pods of removed node are disrupted, so node is kept while budgets do not allow it.
+                // Blocked removal is counted once until it is done or abandoned
+                if !self.try_disrupt_node_pods(node_uid) {
+                    dp_ca!(
+                        "{:.3} ca EventRemoveNode node_uid:{:?} blocked by PodDisruptionBudget",
+                        self.ctx.time(),
+                        node_uid
+                    );
+                    if self.blocked_nodes.insert(node_uid) {
+                        self.monitoring.borrow_mut().on_disruption_blocked();
+                    }
+                    continue;
+                }
+                self.blocked_nodes.remove(&node_uid);
+
                 dp_ca!(
                     "{:.3} ca Issues EventRemoveNode node_uid:{:?}",
                     self.ctx.time(),
@@ -182,7 +220,7 @@ impl CA {
                 group.amount -= 1;
 
                 // Prepare node
-                node.prepare(group_uid);
+                node.prepare(group_uid, &mut self.uids.borrow_mut());
 
                 // Take kubelet from pool
                 let (kubelet_sim_id, kubelet) = self.kubelet_pool.pop().unwrap();
@@ -195,6 +233,9 @@ impl CA {
                 self.used_nodes
                     .insert(node.metadata.uid, (kubelet_sim_id, kubelet, group_uid));
 
+                // Node is paid for since request
+                self.monitoring.borrow_mut().ca_on_node_requested(&node);
+
                 // Emit AddNode event
                 dp_ca!(
                     "{:.3} ca node:{:?} added -> cluster",
@@ -210,6 +251,27 @@ impl CA {
         }
     }
 
+    /// Records disruption of all pods of node if PodDisruptionBudgets allow it.
+    pub fn try_disrupt_node_pods(&self, node_uid: u64) -> bool {
+        let scheduler_cache = self.scheduler_cache.borrow();
+        let pods: Vec<(u64, u64)> = match scheduler_cache.nodes.get(&node_uid) {
+            Some(node) => node
+                .status
+                .pods
+                .iter()
+                .map(|uid| (*uid, scheduler_cache.running_pods.get(uid).unwrap().metadata.group_uid))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let mut disruption_budgets = self.disruption_budgets.borrow_mut();
+        if !disruption_budgets.is_disruption_allowed(&pods) {
+            return false;
+        }
+        disruption_budgets.record_disruption(&pods);
+        return true;
+    }
+
     pub fn update_metrics(&mut self) {
         let available_nodes: Vec<NodeGroup> = self
             .free_nodes_by_group
@@ -217,13 +279,14 @@ impl CA {
             .filter_map(|(_, group)| if group.amount > 0 { Some(group.clone()) } else { None })
             .collect();
 
-        // Get view on scheduler
-        let scheduler = self.scheduler.borrow();
+        // Get view on schedulers
+        let schedulers: Vec<_> = self.schedulers.iter().map(|scheduler| scheduler.borrow()).collect();
+        let scheduler_cache = self.scheduler_cache.borrow();
 
         // For pending pods look available node which may help
         let mut may_help: Option<u64> = None;
         let mut pending_pod_count = 0;
-        for (_, pod) in scheduler.pending_pods.iter() {
+        for pod in schedulers.iter().flat_map(|scheduler| scheduler.pending_pods.values()) {
             // Only pods which cannot be scheduled due to insufficient resources on nodes
             if !pod.status.cluster_resource_starvation {
                 continue;
@@ -247,7 +310,7 @@ impl CA {
         // Count utilization for requested nodes
         let mut used_nodes_utilization: Vec<(u64, f64, f64)> = Vec::with_capacity(self.used_nodes.len());
         for node_uid in self.used_nodes.keys() {
-            let node = scheduler.nodes.get(node_uid);
+            let node = scheduler_cache.nodes.get(node_uid);
             if node.is_some() {
                 let spec = node.unwrap().spec.clone();
                 let cpu: f64 = ((spec.installed_cpu - spec.available_cpu) as f64) / (spec.installed_cpu as f64);
@@ -257,7 +320,8 @@ impl CA {
                 used_nodes_utilization.push((*node_uid, cpu, memory));
             }
         }
-        drop(scheduler);
+        drop(scheduler_cache);
+        drop(schedulers);
 
         // Process scheduler metrics
         self.process_metrics(pending_pod_count, &used_nodes_utilization, may_help);
@@ -311,6 +375,7 @@ impl dsc::EventHandler for CA {
                 self.free_nodes_by_group.get_mut(&group_uid).unwrap().amount += 1;
                 // Remove node's utilization info
                 self.low_utilization.remove(&node_uid);
+                self.blocked_nodes.remove(&node_uid);
             }
         });
     }
@@ -4,6 +4,8 @@ use crate::common_imports::*;
 use crate::dp_hpa;
 use crate::objects::pod::Pod;
 use crate::simulation::init_config::InitConfig;
+use crate::simulation::monitoring::Monitoring;
+use crate::simulation::uid_allocator::UidAllocator;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -16,6 +18,10 @@ pub struct HPA {
     init_config: Rc<RefCell<InitConfig>>,
     /// API-Server simulation DSlab-Core Id.
     api_sim_id: dsc::Id,
+    /// Monitoring of the simulation.
+    monitoring: Rc<RefCell<Monitoring>>,
+    /// Uids of pods created by HPA.
+    uids: Rc<RefCell<UidAllocator>>,
 
     /// Is HPA turned on
     is_turned_on: bool,
@@ -25,11 +31,19 @@ pub struct HPA {
 }
 
 impl HPA {
-    pub fn new(ctx: dsc::SimulationContext, init_config: Rc<RefCell<InitConfig>>, api_sim_id: dsc::Id) -> Self {
+    pub fn new(
+        ctx: dsc::SimulationContext,
+        init_config: Rc<RefCell<InitConfig>>,
+        api_sim_id: dsc::Id,
+        monitoring: Rc<RefCell<Monitoring>>,
+        uids: Rc<RefCell<UidAllocator>>,
+    ) -> Self {
         Self {
             ctx,
             init_config: init_config.clone(),
             api_sim_id,
+            monitoring,
+            uids,
 
             // HPA is created in turned off state
             is_turned_on: false,
@@ -68,6 +82,15 @@ impl HPA {
             let cpu = info.numerator_cpu / (info.running_pod_count as f64);
             let memory = info.numerator_memory / (info.running_pod_count as f64);
 
+            // Report what HPA sees (utilization is undefined without running pods)
+            let is_running = info.running_pod_count > 0;
+            self.monitoring.borrow_mut().hpa_on_group_metrics(
+                *group_uid,
+                group_size,
+                is_running.then_some(cpu),
+                is_running.then_some(memory),
+            );
+
             // Locate current HPA profile
             let profile = &info.hpa_profile;
 
@@ -82,7 +105,7 @@ impl HPA {
                 // Locate pod template
                 let mut pod = info.pod_template.clone();
                 // Prepare pod
-                pod.prepare(*group_uid);
+                pod.prepare(*group_uid, &mut self.uids.borrow_mut());
                 // Emit AddPod event
                 self.send_add_pod(pod);
             }
@@ -121,7 +144,7 @@ impl HPA {
                 // Locate pod template
                 let mut pod = info.pod_template.clone();
                 // Prepare pod
-                pod.prepare(*group_uid);
+                pod.prepare(*group_uid, &mut self.uids.borrow_mut());
                 // Emit AddPod event
                 self.send_add_pod(pod);
             }
@@ -227,7 +250,7 @@ impl dsc::EventHandler for HPA {
                 // Locate group info
                 let group_info = self.managed_groups.entry(pod_group.group_uid).or_default();
                 // Update group info
-                group_info.update_with_new_group(&pod_group);
+                group_info.update_with_new_group(&pod_group, &mut self.uids.borrow_mut());
             }
 
             EventRemovePodGroup { group_uid } => {
@@ -239,6 +262,7 @@ impl dsc::EventHandler for HPA {
 
                 // Remove group from managed
                 self.managed_groups.remove(&group_uid);
+                self.monitoring.borrow_mut().autoscaler_on_group_removed(group_uid);
             }
         });
     }
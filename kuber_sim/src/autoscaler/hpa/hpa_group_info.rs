@@ -2,6 +2,7 @@ use crate::autoscaler::hpa::hpa_profile::HPAProfile;
 use crate::dp_hpa;
 use crate::objects::pod::{Pod, PodPhase};
 use crate::objects::pod_group::PodGroup;
+use crate::simulation::uid_allocator::UidAllocator;
 use std::collections::{BTreeSet, HashMap};
 
 #[derive(Debug, Default)]
@@ -25,14 +26,14 @@ pub struct HPAGroupInfo {
 }
 
 impl HPAGroupInfo {
-    pub fn update_with_new_group(&mut self, pod_group: &PodGroup) {
+    pub fn update_with_new_group(&mut self, pod_group: &PodGroup, uids: &mut UidAllocator) {
         // It is truly new group
         assert!(self.alive_uids.is_empty());
 
         // Update pod template
         self.pod_template = pod_group.pod.clone();
         // Prepare pod template
-        self.pod_template.prepare(pod_group.group_uid);
+        self.pod_template.prepare(pod_group.group_uid, uids);
 
         // Update group HPA profile
         self.hpa_profile = pod_group.hpa_profile.clone().unwrap();
@@ -1,3 +1,5 @@
+use crate::error::{parse_field, KuberSimError};
+
 #[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct HPAProfile {
     /// Min allowed group size
@@ -15,34 +17,53 @@ pub struct HPAProfile {
 }
 
 impl std::str::FromStr for HPAProfile {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "<u64>;<u64>;<f64>;<f64>;<f64>;<f64>"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let data: Vec<&str> = s.split(';').collect();
-        sim_assert!(data.len() == 6, "Invalid number of arguments in HPAProfile.");
+        sim_ensure!(data.len() == 6, "HPAProfile", "Expected 6 values separated by ';'");
 
         Ok(Self {
-            min_size: sim_ok!(str::parse(data[0]), "HPAProfile[0]. Invalid value for min_size."),
-            max_size: sim_ok!(str::parse(data[1]), "HPAProfile[1]. Invalid value for max_size."),
-            scale_down_mean_cpu_fraction: sim_ok!(
-                str::parse(data[2]),
-                "HPAProfile[2]. Invalid value for scale_down_mean_cpu_fraction."
-            ),
-            scale_down_mean_memory_fraction: sim_ok!(
-                str::parse(data[3]),
-                "HPAProfile[3]. Invalid value for scale_down_mean_memory_fraction."
-            ),
-            scale_up_mean_cpu_fraction: sim_ok!(
-                str::parse(data[4]),
-                "HPAProfile[4]. Invalid value for scale_up_mean_cpu_fraction."
-            ),
-            scale_up_mean_memory_fraction: sim_ok!(
-                str::parse(data[5]),
-                "HPAProfile[5]. Invalid value for scale_up_mean_memory_fraction."
-            ),
+            min_size: parse_field(data[0], "HPAProfile.min_size")?,
+            max_size: parse_field(data[1], "HPAProfile.max_size")?,
+            scale_down_mean_cpu_fraction: parse_field(data[2], "HPAProfile.scale_down_mean_cpu_fraction")?,
+            scale_down_mean_memory_fraction: parse_field(data[3], "HPAProfile.scale_down_mean_memory_fraction")?,
+            scale_up_mean_cpu_fraction: parse_field(data[4], "HPAProfile.scale_up_mean_cpu_fraction")?,
+            scale_up_mean_memory_fraction: parse_field(data[5], "HPAProfile.scale_up_mean_memory_fraction")?,
         })
     }
 }
 
+impl HPAProfile {
+    pub fn prepare(&self) -> Result<(), KuberSimError> {
+        sim_ensure!(
+            self.min_size <= self.max_size,
+            "HPAProfile.min_size",
+            "must be <= HPAProfile.max_size"
+        );
+        sim_ensure!(
+            self.scale_down_mean_cpu_fraction >= 0.0,
+            "HPAProfile.scale_down_mean_cpu_fraction",
+            "must be >= 0"
+        );
+        sim_ensure!(
+            self.scale_down_mean_memory_fraction >= 0.0,
+            "HPAProfile.scale_down_mean_memory_fraction",
+            "must be >= 0"
+        );
+        sim_ensure!(
+            self.scale_down_mean_cpu_fraction <= self.scale_up_mean_cpu_fraction,
+            "HPAProfile.scale_down_mean_cpu_fraction",
+            "must be <= HPAProfile.scale_up_mean_cpu_fraction"
+        );
+        sim_ensure!(
+            self.scale_down_mean_memory_fraction <= self.scale_up_mean_memory_fraction,
+            "HPAProfile.scale_down_mean_memory_fraction",
+            "must be <= HPAProfile.scale_up_mean_memory_fraction"
+        );
+        return Ok(());
+    }
+}
+
 impl Eq for HPAProfile {}
@@ -1,8 +1,11 @@
+use crate::api_server::disruption_budgets::DisruptionBudgets;
 use crate::api_server::events::*;
 use crate::autoscaler::vpa::vpa_group_info::VPAGroupInfo;
 use crate::common_imports::*;
 use crate::dp_vpa;
 use crate::simulation::init_config::InitConfig;
+use crate::simulation::monitoring::Monitoring;
+use crate::simulation::uid_allocator::UidAllocator;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -15,6 +18,12 @@ pub struct VPA {
     init_config: Rc<RefCell<InitConfig>>,
     /// API-Server simulation DSlab-Core Id.
     api_sim_id: dsc::Id,
+    /// PodDisruptionBudgets tracked by API-Server.
+    disruption_budgets: Rc<RefCell<DisruptionBudgets>>,
+    /// Monitoring of the simulation.
+    monitoring: Rc<RefCell<Monitoring>>,
+    /// Uids of pods created by VPA.
+    uids: Rc<RefCell<UidAllocator>>,
 
     /// Is VPA turned on
     is_turned_on: bool,
@@ -24,11 +33,21 @@ pub struct VPA {
 }
 
 impl VPA {
-    pub fn new(ctx: dsc::SimulationContext, init_config: Rc<RefCell<InitConfig>>, api_sim_id: dsc::Id) -> Self {
+    pub fn new(
+        ctx: dsc::SimulationContext,
+        init_config: Rc<RefCell<InitConfig>>,
+        api_sim_id: dsc::Id,
+        disruption_budgets: Rc<RefCell<DisruptionBudgets>>,
+        monitoring: Rc<RefCell<Monitoring>>,
+        uids: Rc<RefCell<UidAllocator>>,
+    ) -> Self {
         Self {
             ctx,
             init_config: init_config.clone(),
             api_sim_id,
+            disruption_budgets,
+            monitoring,
+            uids,
 
             // VPA is created in turned off state
             is_turned_on: false,
@@ -104,7 +123,14 @@ impl VPA {
                 pod.spec.request_cpu = request_cpu;
                 pod.spec.request_memory = request_memory;
                 // Prepare pod
-                pod.prepare(*group_uid);
+                pod.prepare(*group_uid, &mut self.uids.borrow_mut());
+                self.monitoring.borrow_mut().vpa_on_recommendation(
+                    *group_uid,
+                    request_cpu,
+                    request_memory,
+                    limit_cpu,
+                    limit_memory,
+                );
 
                 // Emit AddPod event
                 dp_vpa!("VPA emit AddPod pod:{:?}", pod);
@@ -127,9 +153,24 @@ impl VPA {
 
                 // If request and usage differs not too much -> skip
                 if !pod_info.need_reschedule(&self.init_config.borrow(), &profile, self.ctx.time()) {
+                    pod_info.is_disruption_blocked = false;
                     continue;
                 }
 
+                // If PodDisruptionBudget does not allow eviction -> try next time.
+                // Blocked eviction is counted once until it is done or not needed any more
+                let pods = [(pod_uid, *group_uid)];
+                if !self.disruption_budgets.borrow().is_disruption_allowed(&pods) {
+                    dp_vpa!("VPA RemovePod pod_uid:{:?} blocked by PodDisruptionBudget", pod_uid);
+                    if !pod_info.is_disruption_blocked {
+                        pod_info.is_disruption_blocked = true;
+                        self.monitoring.borrow_mut().on_disruption_blocked();
+                    }
+                    continue;
+                }
+                pod_info.is_disruption_blocked = false;
+                self.disruption_budgets.borrow_mut().record_disruption(&pods);
+
                 // Emit RemovePod event
                 dp_vpa!("VPA emit RemovePod pod_uid:{:?}", pod_uid);
                 self.ctx.emit(
@@ -152,7 +193,14 @@ impl VPA {
                 pod.spec.request_cpu = request_cpu;
                 pod.spec.request_memory = request_memory;
                 // Prepare pod
-                pod.prepare(*group_uid);
+                pod.prepare(*group_uid, &mut self.uids.borrow_mut());
+                self.monitoring.borrow_mut().vpa_on_recommendation(
+                    *group_uid,
+                    request_cpu,
+                    request_memory,
+                    limit_cpu,
+                    limit_memory,
+                );
 
                 // Emit AddPod event
                 dp_vpa!("VPA emit AddPod pod:{:?}", pod);
@@ -274,7 +322,7 @@ impl dsc::EventHandler for VPA {
                 // Locate pod's group info
                 let group_info = self.managed_groups.entry(pod_group.group_uid).or_default();
                 // Update group info
-                group_info.update_with_new_group(&pod_group);
+                group_info.update_with_new_group(&pod_group, &mut self.uids.borrow_mut());
             }
 
             EventRemovePodGroup { group_uid } => {
@@ -286,6 +334,7 @@ impl dsc::EventHandler for VPA {
 
                 // Remove managed group
                 self.managed_groups.remove(&group_uid);
+                self.monitoring.borrow_mut().autoscaler_on_group_removed(group_uid);
             }
         });
     }
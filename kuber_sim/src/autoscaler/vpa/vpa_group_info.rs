@@ -3,6 +3,7 @@ use crate::autoscaler::vpa::vpa_profile::VPAProfile;
 use crate::objects::pod::{Pod, PodPhase};
 use crate::objects::pod_group::PodGroup;
 use crate::simulation::init_config::InitConfig;
+use crate::simulation::uid_allocator::UidAllocator;
 use std::collections::HashMap;
 
 #[derive(Debug, Default)]
@@ -17,14 +18,14 @@ pub struct VPAGroupInfo {
 }
 
 impl VPAGroupInfo {
-    pub fn update_with_new_group(&mut self, pod_group: &PodGroup) {
+    pub fn update_with_new_group(&mut self, pod_group: &PodGroup, uids: &mut UidAllocator) {
         // It is truly new group
         assert!(self.uids.is_empty());
 
         // Set pod template if necessary
         self.pod_template = pod_group.pod.clone();
         // Prepare template
-        self.pod_template.prepare(pod_group.group_uid);
+        self.pod_template.prepare(pod_group.group_uid, uids);
 
         // Set local VPA profile
         self.vpa_profile = pod_group.vpa_profile.clone().unwrap();
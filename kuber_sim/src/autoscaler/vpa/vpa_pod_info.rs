@@ -24,6 +24,8 @@ pub struct VPAPodInfo {
     pub hist_memory: Histogram,
 
     pub is_rescheduled: bool,
+    /// Reschedule is needed, but PodDisruptionBudget does not allow it.
+    pub is_disruption_blocked: bool,
 }
 
 impl VPAPodInfo {
@@ -44,6 +46,7 @@ impl VPAPodInfo {
             hist_memory: Histogram::new(7, 10).unwrap(),
 
             is_rescheduled: false,
+            is_disruption_blocked: false,
         }
     }
 
@@ -1,3 +1,5 @@
+use crate::error::{parse_field, KuberSimError};
+
 #[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct VPAProfile {
     /// Min allowed cpu value when down-scale pod cpu request
@@ -12,26 +14,42 @@ pub struct VPAProfile {
 }
 
 impl std::str::FromStr for VPAProfile {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "<i64>;<i64>;<i64>;<i64>"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let data: Vec<&str> = s.split(';').collect();
-        sim_assert!(data.len() == 4, "Invalid number of arguments in VPAProfile.");
+        sim_ensure!(data.len() == 4, "VPAProfile", "Expected 4 values separated by ';'");
 
         Ok(Self {
-            min_allowed_cpu: sim_ok!(str::parse(data[0]), "VPAProfile[0]. Invalid value for min_allowed_cpu."),
-            min_allowed_memory: sim_ok!(
-                str::parse(data[1]),
-                "VPAProfile[1]. Invalid value for min_allowed_memory."
-            ),
-            max_allowed_cpu: sim_ok!(str::parse(data[2]), "VPAProfile[2]. Invalid value for max_allowed_cpu."),
-            max_allowed_memory: sim_ok!(
-                str::parse(data[3]),
-                "VPAProfile[3]. Invalid value for max_allowed_memory."
-            ),
+            min_allowed_cpu: parse_field(data[0], "VPAProfile.min_allowed_cpu")?,
+            min_allowed_memory: parse_field(data[1], "VPAProfile.min_allowed_memory")?,
+            max_allowed_cpu: parse_field(data[2], "VPAProfile.max_allowed_cpu")?,
+            max_allowed_memory: parse_field(data[3], "VPAProfile.max_allowed_memory")?,
         })
     }
 }
 
+impl VPAProfile {
+    pub fn prepare(&self) -> Result<(), KuberSimError> {
+        sim_ensure!(
+            self.min_allowed_cpu <= self.max_allowed_cpu,
+            "VPAProfile.min_allowed_cpu",
+            "must be <= VPAProfile.max_allowed_cpu"
+        );
+        sim_ensure!(
+            self.min_allowed_memory <= self.max_allowed_memory,
+            "VPAProfile.min_allowed_memory",
+            "must be <= VPAProfile.max_allowed_memory"
+        );
+        sim_ensure!(self.min_allowed_cpu > 0, "VPAProfile.min_allowed_cpu", "must be > 0");
+        sim_ensure!(
+            self.min_allowed_memory > 0,
+            "VPAProfile.min_allowed_memory",
+            "must be > 0"
+        );
+        return Ok(());
+    }
+}
+
 impl Eq for VPAProfile {}
@@ -0,0 +1,147 @@
+use crate::simulation::init_trace::InitTrace;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Error in simulation input: unreadable file, invalid trace line or invalid value of some field.
+/// Keeps where the error was found, as far as it is known.
+#[derive(Clone, PartialEq, Eq)]
+pub struct KuberSimError {
+    /// Input file.
+    pub file: Option<String>,
+    /// Line of input file, starting from 1.
+    pub line: Option<usize>,
+    /// Invalid field as <Type>.<field> (e.g. ConfigCA.self_update_period), possibly prefixed by its position in input.
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl KuberSimError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            file: None,
+            line: None,
+            field: None,
+            message: message.into(),
+        }
+    }
+
+    /// Invalid value of field.
+    pub fn invalid(field: impl Into<String>, message: impl Into<String>) -> Self {
+        let mut error = KuberSimError::new(message);
+        error.field = Some(field.into());
+        return error;
+    }
+
+    /// Sets file if it is not known yet.
+    pub fn with_file(mut self, file: &str) -> Self {
+        if self.file.is_none() {
+            self.file = Some(file.to_string());
+        }
+        return self;
+    }
+
+    /// Sets line if it is not known yet.
+    pub fn with_line(mut self, line: usize) -> Self {
+        if self.line.is_none() {
+            self.line = Some(line);
+        }
+        return self;
+    }
+
+    /// Prefixes field with position of its object in input, e.g. "trace[3]".
+    pub fn with_parent(mut self, parent: &str) -> Self {
+        self.field = match self.field {
+            Some(field) => Some(format!("{}.{}", parent, field)),
+            None => Some(parent.to_string()),
+        };
+        return self;
+    }
+
+    pub(crate) fn from_io(file: &str, error: std::io::Error) -> Self {
+        return KuberSimError::new(format!("Unable to read file: {}", error)).with_file(file);
+    }
+
+    pub(crate) fn from_yaml(file: &str, error: serde_yaml::Error) -> Self {
+        let mut result = KuberSimError::new(error.to_string()).with_file(file);
+        result.line = error.location().map(|location| location.line());
+        return result;
+    }
+}
+
+impl Display for KuberSimError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{}:{}: ", file, line)?,
+            (Some(file), None) => write!(f, "{}: ", file)?,
+            (None, Some(line)) => write!(f, "line {}: ", line)?,
+            (None, None) => {}
+        }
+        if let Some(field) = &self.field {
+            write!(f, "{}: ", field)?;
+        }
+        return write!(f, "{}", self.message);
+    }
+}
+
+/// Same as Display, so main() returning Result<(), KuberSimError> prints readable error.
+impl std::fmt::Debug for KuberSimError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        return Display::fmt(self, f);
+    }
+}
+
+impl std::error::Error for KuberSimError {}
+
+/////////////////////////////////////////// Parse helpers //////////////////////////////////////////
+
+/// Parses value of field.
+pub(crate) fn parse_field<T>(s: &str, field: &str) -> Result<T, KuberSimError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    return str::parse(s.trim()).map_err(|e| KuberSimError::invalid(field, format!("Invalid value '{}': {}", s, e)));
+}
+
+/// Splits s at the first delimiter, which must follow value of field.
+pub(crate) fn split_field<'a>(s: &'a str, delimiter: char, field: &str) -> Result<(&'a str, &'a str), KuberSimError> {
+    return s
+        .split_once(delimiter)
+        .ok_or_else(|| KuberSimError::invalid(field, format!("Expected '{}' after '{}'", delimiter, s)));
+}
+
+/// Index of the bracket closing section "{...}" of field which starts at start.
+pub(crate) fn section_end(s: &str, start: usize, field: &str) -> Result<usize, KuberSimError> {
+    let end = match s.get(start..) {
+        Some(rest) if rest.starts_with('{') => InitTrace::find_matching_bracket(s, start),
+        _ => None,
+    };
+    return end.ok_or_else(|| KuberSimError::invalid(field, format!("Expected {{...}} section in '{}'", s)));
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(KuberSimError::new("Unexpected").to_string(), "Unexpected");
+        assert_eq!(KuberSimError::invalid("Constant.cpu", "Invalid value 'x'").with_line(3).with_file("trace.csv").to_string(), "trace.csv:3: Constant.cpu: Invalid value 'x'");
+        assert_eq!(KuberSimError::invalid("PodGroup.min_member", "must be <= pod_count").with_parent("trace[2]").with_file("in.yaml").to_string(), "in.yaml: trace[2].PodGroup.min_member: must be <= pod_count");
+    }
+
+    #[test]
+    fn test_parse_helpers() {
+        assert_eq!(parse_field::<i64>("12", "Constant.cpu"), Ok(12));
+        assert_eq!(parse_field::<i64>("x", "Constant.cpu").unwrap_err().field, Some("Constant.cpu".to_string()));
+        assert_eq!(split_field("1;2;3", ';', "Constant.cpu"), Ok(("1", "2;3")));
+        assert!(split_field("1", ';', "Constant.cpu").is_err());
+        assert_eq!(section_end("{1;{2}};{}", 0, "Pod"), Ok(6));
+        assert_eq!(section_end("{1;{2}};{}", 8, "Pod"), Ok(9));
+        assert!(section_end("{1;{2}};{}", 10, "Pod").is_err());
+        assert!(section_end("{1;{2}", 0, "Pod").is_err());
+    }
+}
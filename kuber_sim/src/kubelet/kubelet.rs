@@ -100,6 +100,7 @@ impl Kubelet {
 
         // Send pod update to Api-server
         self.send_pod_update_zero_usage(pod_uid, PodPhase::Running);
+        self.monitoring.borrow_mut().kubelet_on_pod_started(pod_uid);
 
         // Get pod's load
         let mut load = pod.spec.load.clone();
@@ -117,7 +118,9 @@ impl Kubelet {
 
         // Consume node resources
         self.node.consume(cpu, memory);
-        self.monitoring.borrow_mut().kubelet_on_pod_placed(cpu, memory);
+        self.monitoring
+            .borrow_mut()
+            .kubelet_on_pod_placed(self.node.metadata.uid, cpu, memory);
 
         // Store pod
         self.pods.insert(pod_uid, pod.clone());
@@ -153,7 +156,7 @@ impl Kubelet {
         self.node.restore(*prev_cpu, *prev_memory);
         self.monitoring
             .borrow_mut()
-            .kubelet_on_pod_unplaced(*prev_cpu, *prev_memory);
+            .kubelet_on_pod_unplaced(self.node.metadata.uid, *prev_cpu, *prev_memory);
 
         // If pod finished -> pod Succeeded
         if is_finished {
@@ -175,7 +178,9 @@ impl Kubelet {
 
         // Consume node resources
         self.node.consume(new_cpu, new_memory);
-        self.monitoring.borrow_mut().kubelet_on_pod_placed(new_cpu, new_memory);
+        self.monitoring
+            .borrow_mut()
+            .kubelet_on_pod_placed(self.node.metadata.uid, new_cpu, new_memory);
 
         // Update eviction order
         self.eviction_order.remove(&pod, *prev_memory);
@@ -217,7 +222,7 @@ impl Kubelet {
         self.node.restore(*prev_cpu, *prev_memory);
         self.monitoring
             .borrow_mut()
-            .kubelet_on_pod_unplaced(*prev_cpu, *prev_memory);
+            .kubelet_on_pod_unplaced(self.node.metadata.uid, *prev_cpu, *prev_memory);
 
         // Restore other stuff
         self.remove_pod_without_restoring_resources(
@@ -265,7 +270,7 @@ impl Kubelet {
             self.node.restore(*prev_cpu, *prev_memory);
             self.monitoring
                 .borrow_mut()
-                .kubelet_on_pod_unplaced(*prev_cpu, *prev_memory);
+                .kubelet_on_pod_unplaced(self.node.metadata.uid, *prev_cpu, *prev_memory);
         }
 
         // All resources should be restored
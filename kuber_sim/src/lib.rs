@@ -3,6 +3,7 @@ pub mod macros;
 
 pub mod api_server;
 pub mod autoscaler;
+pub mod error;
 pub mod kubelet;
 pub mod load_types;
 pub mod objects;
@@ -19,13 +20,21 @@ pub use crate::scheduler::queues::active_queue::*;
 pub use crate::scheduler::queues::backoff_queue::*;
 
 pub use crate::scheduler::pipeline::filter::*;
+pub use crate::scheduler::pipeline::permit::*;
+pub use crate::scheduler::pipeline::pre_bind::*;
+pub use crate::scheduler::pipeline::registry::{PluginArgs, PluginRegistry};
+pub use crate::scheduler::pipeline::reserve::*;
 pub use crate::scheduler::pipeline::score::*;
 pub use crate::scheduler::pipeline::score_normalize::*;
+pub use crate::scheduler::scheduler::DEFAULT_SCHEDULER_NAME;
 
 pub use crate::simulation::init_config::InitConfig;
 pub use crate::simulation::init_nodes::InitNodes;
 pub use crate::simulation::init_trace::InitTrace;
-pub use crate::simulation::pipeline_config::PipelineConfig;
+pub use crate::simulation::pipeline_config::{PipelineConfig, PluginSpec, SchedulerProfile, ScorerSpec};
+pub use crate::simulation::run_summary::RunSummary;
+
+pub use crate::error::KuberSimError;
 
 pub use crate::simulation::experiment::Experiment;
 pub use crate::simulation::simulation::Simulation;
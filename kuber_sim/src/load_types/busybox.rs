@@ -1,4 +1,5 @@
 pub use crate::common_imports::dsc;
+use crate::error::{parse_field, split_field, KuberSimError};
 
 #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct BusyBox {
@@ -45,23 +46,23 @@ impl BusyBox {
 }
 
 impl std::str::FromStr for BusyBox {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "i64;i64;64;i64;f64;f64"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (cpu_down_str, other) = s.split_once(';').unwrap();
-        let (memory_down_str, other) = other.split_once(';').unwrap();
-        let (cpu_up_str, other) = other.split_once(';').unwrap();
-        let (memory_up_str, other) = other.split_once(';').unwrap();
-        let (shift_time_str, duration_str) = other.split_once(';').unwrap();
+        let (cpu_down_str, other) = split_field(s, ';', "BusyBox.cpu_down")?;
+        let (memory_down_str, other) = split_field(other, ';', "BusyBox.memory_down")?;
+        let (cpu_up_str, other) = split_field(other, ';', "BusyBox.cpu_up")?;
+        let (memory_up_str, other) = split_field(other, ';', "BusyBox.memory_up")?;
+        let (shift_time_str, duration_str) = split_field(other, ';', "BusyBox.shift_time")?;
 
         Ok(Self {
-            cpu_down: sim_ok!(str::parse(cpu_down_str), "BusyBox. Invalid value for cpu_down."),
-            memory_down: sim_ok!(str::parse(memory_down_str), "BusyBox. Invalid value for memory_down."),
-            cpu_up: sim_ok!(str::parse(cpu_up_str), "BusyBox. Invalid value for cpu_up."),
-            memory_up: sim_ok!(str::parse(memory_up_str), "BusyBox. Invalid value for memory_up."),
-            shift_time: sim_ok!(str::parse(shift_time_str), "BusyBox. Invalid value for shift_time."),
-            duration: sim_ok!(str::parse(duration_str), "BusyBox. Invalid value for duration."),
+            cpu_down: parse_field(cpu_down_str, "BusyBox.cpu_down")?,
+            memory_down: parse_field(memory_down_str, "BusyBox.memory_down")?,
+            cpu_up: parse_field(cpu_up_str, "BusyBox.cpu_up")?,
+            memory_up: parse_field(memory_up_str, "BusyBox.memory_up")?,
+            shift_time: parse_field(shift_time_str, "BusyBox.shift_time")?,
+            duration: parse_field(duration_str, "BusyBox.duration")?,
             start_time: 0.0,
         })
     }
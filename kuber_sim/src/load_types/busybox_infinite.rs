@@ -1,4 +1,5 @@
 pub use crate::common_imports::dsc;
+use crate::error::{parse_field, split_field, KuberSimError};
 
 #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct BusyBoxInfinite {
@@ -34,30 +35,21 @@ impl BusyBoxInfinite {
 }
 
 impl std::str::FromStr for BusyBoxInfinite {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "i64;i64;64;i64;f64"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (cpu_down_str, other) = s.split_once(';').unwrap();
-        let (memory_down_str, other) = other.split_once(';').unwrap();
-        let (cpu_up_str, other) = other.split_once(';').unwrap();
-        let (memory_up_str, shift_time_str) = other.split_once(';').unwrap();
+        let (cpu_down_str, other) = split_field(s, ';', "BusyBoxInfinite.cpu_down")?;
+        let (memory_down_str, other) = split_field(other, ';', "BusyBoxInfinite.memory_down")?;
+        let (cpu_up_str, other) = split_field(other, ';', "BusyBoxInfinite.cpu_up")?;
+        let (memory_up_str, shift_time_str) = split_field(other, ';', "BusyBoxInfinite.memory_up")?;
 
         Ok(Self {
-            cpu_down: sim_ok!(str::parse(cpu_down_str), "BusyBoxInfinite. Invalid value for cpu_down."),
-            memory_down: sim_ok!(
-                str::parse(memory_down_str),
-                "BusyBoxInfinite. Invalid value for memory_down."
-            ),
-            cpu_up: sim_ok!(str::parse(cpu_up_str), "BusyBoxInfinite. Invalid value for cpu_up."),
-            memory_up: sim_ok!(
-                str::parse(memory_up_str),
-                "BusyBoxInfinite. Invalid value for memory_up."
-            ),
-            shift_time: sim_ok!(
-                str::parse(shift_time_str),
-                "BusyBoxInfinite. Invalid value for shift_time."
-            ),
+            cpu_down: parse_field(cpu_down_str, "BusyBoxInfinite.cpu_down")?,
+            memory_down: parse_field(memory_down_str, "BusyBoxInfinite.memory_down")?,
+            cpu_up: parse_field(cpu_up_str, "BusyBoxInfinite.cpu_up")?,
+            memory_up: parse_field(memory_up_str, "BusyBoxInfinite.memory_up")?,
+            shift_time: parse_field(shift_time_str, "BusyBoxInfinite.shift_time")?,
             start_time: 0.0,
         })
     }
@@ -1,4 +1,5 @@
 pub use crate::common_imports::dsc;
+use crate::error::{parse_field, split_field, KuberSimError};
 
 #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Constant {
@@ -32,17 +33,17 @@ impl Constant {
 }
 
 impl std::str::FromStr for Constant {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "i64;i64;f64"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (cpu_str, other) = s.split_once(';').unwrap();
-        let (memory_str, duration_str) = other.split_once(';').unwrap();
+        let (cpu_str, other) = split_field(s, ';', "Constant.cpu")?;
+        let (memory_str, duration_str) = split_field(other, ';', "Constant.memory")?;
 
         Ok(Self {
-            cpu: sim_ok!(str::parse(cpu_str), "Constant. Invalid value for cpu."),
-            memory: sim_ok!(str::parse(memory_str), "Constant. Invalid value for memory."),
-            duration: sim_ok!(str::parse(duration_str), "Constant. Invalid value for duration."),
+            cpu: parse_field(cpu_str, "Constant.cpu")?,
+            memory: parse_field(memory_str, "Constant.memory")?,
+            duration: parse_field(duration_str, "Constant.duration")?,
             start_time: 0.0,
         })
     }
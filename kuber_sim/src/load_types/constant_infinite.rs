@@ -1,3 +1,5 @@
+use crate::error::{parse_field, split_field, KuberSimError};
+
 #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ConstantInfinite {
     pub cpu: i64,
@@ -15,15 +17,15 @@ impl ConstantInfinite {
 }
 
 impl std::str::FromStr for ConstantInfinite {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "i64;i64"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (cpu_str, memory_str) = s.split_once(';').unwrap();
+        let (cpu_str, memory_str) = split_field(s, ';', "ConstantInfinite.cpu")?;
 
         Ok(Self {
-            cpu: sim_ok!(str::parse(cpu_str), "ConstantInfinite. Invalid value for cpu."),
-            memory: sim_ok!(str::parse(memory_str), "ConstantInfinite. Invalid value for memory."),
+            cpu: parse_field(cpu_str, "ConstantInfinite.cpu")?,
+            memory: parse_field(memory_str, "ConstantInfinite.memory")?,
         })
     }
 }
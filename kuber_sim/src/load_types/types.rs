@@ -1,3 +1,4 @@
+use crate::error::{split_field, KuberSimError};
 use crate::load_types::busybox::*;
 use crate::load_types::busybox_infinite::*;
 use crate::load_types::constant::*;
@@ -19,31 +20,22 @@ pub enum LoadType {
 }
 
 impl std::str::FromStr for LoadType {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "<enum_index: u8>;<enum_inner>"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (enum_index, enum_inner) = s.split_once(';').unwrap();
+        let (enum_index, enum_inner) = split_field(s, ';', "LoadType")?;
         let enum_inner = enum_inner.trim();
 
         match enum_index {
-            "0" => Ok(LoadType::Constant(sim_ok!(
-                str::parse(enum_inner),
-                "LoadType. Cannot parse Constant workload model."
-            ))),
-            "1" => Ok(LoadType::ConstantInfinite(sim_ok!(
-                str::parse(enum_inner),
-                "LoadType. Cannot parse ConstantInfinite workload model."
-            ))),
-            "2" => Ok(LoadType::BusyBox(sim_ok!(
-                str::parse(enum_inner),
-                "LoadType. Cannot parse BusyBox workload model."
-            ))),
-            "3" => Ok(LoadType::BusyBoxInfinite(sim_ok!(
-                str::parse(enum_inner),
-                "LoadType. Cannot parse BusyBoxInfinite workload model."
-            ))),
-            _ => Err(()),
+            "0" => Ok(LoadType::Constant(str::parse(enum_inner)?)),
+            "1" => Ok(LoadType::ConstantInfinite(str::parse(enum_inner)?)),
+            "2" => Ok(LoadType::BusyBox(str::parse(enum_inner)?)),
+            "3" => Ok(LoadType::BusyBoxInfinite(str::parse(enum_inner)?)),
+            _ => Err(KuberSimError::invalid(
+                "LoadType",
+                format!("Unexpected enum_index '{}'", enum_index),
+            )),
         }
     }
 }
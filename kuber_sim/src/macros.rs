@@ -10,6 +10,15 @@ macro_rules! sim_assert {
     };
 }
 
+#[macro_export]
+macro_rules! sim_warn {
+    ($msg:expr) => {
+        println!(">>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>");
+        println!("Warning: {}", $msg);
+        println!("<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<");
+    };
+}
+
 #[macro_export]
 macro_rules! sim_ok {
     ($expr:expr, $msg:expr) => {
@@ -40,6 +49,17 @@ macro_rules! sim_some {
     };
 }
 
+/// Returns KuberSimError about invalid field from the enclosing function if condition is false.
+/// Unlike sim_assert!, it is used for checks of simulation input, which caller may recover from.
+#[macro_export]
+macro_rules! sim_ensure {
+    ($condition:expr, $field:expr, $msg:expr) => {
+        if !($condition) {
+            return Err($crate::error::KuberSimError::invalid($field, $msg));
+        }
+    };
+}
+
 #[macro_export]
 #[cfg(feature = "dp_api_server")]
 macro_rules! dp_api_server {
@@ -2,4 +2,7 @@ pub mod node;
 pub mod node_group;
 pub mod object_meta;
 pub mod pod;
+pub mod pod_disruption_budget;
 pub mod pod_group;
+pub mod power_model;
+pub mod priority_class;
@@ -1,6 +1,6 @@
 use crate::objects::object_meta::ObjectMeta;
 use crate::scheduler::features::taints_tolerations::Taint;
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::simulation::uid_allocator::UidAllocator;
 
 #[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct NodeSpec {
@@ -32,21 +32,14 @@ pub struct Node {
 }
 
 impl Node {
-    pub fn prepare(&mut self, group_uid: u64) {
-        static UID_COUNTER: AtomicU64 = AtomicU64::new(1);
-        self.metadata.uid = UID_COUNTER.load(Ordering::Relaxed);
-        UID_COUNTER.fetch_add(1, Ordering::Relaxed);
+    /// Prepares node created from template of its group. Template is checked by NodeGroup::prepare.
+    pub fn prepare(&mut self, group_uid: u64, uids: &mut UidAllocator) {
+        self.metadata.uid = uids.next_node_uid();
 
         self.spec.available_cpu = self.spec.installed_cpu;
         self.spec.available_memory = self.spec.installed_memory;
 
         self.metadata.group_uid = group_uid;
-
-        sim_assert!(self.spec.installed_cpu > 0, "Node.spec.installed_cpu must be > 0.");
-        sim_assert!(
-            self.spec.installed_memory > 0,
-            "Node.spec.installed_memory must be > 0."
-        );
     }
 
     pub fn is_both_consumable(&self, cpu: i64, memory: i64) -> bool {
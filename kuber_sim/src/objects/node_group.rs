@@ -1,5 +1,6 @@
+use crate::error::KuberSimError;
 use crate::objects::node::Node;
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::objects::power_model::PowerModel;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct NodeGroup {
@@ -8,13 +9,35 @@ pub struct NodeGroup {
 
     pub amount: u64,
     pub node: Node,
+    /// Price of one node per hour of simulation time. Nodes without it are free.
+    #[serde(default)]
+    pub cost_per_hour: Option<f64>,
+    /// Power of nodes for energy accounting. Nodes without it are not counted.
+    #[serde(default)]
+    pub power_model: Option<PowerModel>,
 }
 
 impl NodeGroup {
-    pub fn prepare(&mut self) {
-        static UID_COUNTER: AtomicU64 = AtomicU64::new(1);
+    /// Group uid is assigned by InitNodes::prepare, so group can be checked before simulation is created.
+    pub fn prepare(&self) -> Result<(), KuberSimError> {
+        // Nodes are created from template during simulation, so it is checked here
+        sim_ensure!(
+            self.node.spec.installed_cpu > 0,
+            "NodeSpec.installed_cpu",
+            "must be > 0"
+        );
+        sim_ensure!(
+            self.node.spec.installed_memory > 0,
+            "NodeSpec.installed_memory",
+            "must be > 0"
+        );
 
-        self.group_uid = UID_COUNTER.load(Ordering::Relaxed);
-        UID_COUNTER.fetch_add(1, Ordering::Relaxed);
+        if let Some(cost) = self.cost_per_hour {
+            sim_ensure!(cost >= 0.0, "NodeGroup.cost_per_hour", "must be >= 0.0");
+        }
+        if let Some(power_model) = self.power_model.as_ref() {
+            power_model.prepare()?;
+        }
+        return Ok(());
     }
 }
@@ -1,3 +1,5 @@
+use crate::error::{split_field, KuberSimError};
+
 // https://kubernetes.io/docs/reference/generated/kubernetes-api/v1.29/#objectmeta-v1-meta
 #[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ObjectMeta {
@@ -7,10 +9,13 @@ pub struct ObjectMeta {
     pub uid: u64,
     #[serde(skip)]
     pub group_uid: u64,
+    /// Copy of PodGroup.min_member. Pods of the group with min_member > 0 are placed all-or-nothing.
+    #[serde(skip)]
+    pub group_min_member: u64,
 }
 
 impl std::str::FromStr for ObjectMeta {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "key_1:value_1,key_2:value_2,..."
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -18,7 +23,7 @@ impl std::str::FromStr for ObjectMeta {
         let mut labels = std::collections::BTreeMap::<String, String>::new();
 
         for key_value in data {
-            let (key, value) = sim_some!(key_value.split_once(':'), "ObjectMeta. Invalid format.");
+            let (key, value) = split_field(key_value, ':', "ObjectMeta.labels")?;
             labels.insert(key.to_string(), value.to_string());
         }
 
@@ -26,6 +31,7 @@ impl std::str::FromStr for ObjectMeta {
             labels,
             uid: 0,
             group_uid: 0,
+            group_min_member: 0,
         })
     }
 }
@@ -1,9 +1,12 @@
+use crate::error::{parse_field, section_end, split_field, KuberSimError};
 use crate::load_types::types::LoadType;
 use crate::objects::object_meta::ObjectMeta;
+use crate::objects::priority_class::PreemptionPolicy;
 use crate::scheduler::features::node_affinity::NodeAffinity;
+use crate::scheduler::features::pod_affinity::{PodAffinity, PodAntiAffinity};
 use crate::scheduler::features::taints_tolerations::Toleration;
-use crate::simulation::init_trace::InitTrace;
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::scheduler::features::topology_spread::TopologySpreadConstraint;
+use crate::simulation::uid_allocator::UidAllocator;
 
 // https://kubernetes.io/docs/reference/generated/kubernetes-api/v1.29/#podspec-v1-core
 #[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -18,8 +21,17 @@ pub struct PodSpec {
     #[serde(default)]
     pub limit_memory: i64,
 
+    /// Numeric priority of pod without priority_class_name. Zero means it is not given.
     #[serde(default)]
     pub priority: i64,
+    /// Name of PriorityClass. Priority and preemption_policy are resolved from it before simulation starts.
+    #[serde(default)]
+    pub priority_class_name: String,
+    #[serde(default)]
+    pub preemption_policy: PreemptionPolicy,
+    /// Scheduler profile which places this pod. Empty means DEFAULT_SCHEDULER_NAME.
+    #[serde(default)]
+    pub scheduler_name: String,
 
     pub load: LoadType,
 
@@ -29,74 +41,119 @@ pub struct PodSpec {
     pub tolerations: Vec<Toleration>,
     #[serde(default)]
     pub node_affinity: NodeAffinity,
+    #[serde(default)]
+    pub pod_affinity: PodAffinity,
+    #[serde(default)]
+    pub pod_anti_affinity: PodAntiAffinity,
+    #[serde(default)]
+    pub topology_spread_constraints: Vec<TopologySpreadConstraint>,
 }
 
 impl std::str::FromStr for PodSpec {
-    type Err = ();
+    type Err = KuberSimError;
 
-    /// Expects "<i64>;<i64>;<i64>;<i64>;<i64>;{<LoadType>};{<node_selector>};{<tolerations>};{<NodeAffinity>}"
+    /// Expects "<i64>;<i64>;<i64>;<i64>;<i64 | priority_class_name>;{<LoadType>};{<node_selector>};{<tolerations>};{<NodeAffinity>}"
+    /// optionally followed by ";{<PodAffinity>};{<PodAntiAffinity>};{<topology_spread_constraints>}"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (request_cpu_str, other) = s.split_once(';').unwrap();
-        let (request_memory_str, other) = other.split_once(';').unwrap();
-        let (limit_cpu_str, other) = other.split_once(';').unwrap();
-        let (limit_memory_str, other) = other.split_once(';').unwrap();
-        let (priority_str, other) = other.split_once(';').unwrap();
+        let (request_cpu_str, other) = split_field(s, ';', "PodSpec.request_cpu")?;
+        let (request_memory_str, other) = split_field(other, ';', "PodSpec.request_memory")?;
+        let (limit_cpu_str, other) = split_field(other, ';', "PodSpec.limit_cpu")?;
+        let (limit_memory_str, other) = split_field(other, ';', "PodSpec.limit_memory")?;
+        let (priority_str, other) = split_field(other, ';', "PodSpec.priority")?;
 
         let mut request_cpu = 0;
         if !request_cpu_str.is_empty() {
-            request_cpu = str::parse(request_cpu_str).unwrap();
+            request_cpu = parse_field(request_cpu_str, "PodSpec.request_cpu")?;
         }
 
         let mut request_memory = 0;
         if !request_memory_str.is_empty() {
-            request_memory = str::parse(request_memory_str).unwrap();
+            request_memory = parse_field(request_memory_str, "PodSpec.request_memory")?;
         }
 
         let mut limit_cpu = 0;
         if !limit_cpu_str.is_empty() {
-            limit_cpu = str::parse(limit_cpu_str).unwrap();
+            limit_cpu = parse_field(limit_cpu_str, "PodSpec.limit_cpu")?;
         }
 
         let mut limit_memory = 0;
         if !limit_memory_str.is_empty() {
-            limit_memory = str::parse(limit_memory_str).unwrap();
+            limit_memory = parse_field(limit_memory_str, "PodSpec.limit_memory")?;
         }
 
+        // Priority is given either by value or by PriorityClass name
         let mut priority = 0;
+        let mut priority_class_name = String::new();
         if !priority_str.is_empty() {
-            priority = str::parse(priority_str).unwrap();
+            match str::parse(priority_str) {
+                Ok(value) => priority = value,
+                Err(_) => priority_class_name = priority_str.to_string(),
+            }
         }
 
-        let load_end = InitTrace::find_matching_bracket(other, 0).unwrap();
+        let load_end = section_end(other, 0, "PodSpec.load")?;
         let load_str = &other[1..load_end];
 
-        let node_selector_end = InitTrace::find_matching_bracket(other, load_end + 2).unwrap();
+        let node_selector_end = section_end(other, load_end + 2, "PodSpec.node_selector")?;
         let node_selector_str = &other[load_end + 3..node_selector_end];
 
-        let tolerations_end = InitTrace::find_matching_bracket(other, node_selector_end + 2).unwrap();
+        let tolerations_end = section_end(other, node_selector_end + 2, "PodSpec.tolerations")?;
         let tolerations_str = &other[node_selector_end + 3..tolerations_end];
 
-        let node_affinity_end = InitTrace::find_matching_bracket(other, tolerations_end + 2).unwrap();
+        let node_affinity_end = section_end(other, tolerations_end + 2, "PodSpec.node_affinity")?;
         let node_affinity_str = &other[tolerations_end + 3..node_affinity_end];
-        assert_eq!(node_affinity_end + 1, other.len());
+
+        // Optional trailing sections
+        let mut optional_strs: Vec<&str> = Vec::new();
+        let mut section_start = node_affinity_end + 2;
+        while section_start < other.len() {
+            sim_ensure!(
+                optional_strs.len() < 3,
+                "PodSpec",
+                "Expected at most 3 optional sections after node_affinity"
+            );
+            let next_end = section_end(other, section_start, "PodSpec")?;
+            optional_strs.push(&other[section_start + 1..next_end]);
+            section_start = next_end + 2;
+        }
+        optional_strs.resize(3, "");
+        let (pod_affinity_str, pod_anti_affinity_str) = (optional_strs[0], optional_strs[1]);
+        let topology_spread_constraints_str = optional_strs[2];
 
         let mut node_selector = std::collections::BTreeMap::<String, String>::new();
         if !node_selector_str.is_empty() {
             for key_value in node_selector_str.split(',') {
-                let (key, value) = key_value.split_once(':').unwrap();
+                let (key, value) = split_field(key_value, ':', "PodSpec.node_selector")?;
                 node_selector.insert(key.to_string(), value.to_string());
             }
         }
 
         let mut node_affinity = NodeAffinity::default();
         if !node_affinity_str.is_empty() {
-            node_affinity = str::parse(node_affinity_str).unwrap();
+            node_affinity = str::parse(node_affinity_str)?;
+        }
+
+        let mut pod_affinity = PodAffinity::default();
+        if !pod_affinity_str.is_empty() {
+            pod_affinity = str::parse(pod_affinity_str)?;
+        }
+
+        let mut pod_anti_affinity = PodAntiAffinity::default();
+        if !pod_anti_affinity_str.is_empty() {
+            pod_anti_affinity = str::parse(pod_anti_affinity_str)?;
         }
 
         let mut tolerations: Vec<Toleration> = Vec::new();
         if !tolerations_str.is_empty() {
             for toleration_str in tolerations_str.split(';') {
-                tolerations.push(str::parse(toleration_str).unwrap());
+                tolerations.push(str::parse(toleration_str)?);
+            }
+        }
+
+        let mut topology_spread_constraints: Vec<TopologySpreadConstraint> = Vec::new();
+        if !topology_spread_constraints_str.is_empty() {
+            for constraint_str in topology_spread_constraints_str.split(';') {
+                topology_spread_constraints.push(str::parse(constraint_str)?);
             }
         }
 
@@ -106,14 +163,43 @@ impl std::str::FromStr for PodSpec {
             limit_cpu,
             limit_memory,
             priority,
-            load: str::parse(load_str).unwrap(),
+            priority_class_name,
+            preemption_policy: PreemptionPolicy::default(),
+            scheduler_name: String::new(),
+            load: str::parse(load_str)?,
             node_selector,
             tolerations,
             node_affinity,
+            pod_affinity,
+            pod_anti_affinity,
+            topology_spread_constraints,
         })
     }
 }
 
+impl PodSpec {
+    /// Checks spec of pod template before any pod is created from it. Zero limits mean no limits.
+    pub fn prepare(&self) -> Result<(), KuberSimError> {
+        sim_ensure!(self.request_cpu > 0, "PodSpec.request_cpu", "must be > 0");
+        sim_ensure!(self.request_memory > 0, "PodSpec.request_memory", "must be > 0");
+        sim_ensure!(
+            self.limit_cpu == 0 || self.limit_cpu >= self.request_cpu,
+            "PodSpec.limit_cpu",
+            "must be >= PodSpec.request_cpu"
+        );
+        sim_ensure!(
+            self.limit_memory == 0 || self.limit_memory >= self.request_memory,
+            "PodSpec.limit_memory",
+            "must be >= PodSpec.request_memory"
+        );
+        self.node_affinity.prepare()?;
+        for constraint in self.topology_spread_constraints.iter() {
+            constraint.prepare()?;
+        }
+        return Ok(());
+    }
+}
+
 // https://kubernetes.io/docs/concepts/workloads/pods/pod-lifecycle/#pod-phase
 #[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum PodPhase {
@@ -170,6 +256,11 @@ pub struct PodStatus {
 
     #[serde(skip)]
     pub cluster_resource_starvation: bool,
+
+    /// Node chosen for pod by preemption. Resources of node are held for pod until it is bound,
+    /// and pod is tried on it first.
+    #[serde(skip)]
+    pub nominated_node_uid: Option<u64>,
 }
 
 // https://kubernetes.io/docs/reference/generated/kubernetes-api/v1.29/#pod-v1-core
@@ -184,22 +275,23 @@ pub struct Pod {
 }
 
 impl std::str::FromStr for Pod {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "{<ObjectMeta>};{<PodSpec>}"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let metadata_end = InitTrace::find_matching_bracket(s, 0).unwrap();
+        let metadata_end = section_end(s, 0, "Pod.metadata")?;
+        let spec_end = section_end(s, metadata_end + 2, "Pod.spec")?;
 
         let metadata_str = &s[1..metadata_end];
-        let spec_str = &s[metadata_end + 3..s.len() - 1];
+        let spec_str = &s[metadata_end + 3..spec_end];
 
         let mut metadata = ObjectMeta::default();
         if !metadata_str.is_empty() {
-            metadata = str::parse(metadata_str).unwrap();
+            metadata = str::parse(metadata_str)?;
         }
 
         Ok(Self {
-            spec: str::parse(spec_str).unwrap(),
+            spec: str::parse(spec_str)?,
             metadata,
             status: PodStatus::default(),
         })
@@ -207,14 +299,11 @@ impl std::str::FromStr for Pod {
 }
 
 impl Pod {
-    pub fn prepare(&mut self, group_uid: u64) {
-        static UID_COUNTER: AtomicU64 = AtomicU64::new(1);
-
-        self.metadata.uid = UID_COUNTER.load(Ordering::Relaxed);
-        UID_COUNTER.fetch_add(1, Ordering::Relaxed);
+    /// Prepares pod created from template of its group. Template is checked by PodSpec::prepare.
+    pub fn prepare(&mut self, group_uid: u64, uids: &mut UidAllocator) {
+        self.metadata.uid = uids.next_pod_uid();
 
         self.metadata.group_uid = group_uid;
-        sim_assert!(group_uid != 0, "Pod. group_uid must be != 0.");
 
         self.status.phase = PodPhase::Pending;
         self.status.node_uid = None;
@@ -249,19 +338,6 @@ impl Pod {
         else {
             self.status.qos_class = QoSClass::BestEffort;
         }
-
-        sim_assert!(
-            self.spec.limit_cpu >= self.spec.request_cpu,
-            "Pod.spec.limit_cpu must be >= Pod.spec.request_cpu."
-        );
-        sim_assert!(
-            self.spec.limit_memory >= self.spec.request_memory,
-            "Pod.spec.limit_memory must be >= Pod.spec.request_memory."
-        );
-        sim_assert!(self.spec.request_cpu > 0, "Pod.spec.request_cpu must be > 0.");
-        sim_assert!(self.spec.request_memory > 0, "Pod.spec.request_memory must be > 0.");
-        sim_assert!(self.spec.request_cpu >= 0, "Pod.spec.request_cpu must be >= 0.");
-        sim_assert!(self.spec.request_memory >= 0, "Pod.spec.request_memory must be >= 0.");
     }
 
     pub fn is_usage_matches_limits(&self, cpu: i64, memory: i64) -> bool {
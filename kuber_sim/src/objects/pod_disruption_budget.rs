@@ -0,0 +1,57 @@
+use crate::error::KuberSimError;
+
+// https://kubernetes.io/docs/concepts/workloads/pods/disruptions/#pod-disruption-budgets
+/// Limits the number of pods of a PodGroup that may be disrupted at the same time
+/// by preemption, Cluster Autoscaler node removal or VPA eviction.
+/// Exactly one of min_available and max_unavailable must be set.
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PodDisruptionBudget {
+    /// Minimal number of running pods of the group after disruption.
+    #[serde(default)]
+    pub min_available: Option<u64>,
+    /// Maximal number of pods of the group which may be unavailable after disruption.
+    #[serde(default)]
+    pub max_unavailable: Option<u64>,
+}
+
+impl PodDisruptionBudget {
+    pub fn prepare(&self) -> Result<(), KuberSimError> {
+        sim_ensure!(
+            self.min_available.is_some() != self.max_unavailable.is_some(),
+            "PodDisruptionBudget",
+            "Exactly one of min_available and max_unavailable must be set"
+        );
+        return Ok(());
+    }
+
+    /// How many of healthy pods may be disrupted now, if the group is expected to have expected pods.
+    pub fn allowed_disruptions(&self, expected: u64, healthy: u64) -> u64 {
+        let desired_healthy = match (self.min_available, self.max_unavailable) {
+            (Some(min_available), _) => min_available,
+            (None, Some(max_unavailable)) => expected.saturating_sub(max_unavailable),
+            (None, None) => 0,
+        };
+        return healthy.saturating_sub(desired_healthy);
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_disruptions() {
+        let pdb = PodDisruptionBudget { min_available: Some(3), max_unavailable: None };
+        assert_eq!(pdb.allowed_disruptions(5, 5), 2);
+        assert_eq!(pdb.allowed_disruptions(5, 3), 0);
+        assert_eq!(pdb.allowed_disruptions(5, 1), 0);
+
+        let pdb = PodDisruptionBudget { min_available: None, max_unavailable: Some(1) };
+        assert_eq!(pdb.allowed_disruptions(5, 5), 1);
+        assert_eq!(pdb.allowed_disruptions(5, 4), 0);
+        assert_eq!(pdb.allowed_disruptions(0, 0), 0);
+    }
+}
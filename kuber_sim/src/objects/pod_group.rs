@@ -1,8 +1,8 @@
 use crate::autoscaler::hpa::hpa_profile::HPAProfile;
 use crate::autoscaler::vpa::vpa_profile::VPAProfile;
+use crate::error::{parse_field, section_end, split_field, KuberSimError};
 use crate::objects::pod::Pod;
-use crate::simulation::init_trace::InitTrace;
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::objects::pod_disruption_budget::PodDisruptionBudget;
 
 #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PodGroup {
@@ -12,64 +12,97 @@ pub struct PodGroup {
 
     pub pod: Pod,
 
+    /// Minimal number of pods which must be placed together (gang). 0 means pods are placed independently.
+    /// Gang is held only by coscheduling plugins, see PipelineConfig::enable_coscheduling.
+    #[serde(default)]
+    pub min_member: u64,
+
     #[serde(default)]
     pub hpa_profile: Option<HPAProfile>,
     #[serde(default)]
     pub vpa_profile: Option<VPAProfile>,
+    #[serde(default)]
+    pub pdb: Option<PodDisruptionBudget>,
 
     #[serde(skip)]
     pub group_uid: u64,
 }
 
 impl std::str::FromStr for PodGroup {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "<pod_count: u64>;<group_duration: f64>;{<Pod>};{<HPAProfile>};{<VPAProfile>}"
+    /// optionally followed by ";<min_member: u64>"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (pod_count_str, other) = s.split_once(';').unwrap();
-        let (group_duration_str, other) = other.trim().split_once(";").unwrap();
+        let (pod_count_str, other) = split_field(s, ';', "PodGroup.pod_count")?;
+        let (group_duration_str, other) = split_field(other.trim(), ';', "PodGroup.group_duration")?;
         let other = other.trim();
 
-        let pod_end = InitTrace::find_matching_bracket(other, 0).unwrap();
-        let hpa_end = InitTrace::find_matching_bracket(other, pod_end + 2).unwrap();
+        let pod_end = section_end(other, 0, "PodGroup.pod")?;
+        let hpa_end = section_end(other, pod_end + 2, "PodGroup.hpa_profile")?;
+        let vpa_end = section_end(other, hpa_end + 2, "PodGroup.vpa_profile")?;
 
         let pod_str = &other[1..pod_end];
         let hpa_profile_str = &other[pod_end + 3..hpa_end];
-        let vpa_profile_str = &other[hpa_end + 3..other.len() - 1];
+        let vpa_profile_str = &other[hpa_end + 3..vpa_end];
+        let min_member_str = other[vpa_end + 1..].trim().trim_start_matches(';').trim();
 
         let mut group_duration: f64 = 0.0;
         if !group_duration_str.is_empty() {
-            group_duration = str::parse(group_duration_str).unwrap();
+            group_duration = parse_field(group_duration_str, "PodGroup.group_duration")?;
         }
 
         let mut hpa_profile: Option<HPAProfile> = None;
         if !hpa_profile_str.is_empty() {
-            hpa_profile = Some(str::parse(hpa_profile_str).unwrap());
+            hpa_profile = Some(str::parse(hpa_profile_str)?);
         }
 
         let mut vpa_profile: Option<VPAProfile> = None;
         if !vpa_profile_str.is_empty() {
-            vpa_profile = Some(str::parse(vpa_profile_str).unwrap());
+            vpa_profile = Some(str::parse(vpa_profile_str)?);
+        }
+
+        let mut min_member: u64 = 0;
+        if !min_member_str.is_empty() {
+            min_member = parse_field(min_member_str, "PodGroup.min_member")?;
         }
 
         Ok(Self {
-            pod_count: str::parse(pod_count_str).unwrap(),
+            pod_count: parse_field(pod_count_str, "PodGroup.pod_count")?,
             group_duration,
-            pod: str::parse(pod_str).unwrap(),
+            pod: str::parse(pod_str)?,
+            min_member,
             hpa_profile,
             vpa_profile,
+            pdb: None,
             group_uid: 0,
         })
     }
 }
 
 impl PodGroup {
-    pub fn prepare(&mut self) {
-        static UID_COUNTER: AtomicU64 = AtomicU64::new(1);
+    /// Group uid is assigned by InitTrace::prepare, so group can be checked before simulation is created.
+    pub fn prepare(&mut self) -> Result<(), KuberSimError> {
+        sim_ensure!(self.group_duration >= 0.0, "PodGroup.group_duration", "must be >= 0");
+        sim_ensure!(
+            self.min_member <= self.pod_count,
+            "PodGroup.min_member",
+            "must be <= PodGroup.pod_count"
+        );
+        self.pod.spec.prepare()?;
 
-        self.group_uid = UID_COUNTER.load(Ordering::Relaxed);
-        UID_COUNTER.fetch_add(1, Ordering::Relaxed);
+        if let Some(hpa_profile) = &self.hpa_profile {
+            hpa_profile.prepare()?;
+        }
+        if let Some(vpa_profile) = &self.vpa_profile {
+            vpa_profile.prepare()?;
+        }
+        if let Some(pdb) = &self.pdb {
+            pdb.prepare()?;
+        }
 
-        sim_assert!(self.group_duration >= 0.0, "PodGroup. group_duration must be >= 0.");
+        // Every pod of the group (including ones created by HPA and VPA) knows its gang size
+        self.pod.metadata.group_min_member = self.min_member;
+        return Ok(());
     }
 }
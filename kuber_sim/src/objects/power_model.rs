@@ -0,0 +1,64 @@
+use crate::error::KuberSimError;
+
+/// Power of node depending on CPU utilization (actual CPU use on kubelet / installed CPU).
+/// Power is interpolated linearly between (0, idle_watts), points and (1, max_watts).
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PowerModel {
+    pub idle_watts: f64,
+    pub max_watts: f64,
+    /// Intermediate (utilization, watts) points for piecewise interpolation, ordered by utilization.
+    /// Without them power is linear in utilization.
+    #[serde(default)]
+    pub points: Vec<(f64, f64)>,
+}
+
+impl PowerModel {
+    pub fn prepare(&self) -> Result<(), KuberSimError> {
+        sim_ensure!(self.idle_watts >= 0.0, "PowerModel.idle_watts", "must be >= 0.0");
+        sim_ensure!(self.max_watts >= 0.0, "PowerModel.max_watts", "must be >= 0.0");
+
+        let mut prev_utilization = 0.0;
+        for &(utilization, watts) in self.points.iter() {
+            sim_ensure!(
+                prev_utilization < utilization && utilization < 1.0,
+                "PowerModel.points",
+                "utilizations must be increasing in (0.0, 1.0)"
+            );
+            sim_ensure!(watts >= 0.0, "PowerModel.points", "watts must be >= 0.0");
+            prev_utilization = utilization;
+        }
+        return Ok(());
+    }
+
+    /// Utilization is clamped to [0, 1].
+    pub fn power(&self, utilization: f64) -> f64 {
+        let utilization = utilization.clamp(0.0, 1.0);
+
+        let mut prev = (0.0, self.idle_watts);
+        for &next in self.points.iter().chain([(1.0, self.max_watts)].iter()) {
+            if utilization <= next.0 {
+                return prev.1 + (next.1 - prev.1) * (utilization - prev.0) / (next.0 - prev.0);
+            }
+            prev = next;
+        }
+        return self.max_watts;
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power_model() {
+        let linear = PowerModel { idle_watts: 100.0, max_watts: 200.0, points: vec![] };
+        assert_eq!((linear.power(0.0), linear.power(0.25), linear.power(1.0)), (100.0, 125.0, 200.0));
+        assert_eq!((linear.power(-1.0), linear.power(2.0)), (100.0, 200.0));
+
+        let piecewise = PowerModel { idle_watts: 100.0, max_watts: 200.0, points: vec![(0.5, 180.0)] };
+        assert_eq!((piecewise.power(0.25), piecewise.power(0.5), piecewise.power(0.75)), (140.0, 180.0, 190.0));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::error::KuberSimError;
+use crate::objects::pod::PodSpec;
+
+// https://kubernetes.io/docs/concepts/scheduling-eviction/pod-priority-preemption/#non-preempting-priority-class
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum PreemptionPolicy {
+    /// Pod may preempt pods with lower priority.
+    #[default]
+    PreemptLowerPriority = 0,
+    /// Pod is placed ahead of lower priority pods in queue, but never preempts them.
+    Never = 1,
+}
+
+// https://kubernetes.io/docs/concepts/scheduling-eviction/pod-priority-preemption/#priorityclass
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PriorityClass {
+    pub name: String,
+    pub value: i64,
+    /// Class of pods without priority_class_name. At most one class may be global default.
+    #[serde(default)]
+    pub global_default: bool,
+    #[serde(default)]
+    pub preemption_policy: PreemptionPolicy,
+}
+
+impl PriorityClass {
+    pub fn prepare_all(classes: &Vec<PriorityClass>) -> Result<(), KuberSimError> {
+        for (i, class) in classes.iter().enumerate() {
+            sim_ensure!(!class.name.is_empty(), "PriorityClass.name", "must not be empty");
+            sim_ensure!(
+                classes[..i].iter().all(|other| other.name != class.name),
+                "PriorityClass.name",
+                format!("Name {} is used twice", class.name)
+            );
+        }
+        sim_ensure!(
+            classes.iter().filter(|class| class.global_default).count() <= 1,
+            "PriorityClass.global_default",
+            "At most one class may be global_default"
+        );
+        return Ok(());
+    }
+
+    /// Sets pod priority and preemption policy from its PriorityClass, as the Priority admission controller does.
+    /// Pod without priority_class_name keeps its numeric priority if it is given (non-zero),
+    /// otherwise it gets global default class if it exists.
+    pub fn resolve(classes: &Vec<PriorityClass>, spec: &mut PodSpec) -> Result<(), KuberSimError> {
+        let class = if spec.priority_class_name.is_empty() {
+            match spec.priority {
+                0 => classes.iter().find(|class| class.global_default),
+                _ => None,
+            }
+        } else {
+            let class = classes.iter().find(|class| class.name == spec.priority_class_name);
+            sim_ensure!(
+                class.is_some(),
+                "PodSpec.priority_class_name",
+                format!("Unknown PriorityClass {}", spec.priority_class_name)
+            );
+            class
+        };
+
+        if let Some(class) = class {
+            spec.priority = class.value;
+            spec.preemption_policy = class.preemption_policy.clone();
+        }
+        return Ok(());
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_class(name: &str, value: i64, global_default: bool, preemption_policy: PreemptionPolicy) -> PriorityClass {
+        PriorityClass { name: name.to_string(), value, global_default, preemption_policy }
+    }
+
+    #[test]
+    fn test_priority_class_resolve() {
+        let classes = vec![
+            make_class("low", 10, true, PreemptionPolicy::PreemptLowerPriority),
+            make_class("batch", 100, false, PreemptionPolicy::Never),
+        ];
+        PriorityClass::prepare_all(&classes).unwrap();
+
+        let mut spec = PodSpec::default();
+        spec.priority_class_name = "batch".to_string();
+        PriorityClass::resolve(&classes, &mut spec).unwrap();
+        assert_eq!(spec.priority, 100);
+        assert_eq!(spec.preemption_policy, PreemptionPolicy::Never);
+
+        // Global default
+        let mut spec = PodSpec::default();
+        PriorityClass::resolve(&classes, &mut spec).unwrap();
+        assert_eq!(spec.priority, 10);
+        assert_eq!(spec.preemption_policy, PreemptionPolicy::PreemptLowerPriority);
+
+        // Given numeric priority is not rewritten by global default
+        let mut spec = PodSpec::default();
+        spec.priority = 5;
+        PriorityClass::resolve(&classes, &mut spec).unwrap();
+        assert_eq!(spec.priority, 5);
+
+        // Without global default bare priority is kept
+        let mut spec = PodSpec::default();
+        spec.priority = 5;
+        PriorityClass::resolve(&classes[1..].to_vec(), &mut spec).unwrap();
+        assert_eq!(spec.priority, 5);
+
+        // Unknown class
+        let mut spec = PodSpec::default();
+        spec.priority_class_name = "high".to_string();
+        assert!(PriorityClass::resolve(&classes, &mut spec).is_err());
+    }
+}
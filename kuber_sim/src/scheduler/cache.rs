@@ -0,0 +1,234 @@
+use crate::objects::node::Node;
+use crate::objects::pod::Pod;
+use crate::scheduler::features::taints_tolerations::Taint;
+use crate::scheduler::node_index::NodeRTree;
+use crate::scheduler::queues::queueing_hints::ClusterEvent;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Cluster state shared by all scheduler instances (profiles).
+/// Every scheduler sees the pods placed by the others, so their decisions stay consistent.
+pub struct SchedulerCache {
+    pub running_pods: HashMap<u64, Pod>, // HashMap<pod_uid, Pod>
+    pub nodes: HashMap<u64, Node>,       // HashMap<node_uid, Node>
+    pub node_rtree: NodeRTree,
+    /// Cluster changes applied to cache which are not yet processed by each registered scheduler.
+    pub cluster_events: Vec<BTreeSet<ClusterEvent>>, // Vec<events> indexed by scheduler
+    /// Pending pods nominated to nodes by preemption. Analog of nominator of kube-scheduler.
+    pub nominated_pods: HashMap<u64, Pod>, // HashMap<pod_uid, Pod>
+}
+
+impl SchedulerCache {
+    pub fn new() -> Self {
+        Self {
+            running_pods: HashMap::new(),
+            nodes: HashMap::new(),
+            node_rtree: NodeRTree::new(),
+            cluster_events: Vec::new(),
+            nominated_pods: HashMap::new(),
+        }
+    }
+
+    /// Returns index of scheduler's pending cluster events.
+    pub fn register_scheduler(&mut self) -> usize {
+        self.cluster_events.push(BTreeSet::new());
+        return self.cluster_events.len() - 1;
+    }
+
+    /// Cluster change is delivered to every registered scheduler.
+    pub fn push_cluster_event(&mut self, event: ClusterEvent) {
+        for events in self.cluster_events.iter_mut() {
+            events.insert(event);
+        }
+    }
+
+    /// Returns and clears events which happened since the previous call by the same scheduler.
+    pub fn take_cluster_events(&mut self, index: usize) -> BTreeSet<ClusterEvent> {
+        return std::mem::take(&mut self.cluster_events[index]);
+    }
+
+    /// Node updates are delivered to every scheduler, so the first one applies it.
+    /// Returns false if node is already in cache.
+    pub fn add_node(&mut self, node: Node) -> bool {
+        if self.nodes.contains_key(&node.metadata.uid) {
+            return false;
+        }
+
+        self.nodes.insert(node.metadata.uid, node.clone());
+        self.node_rtree.insert(node);
+        self.push_cluster_event(ClusterEvent::NodeAdded);
+        return true;
+    }
+
+    /// Replaces labels and (or) taints of node. Returns false if node is not in cache or nothing is changed.
+    pub fn update_node(
+        &mut self,
+        node_uid: u64,
+        labels: Option<BTreeMap<String, String>>,
+        taints: Option<Vec<Taint>>,
+    ) -> bool {
+        let node = match self.nodes.get_mut(&node_uid) {
+            Some(node) => node,
+            None => return false,
+        };
+
+        let mut events: Vec<ClusterEvent> = Vec::new();
+        if labels.as_ref().map_or(false, |labels| *labels != node.metadata.labels) {
+            events.push(ClusterEvent::NodeLabelsChanged);
+        }
+        if taints.as_ref().map_or(false, |taints| *taints != node.spec.taints) {
+            events.push(ClusterEvent::NodeTaintsChanged);
+        }
+        if events.is_empty() {
+            return false;
+        }
+
+        // RTree stores node copies, so replace it there too
+        self.node_rtree.remove(&node);
+        if let Some(labels) = labels {
+            node.metadata.labels = labels;
+        }
+        if let Some(taints) = taints {
+            node.spec.taints = taints;
+        }
+        self.node_rtree.insert(node.clone());
+
+        for event in events {
+            self.push_cluster_event(event);
+        }
+        return true;
+    }
+
+    /// Returns None if node is not in cache.
+    pub fn remove_node(&mut self, node_uid: u64) -> Option<Node> {
+        let node = self.nodes.remove(&node_uid)?;
+        self.node_rtree.remove(&node);
+        self.nominated_pods
+            .retain(|_, pod| pod.status.nominated_node_uid != Some(node_uid));
+        return Some(node);
+    }
+
+    /// Holds resources of nominated node for pending pod until it is bound.
+    pub fn nominate(&mut self, pod: &Pod) {
+        if pod.status.nominated_node_uid.is_some() {
+            self.nominated_pods.insert(pod.metadata.uid, pod.clone());
+        }
+    }
+
+    /// Copy of node with resources of pods nominated to it consumed, None if there are no such pods.
+    /// Pods with lower priority than pod's are not taken into account, as pod may preempt them anyway.
+    /// Analog of addNominatedPods of kube-scheduler.
+    pub fn node_with_nominated_pods(&self, node: &Node, pod: &Pod) -> Option<Node> {
+        let mut result: Option<Node> = None;
+        for other in self.nominated_pods.values() {
+            if other.metadata.uid != pod.metadata.uid
+                && other.status.nominated_node_uid == Some(node.metadata.uid)
+                && other.spec.priority >= pod.spec.priority
+            {
+                result
+                    .get_or_insert_with(|| node.clone())
+                    .consume(other.spec.request_cpu, other.spec.request_memory);
+            }
+        }
+        return result;
+    }
+
+    pub fn place_pod_to_node(&mut self, pod_uid: u64, node_uid: u64, cpu: i64, memory: i64) {
+        // Get pod's node
+        let node = self.nodes.get_mut(&node_uid).unwrap();
+
+        // Remove node from RTree
+        self.node_rtree.remove(&node);
+
+        // Update node
+        node.consume(cpu, memory);
+        let _not_presented = node.status.pods.insert(pod_uid);
+        assert!(_not_presented);
+
+        // Add node to RTree
+        self.node_rtree.insert(node.clone());
+    }
+
+    pub fn remove_pod_from_node(&mut self, pod_uid: u64, node_uid: u64, cpu: i64, memory: i64) {
+        // Get pod's node
+        let node = self.nodes.get_mut(&node_uid).unwrap();
+
+        // Remove node from RTree
+        self.node_rtree.remove(&node);
+
+        // Update node
+        node.restore(cpu, memory);
+        let _was_presented = node.status.pods.remove(&pod_uid);
+        assert!(_was_presented);
+
+        // Add node to RTree
+        self.node_rtree.insert(node.clone());
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::uid_allocator::UidAllocator;
+
+    #[test]
+    fn test_scheduler_cache() {
+        let mut node = Node::default();
+        node.spec.installed_cpu = 100;
+        node.spec.installed_memory = 100;
+        node.prepare(1, &mut UidAllocator::new());
+        let node_uid = node.metadata.uid;
+
+        let mut cache = SchedulerCache::new();
+        let index = cache.register_scheduler();
+        assert!(cache.add_node(node.clone()));
+        assert!(!cache.add_node(node.clone()));
+        assert_eq!(cache.nodes.len(), 1);
+
+        assert_eq!(cache.take_cluster_events(index), BTreeSet::from([ClusterEvent::NodeAdded]));
+
+        // Update is applied once
+        let labels = BTreeMap::from([("zone".to_string(), "a".to_string())]);
+        assert!(cache.update_node(node_uid, Some(labels.clone()), None));
+        assert!(!cache.update_node(node_uid, Some(labels.clone()), Some(Vec::new())));
+        assert!(!cache.update_node(0, Some(BTreeMap::new()), None));
+        assert_eq!(cache.take_cluster_events(index), BTreeSet::from([ClusterEvent::NodeLabelsChanged]));
+        assert!(cache.take_cluster_events(index).is_empty());
+
+        let mut nodes = Vec::new();
+        cache.node_rtree.find_suitable_nodes(50, 50, &mut nodes);
+        assert_eq!(nodes[0].metadata.labels, labels);
+
+        cache.place_pod_to_node(10, node_uid, 60, 60);
+        cache.node_rtree.find_suitable_nodes(50, 50, &mut nodes);
+        assert!(nodes.is_empty());
+
+        cache.remove_pod_from_node(10, node_uid, 60, 60);
+        cache.node_rtree.find_suitable_nodes(50, 50, &mut nodes);
+        assert_eq!(nodes.len(), 1);
+        assert!(nodes[0].status.pods.is_empty());
+
+        // Nominated pod holds node resources for pods of not higher priority
+        let mut nominated = Pod::default();
+        nominated.metadata.uid = 20;
+        nominated.spec.priority = 5;
+        nominated.spec.request_cpu = 30;
+        nominated.status.nominated_node_uid = Some(node_uid);
+        cache.nominate(&nominated);
+        let mut pod = Pod::default();
+        pod.metadata.uid = 21;
+        pod.spec.priority = 5;
+        assert_eq!(cache.node_with_nominated_pods(&cache.nodes[&node_uid], &pod).unwrap().spec.available_cpu, 70);
+        pod.spec.priority = 6;
+        assert!(cache.node_with_nominated_pods(&cache.nodes[&node_uid], &pod).is_none());
+        assert!(cache.node_with_nominated_pods(&cache.nodes[&node_uid], &nominated).is_none());
+
+        assert!(cache.remove_node(node_uid).is_some());
+        assert!(cache.nominated_pods.is_empty());
+        assert!(cache.remove_node(node_uid).is_none());
+        cache.node_rtree.find_suitable_nodes(0, 0, &mut nodes);
+        assert!(nodes.is_empty());
+    }
+}
@@ -1,2 +1,5 @@
 pub mod node_affinity;
+pub mod pod_affinity;
+pub mod preemption;
 pub mod taints_tolerations;
+pub mod topology_spread;
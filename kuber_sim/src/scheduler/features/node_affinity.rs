@@ -1,3 +1,4 @@
+use crate::error::KuberSimError;
 use crate::objects::node::Node;
 
 // https://kubernetes.io/docs/concepts/scheduling-eviction/assign-pod-node/#operators
@@ -36,6 +37,33 @@ pub struct NodeAffinityMatchExpression {
 }
 
 impl NodeAffinityMatchExpression {
+    pub fn prepare(&self) -> Result<(), KuberSimError> {
+        match self.operator {
+            NodeAffinityOperator::In | NodeAffinityOperator::NotIn => {
+                sim_ensure!(
+                    !self.values.is_empty(),
+                    "NodeAffinityMatchExpression.values",
+                    "must be non-empty for In and NotIn"
+                );
+            }
+            NodeAffinityOperator::Exists | NodeAffinityOperator::DoesNotExist => {
+                sim_ensure!(
+                    self.values.is_empty(),
+                    "NodeAffinityMatchExpression.values",
+                    "must be empty for Exists and DoesNotExist"
+                );
+            }
+            NodeAffinityOperator::Gt | NodeAffinityOperator::Lt => {
+                sim_ensure!(
+                    self.values.len() == 1 && self.values[0].parse::<i64>().is_ok(),
+                    "NodeAffinityMatchExpression.values",
+                    "must be a single integer for Gt and Lt"
+                );
+            }
+        }
+        return Ok(());
+    }
+
     pub fn matches(&self, node: &Node) -> bool {
         let value = node.metadata.labels.get(&self.key);
 
@@ -100,7 +128,7 @@ pub struct NodeAffinity {
 }
 
 impl std::str::FromStr for NodeAffinity {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "{Vec<NodeAffinityPreferredTerm>};{Vec<NodeAffinityRequiredTerm>}"
     fn from_str(_: &str) -> Result<Self, Self::Err> {
@@ -109,6 +137,20 @@ impl std::str::FromStr for NodeAffinity {
 }
 
 impl NodeAffinity {
+    pub fn prepare(&self) -> Result<(), KuberSimError> {
+        for term in self.preferred_terms.iter() {
+            for expression in term.node_selector_term.iter() {
+                expression.prepare()?;
+            }
+        }
+        for term in self.required_terms.iter() {
+            for expression in term.node_selector_term.iter() {
+                expression.prepare()?;
+            }
+        }
+        return Ok(());
+    }
+
     /// Is required NodeAffinitySelectorTerms matches node.
     pub fn is_required_matches(&self, node: &Node) -> bool {
         for match_expression in self.required_terms.iter() {
@@ -151,6 +193,7 @@ impl NodeAffinity {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::simulation::uid_allocator::UidAllocator;
     use std::collections::BTreeMap;
 
     #[test]
@@ -164,7 +207,7 @@ mod tests {
             ("gpu".to_string(), "amd".to_string()),
             ("value".to_string(), "23".to_string()),
         ]);
-        node.prepare(123);
+        node.prepare(123, &mut UidAllocator::new());
 
         // Create NodeAffinity rules
         let mut nf: NodeAffinity = NodeAffinity::default();
@@ -0,0 +1,564 @@
+use crate::error::{parse_field, section_end, split_field, KuberSimError};
+use crate::objects::node::Node;
+use crate::objects::pod::Pod;
+use std::collections::{BTreeMap, HashMap};
+
+/// Weight of existing pods' required affinity terms when scoring the incoming pod.
+/// Analog of hardPodAffinityWeight of kube-scheduler InterPodAffinity plugin.
+pub const HARD_POD_AFFINITY_WEIGHT: i64 = 1;
+
+// https://kubernetes.io/docs/reference/kubernetes-api/common-definitions/label-selector/
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum LabelSelectorOperator {
+    /// In = The label value is present in the supplied set of strings.
+    #[default]
+    In = 0,
+    /// NotIn = The label value is not contained in the supplied set of strings.
+    NotIn = 1,
+    /// Exists = A label with this key exists on the object.
+    Exists = 2,
+    /// DoNotExist = No label with this key exists on the object.
+    DoesNotExist = 3,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LabelSelectorRequirement {
+    /// The label key that the selector applies to.
+    pub key: String,
+    /// Represents a key's relationship to a set of values.
+    /// Valid operators are In, NotIn, Exists and DoesNotExist.
+    pub operator: LabelSelectorOperator,
+    /// An array of string values.
+    /// If the operator is In or NotIn, the values array must be non-empty.
+    /// If the operator is Exists or DoesNotExist, the values array must be empty.
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+impl LabelSelectorRequirement {
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        return match labels.get(&self.key) {
+            Some(value) => match self.operator {
+                LabelSelectorOperator::In => self.values.contains(value),
+                LabelSelectorOperator::NotIn => !self.values.contains(value),
+                LabelSelectorOperator::Exists => true,
+                LabelSelectorOperator::DoesNotExist => false,
+            },
+            None => match self.operator {
+                LabelSelectorOperator::In | LabelSelectorOperator::Exists => false,
+                LabelSelectorOperator::NotIn | LabelSelectorOperator::DoesNotExist => true,
+            },
+        };
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LabelSelector {
+    /// Map of {key,value} pairs. Each pair is equivalent to the In requirement with single value.
+    #[serde(default)]
+    pub match_labels: BTreeMap<String, String>,
+    /// A list of label selector requirements.
+    /// The requirements are ANDed with each other and with match_labels.
+    #[serde(default)]
+    pub match_expressions: Vec<LabelSelectorRequirement>,
+}
+
+impl std::str::FromStr for LabelSelector {
+    type Err = KuberSimError;
+
+    /// Expects "key_1:value_1,key_2:value_2,..."
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut match_labels = BTreeMap::<String, String>::new();
+        if !s.is_empty() {
+            for key_value in s.split(',') {
+                let (key, value) = split_field(key_value, ':', "LabelSelector.match_labels")?;
+                match_labels.insert(key.to_string(), value.to_string());
+            }
+        }
+
+        Ok(Self {
+            match_labels,
+            match_expressions: Vec::new(),
+        })
+    }
+}
+
+impl LabelSelector {
+    /// Is all selector requirements matches labels. Empty selector matches everything.
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        for (key, value) in self.match_labels.iter() {
+            if labels.get(key) != Some(value) {
+                return false;
+            }
+        }
+        for expression in self.match_expressions.iter() {
+            if !expression.matches(labels) {
+                return false;
+            }
+        }
+        return true;
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PodAffinityTerm {
+    /// A label query over pods.
+    #[serde(default)]
+    pub label_selector: LabelSelector,
+    /// Node label key. Nodes with the same value of this label are in the same topology domain.
+    pub topology_key: String,
+}
+
+impl std::str::FromStr for PodAffinityTerm {
+    type Err = KuberSimError;
+
+    /// Expects "<topology_key: String>,<key_1>:<value_1>,<key_2>:<value_2>,..."
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (topology_key, selector_str) = s.split_once(',').unwrap_or((s, ""));
+
+        Ok(Self {
+            label_selector: str::parse(selector_str)?,
+            topology_key: topology_key.to_string(),
+        })
+    }
+}
+
+impl PodAffinityTerm {
+    /// Topology domain of node. Node without topology_key label is not in any domain.
+    pub fn domain<'a>(&self, node: &'a Node) -> Option<&'a String> {
+        return node.metadata.labels.get(&self.topology_key);
+    }
+
+    /// Adds value to the count of node's topology domain.
+    fn add_to_domain(&self, counts: &mut TopologyCounts, node: &Node, value: i64) {
+        if let Some(domain) = self.domain(node) {
+            let domains = counts.entry(self.topology_key.clone()).or_default();
+            *domains.entry(domain.clone()).or_insert(0) += value;
+        }
+    }
+
+    /// Adds value to the count of topology domain of each running pod matching label_selector.
+    fn add_matching_pods(
+        &self,
+        counts: &mut TopologyCounts,
+        running_pods: &HashMap<u64, Pod>,
+        nodes: &HashMap<u64, Node>,
+        value: i64,
+    ) {
+        for other in running_pods.values() {
+            if !self.label_selector.matches(&other.metadata.labels) {
+                continue;
+            }
+            if let Some(other_node) = other.status.node_uid.and_then(|uid| nodes.get(&uid)) {
+                self.add_to_domain(counts, other_node, value);
+            }
+        }
+    }
+
+    /// Counts running pods matching label_selector per topology domain.
+    pub fn count_domains(&self, running_pods: &HashMap<u64, Pod>, nodes: &HashMap<u64, Node>) -> HashMap<String, i64> {
+        let mut counts = TopologyCounts::new();
+        self.add_matching_pods(&mut counts, running_pods, nodes, 1);
+        return counts.remove(&self.topology_key).unwrap_or_default();
+    }
+
+    /// Count of matching pods in the node's topology domain.
+    pub fn count_in_domain(&self, domains: &HashMap<String, i64>, node: &Node) -> i64 {
+        return self
+            .domain(node)
+            .and_then(|domain| domains.get(domain))
+            .copied()
+            .unwrap_or(0);
+    }
+
+    /// Is any running pod in cluster matches label_selector.
+    pub fn is_any_in_cluster(&self, running_pods: &HashMap<u64, Pod>) -> bool {
+        return running_pods
+            .values()
+            .any(|other| self.label_selector.matches(&other.metadata.labels));
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PodAffinityPreferredTerm {
+    /// Pod affinity term associated with the corresponding weight.
+    pub pod_affinity_term: PodAffinityTerm,
+    /// Weight associated with matching the corresponding pod_affinity_term, in the range 1-100.
+    pub weight: i64,
+}
+
+impl std::str::FromStr for PodAffinityPreferredTerm {
+    type Err = KuberSimError;
+
+    /// Expects "<weight: i64>,<PodAffinityTerm>"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (weight_str, term_str) = split_field(s, ',', "PodAffinityPreferredTerm.weight")?;
+
+        Ok(Self {
+            pod_affinity_term: str::parse(term_str)?,
+            weight: parse_field(weight_str, "PodAffinityPreferredTerm.weight")?,
+        })
+    }
+}
+
+/// Parses "{<required terms separated by ';'>};{<preferred terms separated by ';'>}" of field.
+fn parse_terms(s: &str, field: &str) -> Result<(Vec<PodAffinityTerm>, Vec<PodAffinityPreferredTerm>), KuberSimError> {
+    let required_end = section_end(s, 0, field)?;
+    let preferred_end = section_end(s, required_end + 2, field)?;
+    let required_str = &s[1..required_end];
+    let preferred_str = &s[required_end + 3..preferred_end];
+
+    let mut required_terms: Vec<PodAffinityTerm> = Vec::new();
+    if !required_str.is_empty() {
+        for term_str in required_str.split(';') {
+            required_terms.push(str::parse(term_str)?);
+        }
+    }
+
+    let mut preferred_terms: Vec<PodAffinityPreferredTerm> = Vec::new();
+    if !preferred_str.is_empty() {
+        for term_str in preferred_str.split(';') {
+            preferred_terms.push(str::parse(term_str)?);
+        }
+    }
+
+    return Ok((required_terms, preferred_terms));
+}
+
+// https://kubernetes.io/docs/concepts/scheduling-eviction/assign-pod-node/#inter-pod-affinity-and-anti-affinity
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PodAffinity {
+    /// Analog of preferredDuringSchedulingIgnoredDuringExecution.
+    #[serde(default)]
+    pub preferred_terms: Vec<PodAffinityPreferredTerm>,
+    /// Analog of requiredDuringSchedulingIgnoredDuringExecution.
+    /// The terms are ANDed.
+    #[serde(default)]
+    pub required_terms: Vec<PodAffinityTerm>,
+}
+
+impl std::str::FromStr for PodAffinity {
+    type Err = KuberSimError;
+
+    /// Expects "{Vec<PodAffinityTerm>};{Vec<PodAffinityPreferredTerm>}"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (required_terms, preferred_terms) = parse_terms(s, "PodAffinity")?;
+        Ok(Self {
+            preferred_terms,
+            required_terms,
+        })
+    }
+}
+
+// https://kubernetes.io/docs/concepts/scheduling-eviction/assign-pod-node/#inter-pod-affinity-and-anti-affinity
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PodAntiAffinity {
+    /// Analog of preferredDuringSchedulingIgnoredDuringExecution.
+    #[serde(default)]
+    pub preferred_terms: Vec<PodAffinityPreferredTerm>,
+    /// Analog of requiredDuringSchedulingIgnoredDuringExecution.
+    /// The terms are ANDed.
+    #[serde(default)]
+    pub required_terms: Vec<PodAffinityTerm>,
+}
+
+impl std::str::FromStr for PodAntiAffinity {
+    type Err = KuberSimError;
+
+    /// Expects "{Vec<PodAffinityTerm>};{Vec<PodAffinityPreferredTerm>}"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (required_terms, preferred_terms) = parse_terms(s, "PodAntiAffinity")?;
+        Ok(Self {
+            preferred_terms,
+            required_terms,
+        })
+    }
+}
+
+/// Counts of pods per topology domain for each topology key.
+pub type TopologyCounts = HashMap<String, HashMap<String, i64>>; // HashMap<topology_key, HashMap<domain, count>>
+
+/// Sums counts of the node's topology domains over all topology keys.
+pub fn sum_in_domains(counts: &TopologyCounts, node: &Node) -> i64 {
+    let mut sum = 0;
+    for (topology_key, domains) in counts.iter() {
+        if let Some(domain) = node.metadata.labels.get(topology_key) {
+            sum += domains.get(domain).copied().unwrap_or(0);
+        }
+    }
+    return sum;
+}
+
+/// Required inter-pod affinity state of pod computed once per scheduling attempt.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PodAffinityCounts {
+    /// Matching running pods count per domain for each required affinity term of pod.
+    pub affinity: Vec<HashMap<String, i64>>, // Vec<HashMap<domain, matching_pods_count>>
+    /// Is pod the first pod in a series that have affinity to themselves:
+    /// no pod in cluster matches any required affinity term and the pod matches all of them.
+    pub is_first_of_series: bool,
+    /// Count of required anti-affinity terms forbidding the domain: terms of pod matching
+    /// running pods in the domain and terms of running pods in the domain matching pod.
+    pub anti_affinity: TopologyCounts,
+}
+
+impl PodAffinityCounts {
+    pub fn new(running_pods: &HashMap<u64, Pod>, nodes: &HashMap<u64, Node>, pod: &Pod) -> Self {
+        let affinity_terms = &pod.spec.pod_affinity.required_terms;
+        let affinity = affinity_terms
+            .iter()
+            .map(|term| term.count_domains(running_pods, nodes))
+            .collect();
+        let is_first_of_series = affinity_terms
+            .iter()
+            .all(|term| !term.is_any_in_cluster(running_pods) && term.label_selector.matches(&pod.metadata.labels));
+
+        let mut anti_affinity = TopologyCounts::new();
+        for term in pod.spec.pod_anti_affinity.required_terms.iter() {
+            term.add_matching_pods(&mut anti_affinity, running_pods, nodes, 1);
+        }
+        // Existing pods' anti-affinity is symmetric
+        for other in running_pods.values() {
+            if other.spec.pod_anti_affinity.required_terms.is_empty() {
+                continue;
+            }
+            let other_node = match other.status.node_uid.and_then(|uid| nodes.get(&uid)) {
+                Some(other_node) => other_node,
+                None => continue,
+            };
+            for term in other.spec.pod_anti_affinity.required_terms.iter() {
+                if term.label_selector.matches(&pod.metadata.labels) {
+                    term.add_to_domain(&mut anti_affinity, other_node, 1);
+                }
+            }
+        }
+
+        return Self {
+            affinity,
+            is_first_of_series,
+            anti_affinity,
+        };
+    }
+
+    /// Is each required affinity term of pod has matching running pod in the node's topology domain
+    /// and no required anti-affinity term of pod or running pods forbids the node's topology domains.
+    /// As in Kubernetes, the first pod of a series with affinity to itself is allowed.
+    pub fn is_required_matches(&self, pod: &Pod, node: &Node) -> bool {
+        let mut pods_exist = true;
+        for (term, domains) in pod.spec.pod_affinity.required_terms.iter().zip(self.affinity.iter()) {
+            // All topology labels must exist on the node
+            if term.domain(node).is_none() {
+                return false;
+            }
+            if term.count_in_domain(domains, node) == 0 {
+                pods_exist = false;
+            }
+        }
+
+        return (pods_exist || self.is_first_of_series) && sum_in_domains(&self.anti_affinity, node) == 0;
+    }
+}
+
+/// Counts weighted sum of preferred terms per topology domain: terms of pod matching running pods
+/// in the domain and terms of running pods in the domain matching pod.
+/// Required affinity terms of running pods are counted with HARD_POD_AFFINITY_WEIGHT.
+pub fn count_preferred_domains(
+    running_pods: &HashMap<u64, Pod>,
+    nodes: &HashMap<u64, Node>,
+    pod: &Pod,
+) -> TopologyCounts {
+    let mut counts = TopologyCounts::new();
+    for term in pod.spec.pod_affinity.preferred_terms.iter() {
+        term.pod_affinity_term
+            .add_matching_pods(&mut counts, running_pods, nodes, term.weight);
+    }
+    for term in pod.spec.pod_anti_affinity.preferred_terms.iter() {
+        term.pod_affinity_term
+            .add_matching_pods(&mut counts, running_pods, nodes, -term.weight);
+    }
+
+    for other in running_pods.values() {
+        let (affinity, anti_affinity) = (&other.spec.pod_affinity, &other.spec.pod_anti_affinity);
+        if affinity.required_terms.is_empty()
+            && affinity.preferred_terms.is_empty()
+            && anti_affinity.preferred_terms.is_empty()
+        {
+            continue;
+        }
+        let other_node = match other.status.node_uid.and_then(|uid| nodes.get(&uid)) {
+            Some(other_node) => other_node,
+            None => continue,
+        };
+
+        for term in affinity.required_terms.iter() {
+            if term.label_selector.matches(&pod.metadata.labels) {
+                term.add_to_domain(&mut counts, other_node, HARD_POD_AFFINITY_WEIGHT);
+            }
+        }
+        for term in affinity.preferred_terms.iter() {
+            let inner = &term.pod_affinity_term;
+            if inner.label_selector.matches(&pod.metadata.labels) {
+                inner.add_to_domain(&mut counts, other_node, term.weight);
+            }
+        }
+        for term in anti_affinity.preferred_terms.iter() {
+            let inner = &term.pod_affinity_term;
+            if inner.label_selector.matches(&pod.metadata.labels) {
+                inner.add_to_domain(&mut counts, other_node, -term.weight);
+            }
+        }
+    }
+    return counts;
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::uid_allocator::UidAllocator;
+
+    fn make_node(uid: u64, zone: &str) -> Node {
+        let mut node = Node::default();
+        node.spec.installed_cpu = 100;
+        node.spec.installed_memory = 100;
+        node.metadata.labels = BTreeMap::from([("zone".to_string(), zone.to_string())]);
+        node.prepare(1, &mut UidAllocator::new());
+        node.metadata.uid = uid;
+        return node;
+    }
+
+    fn make_pod(uid: u64, app: &str, node_uid: Option<u64>) -> Pod {
+        let mut pod = Pod::default();
+        pod.metadata.uid = uid;
+        pod.metadata.labels = BTreeMap::from([("app".to_string(), app.to_string())]);
+        pod.status.node_uid = node_uid;
+        return pod;
+    }
+
+    fn app_term(app: &str) -> PodAffinityTerm {
+        return PodAffinityTerm {
+            label_selector: LabelSelector {
+                match_labels: BTreeMap::from([("app".to_string(), app.to_string())]),
+                match_expressions: vec![],
+            },
+            topology_key: "zone".to_string(),
+        };
+    }
+
+    #[test]
+    fn test_label_selector() {
+        let labels = BTreeMap::from([
+            ("app".to_string(), "web".to_string()),
+            ("tier".to_string(), "front".to_string()),
+        ]);
+
+        assert!(LabelSelector::default().matches(&labels));
+        assert!(app_term("web").label_selector.matches(&labels));
+        assert!(!app_term("db").label_selector.matches(&labels));
+
+        let mut selector = LabelSelector::default();
+        selector.match_expressions = vec![LabelSelectorRequirement {
+            key: "tier".to_string(),
+            operator: LabelSelectorOperator::NotIn,
+            values: vec!["back".to_string()],
+        }];
+        assert!(selector.matches(&labels));
+
+        selector.match_expressions.push(LabelSelectorRequirement {
+            key: "gpu".to_string(),
+            operator: LabelSelectorOperator::Exists,
+            values: vec![],
+        });
+        assert!(!selector.matches(&labels));
+    }
+
+    #[test]
+    fn test_pod_affinity() {
+        let nodes = HashMap::from([(1, make_node(1, "a")), (2, make_node(2, "a")), (3, make_node(3, "b"))]);
+        let running_pods = HashMap::from([(10, make_pod(10, "cache", Some(1)))]);
+
+        // Affinity to cache pods is satisfied in zone "a" only
+        let mut pod = make_pod(20, "web", None);
+        pod.spec.pod_affinity.required_terms = vec![app_term("cache")];
+        let counts = PodAffinityCounts::new(&running_pods, &nodes, &pod);
+        assert_eq!(counts.affinity, vec![HashMap::from([("a".to_string(), 1)])]);
+        assert!(counts.is_required_matches(&pod, &nodes[&1]));
+        assert!(counts.is_required_matches(&pod, &nodes[&2]));
+        assert!(!counts.is_required_matches(&pod, &nodes[&3]));
+
+        // First pod of a series with affinity to itself is allowed everywhere
+        let mut pod = make_pod(21, "batch", None);
+        pod.spec.pod_affinity.required_terms = vec![app_term("batch")];
+        let counts = PodAffinityCounts::new(&running_pods, &nodes, &pod);
+        assert!(counts.is_first_of_series);
+        assert!(counts.is_required_matches(&pod, &nodes[&3]));
+
+        // Anti-affinity to cache pods forbids zone "a"
+        let mut pod = make_pod(22, "web", None);
+        pod.spec.pod_anti_affinity.required_terms = vec![app_term("cache")];
+        let counts = PodAffinityCounts::new(&running_pods, &nodes, &pod);
+        assert!(!counts.is_required_matches(&pod, &nodes[&2]));
+        assert!(counts.is_required_matches(&pod, &nodes[&3]));
+
+        // Existing pod anti-affinity is symmetric
+        let mut running_pods = running_pods.clone();
+        running_pods.get_mut(&10).unwrap().spec.pod_anti_affinity.required_terms = vec![app_term("web")];
+        let pod = make_pod(23, "web", None);
+        let counts = PodAffinityCounts::new(&running_pods, &nodes, &pod);
+        assert!(!counts.is_required_matches(&pod, &nodes[&2]));
+        assert!(counts.is_required_matches(&pod, &nodes[&3]));
+    }
+
+    #[test]
+    fn test_pod_affinity_preferred() {
+        let nodes = HashMap::from([(1, make_node(1, "a")), (2, make_node(2, "b"))]);
+        let running_pods = HashMap::from([
+            (10, make_pod(10, "cache", Some(1))),
+            (11, make_pod(11, "cache", Some(1))),
+            (12, make_pod(12, "web", Some(2))),
+        ]);
+
+        let mut pod = make_pod(20, "web", None);
+        pod.spec.pod_affinity.preferred_terms = vec![PodAffinityPreferredTerm {
+            pod_affinity_term: app_term("cache"),
+            weight: 5,
+        }];
+        pod.spec.pod_anti_affinity.preferred_terms = vec![PodAffinityPreferredTerm {
+            pod_affinity_term: app_term("web"),
+            weight: 3,
+        }];
+
+        let counts = count_preferred_domains(&running_pods, &nodes, &pod);
+        assert_eq!(sum_in_domains(&counts, &nodes[&1]), 10);
+        assert_eq!(sum_in_domains(&counts, &nodes[&2]), -3);
+
+        // Preferred terms of running pods matching pod are counted in their domains
+        let mut running_pods = running_pods.clone();
+        running_pods.get_mut(&12).unwrap().spec.pod_affinity.required_terms = vec![app_term("web")];
+        running_pods.get_mut(&10).unwrap().spec.pod_anti_affinity.preferred_terms = vec![PodAffinityPreferredTerm {
+            pod_affinity_term: app_term("web"),
+            weight: 4,
+        }];
+        let counts = count_preferred_domains(&running_pods, &nodes, &pod);
+        assert_eq!(sum_in_domains(&counts, &nodes[&1]), 10 - 4);
+        assert_eq!(sum_in_domains(&counts, &nodes[&2]), -3 + HARD_POD_AFFINITY_WEIGHT);
+        assert_eq!(sum_in_domains(&counts, &make_node(3, "c")), 0);
+    }
+
+    #[test]
+    fn test_pod_affinity_from_str() {
+        let affinity: PodAffinity = str::parse("{zone,app:cache;host};{10,zone,app:web,tier:front}").unwrap();
+        assert_eq!(affinity.required_terms.len(), 2);
+        assert_eq!(affinity.required_terms[0], app_term("cache"));
+        assert_eq!(affinity.required_terms[1].topology_key, "host");
+        assert!(affinity.required_terms[1].label_selector.match_labels.is_empty());
+        assert_eq!(affinity.preferred_terms.len(), 1);
+        assert_eq!(affinity.preferred_terms[0].weight, 10);
+        assert_eq!(affinity.preferred_terms[0].pod_affinity_term.label_selector.match_labels.len(), 2);
+
+        let anti_affinity: PodAntiAffinity = str::parse("{};{}").unwrap();
+        assert_eq!(anti_affinity, PodAntiAffinity::default());
+    }
+}
@@ -0,0 +1,187 @@
+use crate::objects::node::Node;
+use crate::objects::pod::{Pod, PodPhase};
+use crate::objects::priority_class::PreemptionPolicy;
+use std::collections::HashMap;
+
+// https://kubernetes.io/docs/concepts/scheduling-eviction/pod-priority-preemption/#preemption
+/// Pods which should be preempted from node to place pending pod there.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PreemptionCandidate {
+    pub node_uid: u64,
+    /// Victims sorted by priority descending, then by uid.
+    pub victims: Vec<u64>,
+    /// Maximal priority among victims (i64::MIN if there are no victims).
+    pub max_victim_priority: i64,
+    /// Victims which would violate PodDisruptionBudget of their groups.
+    pub pdb_violations: u64,
+}
+
+impl PreemptionCandidate {
+    /// Candidates are compared by: fewest PodDisruptionBudget violations, fewest victims,
+    /// lowest max victim priority, lowest node uid. As in kube-scheduler, budgets are respected
+    /// on a best-effort basis: violating candidate is chosen only if there is no other.
+    pub fn is_better_than(&self, other: &PreemptionCandidate) -> bool {
+        let key = (
+            self.pdb_violations,
+            self.victims.len(),
+            self.max_victim_priority,
+            self.node_uid,
+        );
+        let other_key = (
+            other.pdb_violations,
+            other.victims.len(),
+            other.max_victim_priority,
+            other.node_uid,
+        );
+        return key < other_key;
+    }
+}
+
+/// Returns minimal set of pods with priority lower than pod's, which must be removed from node to fit pod.
+/// None if pod does not fit node even without all of them.
+/// As kube-scheduler does, firstly all lower priority pods are removed,
+/// then they are reprieved one by one starting from the highest priority while pod still fits.
+/// Only running pods may be victims: pods waiting on permit or already preempted are skipped.
+/// Pods with preemption policy Never do not preempt at all.
+pub fn select_victims(running_pods: &HashMap<u64, Pod>, pod: &Pod, node: &Node) -> Option<PreemptionCandidate> {
+    if pod.spec.preemption_policy == PreemptionPolicy::Never {
+        return None;
+    }
+
+    let mut potential: Vec<&Pod> = node
+        .status
+        .pods
+        .iter()
+        .map(|uid| running_pods.get(uid).unwrap())
+        .filter(|other| other.status.phase == PodPhase::Running && other.spec.priority < pod.spec.priority)
+        .collect();
+    potential.sort_by_key(|other| (-other.spec.priority, other.metadata.uid));
+
+    // Remove all potential victims
+    let (mut cpu, mut memory) = (node.spec.available_cpu, node.spec.available_memory);
+    for other in potential.iter() {
+        cpu += other.spec.request_cpu;
+        memory += other.spec.request_memory;
+    }
+    if cpu < pod.spec.request_cpu || memory < pod.spec.request_memory {
+        return None;
+    }
+
+    // Reprieve as many as possible
+    let mut victims: Vec<u64> = Vec::new();
+    let mut max_victim_priority = i64::MIN;
+    for other in potential {
+        let (rest_cpu, rest_memory) = (cpu - other.spec.request_cpu, memory - other.spec.request_memory);
+        if rest_cpu >= pod.spec.request_cpu && rest_memory >= pod.spec.request_memory {
+            cpu = rest_cpu;
+            memory = rest_memory;
+        } else {
+            victims.push(other.metadata.uid);
+            max_victim_priority = max_victim_priority.max(other.spec.priority);
+        }
+    }
+
+    return Some(PreemptionCandidate {
+        node_uid: node.metadata.uid,
+        victims,
+        max_victim_priority,
+        pdb_violations: 0,
+    });
+}
+
+/// Copy of node as if victims were already removed from it.
+pub fn node_without_victims(running_pods: &HashMap<u64, Pod>, node: &Node, victims: &Vec<u64>) -> Node {
+    let mut result = node.clone();
+    for uid in victims.iter() {
+        let victim = running_pods.get(uid).unwrap();
+        result.restore(victim.spec.request_cpu, victim.spec.request_memory);
+        result.status.pods.remove(uid);
+    }
+    return result;
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::uid_allocator::UidAllocator;
+
+    fn make_node(uid: u64, pods: &[&Pod]) -> Node {
+        let mut node = Node::default();
+        node.spec.installed_cpu = 100;
+        node.spec.installed_memory = 100;
+        node.prepare(1, &mut UidAllocator::new());
+        node.metadata.uid = uid;
+        for pod in pods {
+            node.consume(pod.spec.request_cpu, pod.spec.request_memory);
+            node.status.pods.insert(pod.metadata.uid);
+        }
+        return node;
+    }
+
+    fn make_pod(uid: u64, priority: i64, cpu: i64) -> Pod {
+        let mut pod = Pod::default();
+        pod.metadata.uid = uid;
+        pod.spec.priority = priority;
+        pod.spec.request_cpu = cpu;
+        pod.spec.request_memory = cpu;
+        pod.status.phase = PodPhase::Running;
+        return pod;
+    }
+
+    #[test]
+    fn test_select_victims() {
+        let pods = [make_pod(10, 1, 20), make_pod(11, 3, 30), make_pod(12, 2, 20), make_pod(13, 9, 30)];
+        let running_pods: HashMap<u64, Pod> = pods.iter().map(|pod| (pod.metadata.uid, pod.clone())).collect();
+        let node = make_node(1, &pods.iter().collect::<Vec<&Pod>>());
+
+        // 40 is needed: reprieve 11 (3) first, then 12 (2) still fits, 10 (1) is the only victim
+        let candidate = select_victims(&running_pods, &make_pod(20, 5, 20), &node).unwrap();
+        assert_eq!(candidate.victims, vec![10]);
+        assert_eq!(candidate.max_victim_priority, 1);
+
+        // Reprieving the highest priority pod first
+        let candidate = select_victims(&running_pods, &make_pod(20, 5, 40), &node).unwrap();
+        assert_eq!(candidate.victims, vec![12, 10]);
+
+        // Higher or equal priority pods are never victims
+        assert!(select_victims(&running_pods, &make_pod(20, 5, 80), &node).is_none());
+        assert!(select_victims(&running_pods, &make_pod(20, 1, 10), &node).is_none());
+
+        // Pod which fits does not need victims
+        let candidate = select_victims(&running_pods, &make_pod(20, 0, 0), &node).unwrap();
+        assert!(candidate.victims.is_empty());
+
+        // Non-preempting pod
+        let mut pod = make_pod(20, 5, 20);
+        pod.spec.preemption_policy = PreemptionPolicy::Never;
+        assert!(select_victims(&running_pods, &pod, &node).is_none());
+
+        // Pods which are not running are skipped
+        let mut running_pods = running_pods.clone();
+        running_pods.get_mut(&10).unwrap().status.phase = PodPhase::Preempted;
+        let candidate = select_victims(&running_pods, &make_pod(20, 5, 20), &node).unwrap();
+        assert_eq!(candidate.victims, vec![12]);
+
+        let node = node_without_victims(&running_pods, &node, &candidate.victims);
+        assert_eq!(node.spec.available_cpu, 20);
+        assert!(!node.status.pods.contains(&12));
+    }
+
+    #[test]
+    fn test_preemption_candidate_order() {
+        let make = |node_uid: u64, victims: Vec<u64>, max_victim_priority: i64| PreemptionCandidate { node_uid, victims, max_victim_priority, pdb_violations: 0 };
+
+        assert!(make(2, vec![1], 5).is_better_than(&make(1, vec![2, 3], 1)));
+        assert!(make(2, vec![1], 1).is_better_than(&make(1, vec![2], 5)));
+        assert!(make(1, vec![1], 1).is_better_than(&make(2, vec![2], 1)));
+        assert!(!make(1, vec![1], 1).is_better_than(&make(1, vec![1], 1)));
+
+        // Candidate violating budget is the last resort
+        let violating = PreemptionCandidate { pdb_violations: 1, ..make(1, vec![1], 1) };
+        assert!(make(2, vec![2, 3], 5).is_better_than(&violating));
+        assert!(!violating.is_better_than(&make(2, vec![2, 3], 5)));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::{split_field, KuberSimError};
+
 // https://kubernetes.io/docs/concepts/scheduling-eviction/taint-and-toleration/
 #[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum TaintTolerationEffect {
@@ -10,14 +12,17 @@ pub enum TaintTolerationEffect {
 }
 
 impl std::str::FromStr for TaintTolerationEffect {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "u8"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "0" => Ok(TaintTolerationEffect::NoSchedule),
             "1" => Ok(TaintTolerationEffect::PreferNoSchedule),
-            _ => Err(()),
+            _ => Err(KuberSimError::invalid(
+                "TaintTolerationEffect",
+                format!("Unexpected value '{}'", s),
+            )),
         }
     }
 }
@@ -33,14 +38,17 @@ pub enum TaintTolerationOperator {
 }
 
 impl std::str::FromStr for TaintTolerationOperator {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "u8"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "0" => Ok(TaintTolerationOperator::Equal),
             "1" => Ok(TaintTolerationOperator::Exists),
-            _ => Err(()),
+            _ => Err(KuberSimError::invalid(
+                "TaintTolerationOperator",
+                format!("Unexpected value '{}'", s),
+            )),
         }
     }
 }
@@ -70,19 +78,19 @@ pub struct Toleration {
 }
 
 impl std::str::FromStr for Toleration {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "<key: String>,<value: String>,<TaintTolerationOperator>,<TaintTolerationEffect>"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (key, other) = s.split_once(',').unwrap();
-        let (value, other) = other.split_once(',').unwrap();
-        let (operator_str, effect_str) = other.split_once(',').unwrap();
+        let (key, other) = split_field(s, ',', "Toleration.key")?;
+        let (value, other) = split_field(other, ',', "Toleration.value")?;
+        let (operator_str, effect_str) = split_field(other, ',', "Toleration.operator")?;
 
         Ok(Self {
             key: key.to_string(),
             value: value.to_string(),
-            operator: str::parse(operator_str).unwrap(),
-            effect: str::parse(effect_str).unwrap(),
+            operator: str::parse(operator_str)?,
+            effect: str::parse(effect_str)?,
         })
     }
 }
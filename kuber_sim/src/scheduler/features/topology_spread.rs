@@ -0,0 +1,216 @@
+use crate::error::{parse_field, split_field, KuberSimError};
+use crate::objects::node::Node;
+use crate::objects::pod::Pod;
+use crate::scheduler::features::pod_affinity::LabelSelector;
+use std::collections::HashMap;
+
+// https://kubernetes.io/docs/concepts/scheduling-eviction/topology-spread-constraints/
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum WhenUnsatisfiable {
+    /// DoNotSchedule = Tells the scheduler not to schedule pod if constraint is violated.
+    #[default]
+    DoNotSchedule = 0,
+    /// ScheduleAnyway = Tells the scheduler to still schedule pod while prioritizing nodes that minimize the skew.
+    ScheduleAnyway = 1,
+}
+
+impl std::str::FromStr for WhenUnsatisfiable {
+    type Err = KuberSimError;
+
+    /// Expects "u8"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(WhenUnsatisfiable::DoNotSchedule),
+            "1" => Ok(WhenUnsatisfiable::ScheduleAnyway),
+            _ => Err(KuberSimError::invalid(
+                "WhenUnsatisfiable",
+                format!("Unexpected value '{}'", s),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TopologySpreadConstraint {
+    /// The degree to which matching pods may be unevenly distributed. Must be > 0.
+    pub max_skew: i64,
+    /// Node label key. Nodes with the same value of this label are in the same topology domain.
+    pub topology_key: String,
+    /// Indicates how to deal with a pod if it doesn't satisfy the spread constraint.
+    #[serde(default)]
+    pub when_unsatisfiable: WhenUnsatisfiable,
+    /// A label query over pods which are counted to determine the number of pods in their domain.
+    #[serde(default)]
+    pub label_selector: LabelSelector,
+}
+
+impl std::str::FromStr for TopologySpreadConstraint {
+    type Err = KuberSimError;
+
+    /// Expects "<max_skew: i64>,<topology_key: String>,<when_unsatisfiable: 0|1>,<key_1>:<value_1>,..."
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (max_skew_str, other) = split_field(s, ',', "TopologySpreadConstraint.max_skew")?;
+        let (topology_key, other) = split_field(other, ',', "TopologySpreadConstraint.topology_key")?;
+        let (when_unsatisfiable_str, selector_str) = other.split_once(',').unwrap_or((other, ""));
+
+        Ok(Self {
+            max_skew: parse_field(max_skew_str, "TopologySpreadConstraint.max_skew")?,
+            topology_key: topology_key.to_string(),
+            when_unsatisfiable: str::parse(when_unsatisfiable_str)?,
+            label_selector: str::parse(selector_str)?,
+        })
+    }
+}
+
+/// Counts of pods matching a constraint per topology domain.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct TopologySpreadCounts {
+    pub domains: HashMap<String, i64>, // HashMap<domain, matching_pods_count>
+    /// Minimal matching pods count over all domains (0 if there are no domains).
+    pub min_count: i64,
+    /// Maximal matching pods count over all domains (0 if there are no domains).
+    pub max_count: i64,
+}
+
+impl TopologySpreadConstraint {
+    pub fn prepare(&self) -> Result<(), KuberSimError> {
+        sim_ensure!(self.max_skew > 0, "TopologySpreadConstraint.max_skew", "must be > 0");
+        return Ok(());
+    }
+
+    /// Is node taken into account when counting domains for pod.
+    /// As in Kubernetes, only nodes matching pod's node selector and required node affinity are counted.
+    pub fn is_node_eligible(&self, pod: &Pod, node: &Node) -> bool {
+        if !node.metadata.labels.contains_key(&self.topology_key) {
+            return false;
+        }
+        for (key, value) in pod.spec.node_selector.iter() {
+            if node.metadata.labels.get(key) != Some(value) {
+                return false;
+            }
+        }
+        return pod.spec.node_affinity.is_required_matches(node);
+    }
+
+    /// Counts running pods matching label_selector per topology domain of nodes eligible for pod.
+    /// Works in O(nodes + running_pods).
+    pub fn count_domains(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        nodes: &HashMap<u64, Node>,
+        pod: &Pod,
+    ) -> TopologySpreadCounts {
+        let mut domains: HashMap<String, i64> = HashMap::new();
+        let mut node_domains: HashMap<u64, &String> = HashMap::new(); // HashMap<node_uid, domain>
+
+        for (&node_uid, node) in nodes.iter() {
+            if self.is_node_eligible(pod, node) {
+                let domain = node.metadata.labels.get(&self.topology_key).unwrap();
+                domains.entry(domain.clone()).or_insert(0);
+                node_domains.insert(node_uid, domain);
+            }
+        }
+
+        for other in running_pods.values() {
+            let domain = match other.status.node_uid.and_then(|uid| node_domains.get(&uid)) {
+                Some(&domain) => domain,
+                None => continue,
+            };
+            if self.label_selector.matches(&other.metadata.labels) {
+                *domains.get_mut(domain).unwrap() += 1;
+            }
+        }
+
+        let min_count = domains.values().copied().min().unwrap_or(0);
+        let max_count = domains.values().copied().max().unwrap_or(0);
+        return TopologySpreadCounts {
+            domains,
+            min_count,
+            max_count,
+        };
+    }
+
+    /// Skew of node's domain if pod is placed to node. None if node has no topology_key label.
+    pub fn skew(&self, counts: &TopologySpreadCounts, pod: &Pod, node: &Node) -> Option<i64> {
+        let domain = node.metadata.labels.get(&self.topology_key)?;
+        let count = counts.domains.get(domain).copied().unwrap_or(0);
+        let self_match = self.label_selector.matches(&pod.metadata.labels) as i64;
+        return Some(count + self_match - counts.min_count);
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::uid_allocator::UidAllocator;
+    use std::collections::BTreeMap;
+
+    fn make_node(uid: u64, zone: Option<&str>) -> Node {
+        let mut node = Node::default();
+        node.spec.installed_cpu = 100;
+        node.spec.installed_memory = 100;
+        if let Some(zone) = zone {
+            node.metadata.labels = BTreeMap::from([("zone".to_string(), zone.to_string())]);
+        }
+        node.prepare(1, &mut UidAllocator::new());
+        node.metadata.uid = uid;
+        return node;
+    }
+
+    fn make_pod(uid: u64, app: &str, node_uid: Option<u64>) -> Pod {
+        let mut pod = Pod::default();
+        pod.metadata.uid = uid;
+        pod.metadata.labels = BTreeMap::from([("app".to_string(), app.to_string())]);
+        pod.status.node_uid = node_uid;
+        return pod;
+    }
+
+    #[test]
+    fn test_topology_spread() {
+        let nodes = HashMap::from([
+            (1, make_node(1, Some("a"))),
+            (2, make_node(2, Some("a"))),
+            (3, make_node(3, Some("b"))),
+            (4, make_node(4, Some("c"))),
+            (5, make_node(5, None)),
+        ]);
+        let running_pods = HashMap::from([
+            (10, make_pod(10, "web", Some(1))),
+            (11, make_pod(11, "web", Some(2))),
+            (12, make_pod(12, "web", Some(3))),
+            (13, make_pod(13, "db", Some(4))),
+            (14, make_pod(14, "web", Some(5))),
+        ]);
+
+        let constraint: TopologySpreadConstraint = str::parse("1,zone,0,app:web").unwrap();
+        assert_eq!(constraint.max_skew, 1);
+        assert_eq!(constraint.when_unsatisfiable, WhenUnsatisfiable::DoNotSchedule);
+        assert!(constraint.prepare().is_ok());
+        assert!(str::parse::<TopologySpreadConstraint>("0,zone,0,app:web").unwrap().prepare().is_err());
+
+        let pod = make_pod(20, "web", None);
+        let counts = constraint.count_domains(&running_pods, &nodes, &pod);
+        assert_eq!(counts.domains, HashMap::from([("a".to_string(), 2), ("b".to_string(), 1), ("c".to_string(), 0)]));
+        assert_eq!(counts.min_count, 0);
+        assert_eq!(counts.max_count, 2);
+
+        assert_eq!(constraint.skew(&counts, &pod, &nodes[&1]), Some(3));
+        assert_eq!(constraint.skew(&counts, &pod, &nodes[&3]), Some(2));
+        assert_eq!(constraint.skew(&counts, &pod, &nodes[&4]), Some(1));
+        assert_eq!(constraint.skew(&counts, &pod, &nodes[&5]), None);
+
+        // Pod which does not match selector does not add to skew
+        let pod = make_pod(21, "db", None);
+        assert_eq!(constraint.skew(&counts, &pod, &nodes[&4]), Some(0));
+
+        // Nodes not matching node selector are not counted
+        let mut pod = make_pod(22, "web", None);
+        pod.spec.node_selector = BTreeMap::from([("zone".to_string(), "a".to_string())]);
+        let counts = constraint.count_domains(&running_pods, &nodes, &pod);
+        assert_eq!(counts.domains, HashMap::from([("a".to_string(), 2)]));
+        assert_eq!(constraint.skew(&counts, &pod, &nodes[&1]), Some(1));
+    }
+}
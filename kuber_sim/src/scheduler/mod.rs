@@ -1,3 +1,4 @@
+pub mod cache;
 pub mod features;
 pub mod node_index;
 pub mod pipeline;
@@ -1,6 +1,12 @@
 use crate::objects::node::Node;
 use crate::objects::pod::Pod;
+use crate::scheduler::features::pod_affinity::PodAffinityCounts;
+use crate::scheduler::features::preemption;
 use crate::scheduler::features::taints_tolerations::TaintTolerationEffect;
+use crate::scheduler::features::topology_spread::{TopologySpreadCounts, WhenUnsatisfiable};
+use crate::scheduler::pipeline::permit;
+use crate::scheduler::queues::queueing_hints::ClusterEvent;
+use std::cell::RefCell;
 use std::collections::HashMap;
 
 pub type FilterPluginF = fn(&HashMap<u64, Pod>, &HashMap<u64, Pod>, &HashMap<u64, Node>, &Pod, &Node) -> bool;
@@ -8,6 +14,13 @@ pub type FilterPluginF = fn(&HashMap<u64, Pod>, &HashMap<u64, Pod>, &HashMap<u64
 pub trait IFilterPlugin {
     fn name(&self) -> String;
 
+    /// PreFilter. Called once per scheduling attempt before any node is filtered.
+    /// Plugins may precompute here the state shared across nodes.
+    /// Returns false if pod cannot be scheduled in this attempt at all (e.g. quota is exceeded).
+    fn pre_filter(&self, _: &HashMap<u64, Pod>, _: &HashMap<u64, Pod>, _: &HashMap<u64, Node>, _: &Pod) -> bool {
+        return true;
+    }
+
     fn filter(
         &self,
         running_pods: &HashMap<u64, Pod>,
@@ -17,6 +30,12 @@ pub trait IFilterPlugin {
         node: &Node,
     ) -> bool;
 
+    /// Cluster events which may make pod rejected by this plugin schedulable again (queueing hints).
+    /// Unschedulable pod is moved to ActiveQ when any of them happens.
+    fn events_to_register(&self) -> Vec<ClusterEvent> {
+        return ClusterEvent::all();
+    }
+
     fn clone(&self) -> Box<dyn IFilterPlugin + Send>;
 }
 
@@ -51,6 +70,10 @@ impl IFilterPlugin for FilterAlwaysFalse {
         return false;
     }
 
+    fn events_to_register(&self) -> Vec<ClusterEvent> {
+        return Vec::new();
+    }
+
     fn clone(&self) -> Box<dyn IFilterPlugin + Send> {
         return Box::new(FilterAlwaysFalse);
     }
@@ -86,6 +109,10 @@ impl IFilterPlugin for FilterNodeSelector {
         return true;
     }
 
+    fn events_to_register(&self) -> Vec<ClusterEvent> {
+        return vec![ClusterEvent::NodeAdded, ClusterEvent::NodeLabelsChanged];
+    }
+
     fn clone(&self) -> Box<dyn IFilterPlugin + Send> {
         return Box::new(FilterNodeSelector);
     }
@@ -111,6 +138,10 @@ impl IFilterPlugin for FilterRequestedResourcesAvailable {
         return node.is_both_consumable(pod.spec.request_cpu, pod.spec.request_memory);
     }
 
+    fn events_to_register(&self) -> Vec<ClusterEvent> {
+        return vec![ClusterEvent::NodeAdded, ClusterEvent::PodTerminated];
+    }
+
     fn clone(&self) -> Box<dyn IFilterPlugin + Send> {
         return Box::new(FilterRequestedResourcesAvailable);
     }
@@ -154,6 +185,10 @@ impl IFilterPlugin for FilterTaintsTolerations {
         return true;
     }
 
+    fn events_to_register(&self) -> Vec<ClusterEvent> {
+        return vec![ClusterEvent::NodeAdded, ClusterEvent::NodeTaintsChanged];
+    }
+
     fn clone(&self) -> Box<dyn IFilterPlugin + Send> {
         return Box::new(FilterTaintsTolerations);
     }
@@ -179,6 +214,10 @@ impl IFilterPlugin for FilterNodeAffinity {
         return pod.spec.node_affinity.is_required_matches(&node);
     }
 
+    fn events_to_register(&self) -> Vec<ClusterEvent> {
+        return vec![ClusterEvent::NodeAdded, ClusterEvent::NodeLabelsChanged];
+    }
+
     fn clone(&self) -> Box<dyn IFilterPlugin + Send> {
         return Box::new(FilterNodeAffinity);
     }
@@ -186,6 +225,106 @@ impl IFilterPlugin for FilterNodeAffinity {
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
+#[derive(Default)]
+pub struct FilterPodAffinity {
+    /// Domain counts of pod's and running pods' required terms computed in pre_filter.
+    counts: RefCell<PodAffinityCounts>,
+}
+
+impl IFilterPlugin for FilterPodAffinity {
+    fn name(&self) -> String {
+        return "FilterPodAffinity".to_string();
+    }
+
+    fn pre_filter(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        nodes: &HashMap<u64, Node>,
+        pod: &Pod,
+    ) -> bool {
+        *self.counts.borrow_mut() = PodAffinityCounts::new(running_pods, nodes, pod);
+        return true;
+    }
+
+    fn filter(
+        &self,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        pod: &Pod,
+        node: &Node,
+    ) -> bool {
+        return self.counts.borrow().is_required_matches(pod, node);
+    }
+
+    fn clone(&self) -> Box<dyn IFilterPlugin + Send> {
+        return Box::new(FilterPodAffinity::default());
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Default)]
+pub struct FilterTopologySpread {
+    /// Domain counts of pod's DoNotSchedule constraints computed in pre_filter.
+    counts: RefCell<Vec<TopologySpreadCounts>>,
+}
+
+impl IFilterPlugin for FilterTopologySpread {
+    fn name(&self) -> String {
+        return "FilterTopologySpread".to_string();
+    }
+
+    fn pre_filter(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        nodes: &HashMap<u64, Node>,
+        pod: &Pod,
+    ) -> bool {
+        let mut counts = self.counts.borrow_mut();
+        counts.clear();
+        for constraint in pod.spec.topology_spread_constraints.iter() {
+            if constraint.when_unsatisfiable == WhenUnsatisfiable::DoNotSchedule {
+                counts.push(constraint.count_domains(running_pods, nodes, pod));
+            }
+        }
+        return true;
+    }
+
+    fn filter(
+        &self,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        pod: &Pod,
+        node: &Node,
+    ) -> bool {
+        let counts = self.counts.borrow();
+        let constraints = pod
+            .spec
+            .topology_spread_constraints
+            .iter()
+            .filter(|constraint| constraint.when_unsatisfiable == WhenUnsatisfiable::DoNotSchedule);
+
+        for (constraint, constraint_counts) in constraints.zip(counts.iter()) {
+            match constraint.skew(constraint_counts, pod, node) {
+                Some(skew) if skew <= constraint.max_skew => {}
+                _ => return false,
+            }
+        }
+        return true;
+    }
+
+    fn clone(&self) -> Box<dyn IFilterPlugin + Send> {
+        return Box::new(FilterTopologySpread::default());
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// PostFilter. Node is schedulable if pod fits it after preemption of lower priority pods.
 pub struct FilterPreemption;
 
 impl IFilterPlugin for FilterPreemption {
@@ -201,25 +340,54 @@ impl IFilterPlugin for FilterPreemption {
         pod: &Pod,
         node: &Node,
     ) -> bool {
-        let (mut cpu, mut memory) = (node.spec.available_cpu, node.spec.available_memory);
-        for &tmp_uid in node.status.pods.iter() {
-            let tmp_pod = running_pods.get(&tmp_uid).unwrap();
-            if tmp_pod.spec.priority >= pod.spec.priority {
-                continue;
-            }
+        return preemption::select_victims(running_pods, pod, node).is_some();
+    }
 
-            cpu += tmp_pod.spec.request_cpu;
-            memory += tmp_pod.spec.request_memory;
+    fn events_to_register(&self) -> Vec<ClusterEvent> {
+        return vec![ClusterEvent::NodeAdded, ClusterEvent::PodTerminated];
+    }
 
-            if cpu >= pod.spec.request_cpu && memory >= pod.spec.request_memory {
-                return true;
-            }
+    fn clone(&self) -> Box<dyn IFilterPlugin + Send> {
+        return Box::new(FilterPreemption);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Rejects pod of a gang in PreFilter if its group has less than min_member pods,
+/// so they do not reserve nodes in vain.
+pub struct FilterCoscheduling;
+
+impl IFilterPlugin for FilterCoscheduling {
+    fn name(&self) -> String {
+        return "FilterCoscheduling".to_string();
+    }
+
+    fn pre_filter(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        pending_pods: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        pod: &Pod,
+    ) -> bool {
+        let min_member = pod.metadata.group_min_member;
+        if min_member == 0 {
+            return true;
         }
 
-        return false;
+        let group_uid = pod.metadata.group_uid;
+        let pending = pending_pods
+            .values()
+            .filter(|other| other.metadata.group_uid == group_uid)
+            .count() as u64;
+        return permit::count_gang_members(running_pods, group_uid) + pending >= min_member;
+    }
+
+    fn filter(&self, _: &HashMap<u64, Pod>, _: &HashMap<u64, Pod>, _: &HashMap<u64, Node>, _: &Pod, _: &Node) -> bool {
+        return true;
     }
 
     fn clone(&self) -> Box<dyn IFilterPlugin + Send> {
-        return Box::new(FilterPreemption);
+        return Box::new(FilterCoscheduling);
     }
 }
@@ -1,3 +1,7 @@
 pub mod filter;
+pub mod permit;
+pub mod pre_bind;
+pub mod registry;
+pub mod reserve;
 pub mod score;
 pub mod score_normalize;
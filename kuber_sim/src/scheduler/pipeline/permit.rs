@@ -0,0 +1,151 @@
+use crate::error::KuberSimError;
+use crate::objects::node::Node;
+use crate::objects::pod::{Pod, PodPhase};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PermitStatus {
+    /// Pod can be bound.
+    Allow,
+    /// Pod is unreserved and returned to the queues.
+    Reject,
+    /// Pod keeps its reservation, but is not bound for at most timeout seconds.
+    Wait(f64),
+}
+
+impl PermitStatus {
+    /// Status of pod for several plugins. Reject wins, then the shortest Wait.
+    pub fn combine(self, other: PermitStatus) -> PermitStatus {
+        return match (self, other) {
+            (PermitStatus::Reject, _) | (_, PermitStatus::Reject) => PermitStatus::Reject,
+            (PermitStatus::Wait(lhs), PermitStatus::Wait(rhs)) => PermitStatus::Wait(lhs.min(rhs)),
+            (PermitStatus::Wait(timeout), _) | (_, PermitStatus::Wait(timeout)) => PermitStatus::Wait(timeout),
+            (PermitStatus::Allow, PermitStatus::Allow) => PermitStatus::Allow,
+        };
+    }
+}
+
+/// Called after reserve plugins. Waiting pod is asked again on each scheduling cycle
+/// until all plugins allow it, any plugin rejects it or its timeout expires.
+pub trait IPermitPlugin {
+    fn name(&self) -> String;
+
+    fn permit(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        pending_pods: &HashMap<u64, Pod>,
+        nodes: &HashMap<u64, Node>,
+        pod: &Pod,
+        node: &Node,
+    ) -> PermitStatus;
+
+    fn clone(&self) -> Box<dyn IPermitPlugin + Send>;
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Number of pods of group in scheduler cache which count towards its gang: bound running pods and
+/// pods assumed by scheduler (reserved or waiting on permit). Preempted victims and other pods awaiting
+/// removal by kubelet do not count.
+pub fn count_gang_members(running_pods: &HashMap<u64, Pod>, group_uid: u64) -> u64 {
+    return running_pods
+        .values()
+        .filter(|other| other.metadata.group_uid == group_uid)
+        .filter(|other| other.status.phase == PodPhase::Running || other.status.phase == PodPhase::Pending)
+        .count() as u64;
+}
+
+/// Permit barrier of gang scheduling (coscheduling).
+/// Pod of a group with min_member > 0 waits until min_member pods of its group are reserved or bound.
+pub struct PermitCoscheduling {
+    /// How long reserved pods of a gang wait for the rest of it.
+    pub timeout: f64,
+}
+
+impl PermitCoscheduling {
+    pub fn new(timeout: f64) -> Result<Self, KuberSimError> {
+        sim_ensure!(timeout > 0.0, "PermitCoscheduling.timeout", "must be > 0.0");
+        return Ok(Self { timeout });
+    }
+
+    /// Default timeout is the same as permitWaitingTimeSeconds of Kubernetes coscheduling plugin.
+    pub fn default() -> Self {
+        Self { timeout: 60.0 }
+    }
+}
+
+impl IPermitPlugin for PermitCoscheduling {
+    fn name(&self) -> String {
+        return "PermitCoscheduling".to_string();
+    }
+
+    fn permit(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        pod: &Pod,
+        _: &Node,
+    ) -> PermitStatus {
+        let min_member = pod.metadata.group_min_member;
+        if min_member == 0 {
+            return PermitStatus::Allow;
+        }
+
+        // Running pods include reserved ones, so pod counts itself
+        if count_gang_members(running_pods, pod.metadata.group_uid) >= min_member {
+            return PermitStatus::Allow;
+        }
+        return PermitStatus::Wait(self.timeout);
+    }
+
+    fn clone(&self) -> Box<dyn IPermitPlugin + Send> {
+        return Box::new(PermitCoscheduling { timeout: self.timeout });
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permit_status_combine() {
+        assert_eq!(PermitStatus::Allow.combine(PermitStatus::Allow), PermitStatus::Allow);
+        assert_eq!(PermitStatus::Allow.combine(PermitStatus::Wait(5.0)), PermitStatus::Wait(5.0));
+        assert_eq!(PermitStatus::Wait(5.0).combine(PermitStatus::Wait(3.0)), PermitStatus::Wait(3.0));
+        assert_eq!(PermitStatus::Wait(5.0).combine(PermitStatus::Reject), PermitStatus::Reject);
+        assert_eq!(PermitStatus::Reject.combine(PermitStatus::Allow), PermitStatus::Reject);
+    }
+
+    #[test]
+    fn test_permit_coscheduling() {
+        let make_pod = |uid: u64, group_uid: u64| {
+            let mut pod = Pod::default();
+            pod.metadata.uid = uid;
+            pod.metadata.group_uid = group_uid;
+            pod.metadata.group_min_member = 3;
+            return pod;
+        };
+
+        assert!(PermitCoscheduling::new(0.0).is_err());
+        let plugin = PermitCoscheduling::new(10.0).unwrap();
+        let nodes = HashMap::new();
+        let node = Node::default();
+        let mut running_pods = HashMap::from([(1, make_pod(1, 1)), (2, make_pod(2, 1)), (3, make_pod(3, 2))]);
+        // Preempted victim of the group is not a member of its gang
+        running_pods.insert(5, make_pod(5, 1));
+        running_pods.get_mut(&5).unwrap().status.phase = PodPhase::Preempted;
+
+        assert_eq!(plugin.permit(&running_pods, &HashMap::new(), &nodes, &running_pods[&1], &node), PermitStatus::Wait(10.0));
+        running_pods.insert(4, make_pod(4, 1));
+        assert_eq!(plugin.permit(&running_pods, &HashMap::new(), &nodes, &running_pods[&1], &node), PermitStatus::Allow);
+        assert_eq!(plugin.permit(&running_pods, &HashMap::new(), &nodes, &running_pods[&3], &node), PermitStatus::Wait(10.0));
+
+        // Pods without gang are allowed at once
+        let pod = Pod::default();
+        assert_eq!(plugin.permit(&running_pods, &HashMap::new(), &nodes, &pod, &node), PermitStatus::Allow);
+    }
+}
@@ -0,0 +1,19 @@
+use crate::objects::node::Node;
+use crate::objects::pod::Pod;
+use std::collections::HashMap;
+
+pub trait IPreBindPlugin {
+    fn name(&self) -> String;
+
+    /// Called right before pod is bound to node. Returns false to unreserve the pod.
+    fn pre_bind(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        pending_pods: &HashMap<u64, Pod>,
+        nodes: &HashMap<u64, Node>,
+        pod: &Pod,
+        node: &Node,
+    ) -> bool;
+
+    fn clone(&self) -> Box<dyn IPreBindPlugin + Send>;
+}
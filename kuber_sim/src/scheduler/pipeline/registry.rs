@@ -0,0 +1,303 @@
+use crate::error::KuberSimError;
+use crate::scheduler::pipeline::filter::*;
+use crate::scheduler::pipeline::permit::{IPermitPlugin, PermitCoscheduling};
+use crate::scheduler::pipeline::pre_bind::IPreBindPlugin;
+use crate::scheduler::pipeline::reserve::IReservePlugin;
+use crate::scheduler::pipeline::score::*;
+use crate::scheduler::pipeline::score_normalize::*;
+use crate::scheduler::queues::active_queue::{ActiveMaxQ, ActiveMinQ, IActiveQ};
+use crate::scheduler::queues::active_queue_cmp::{ActiveQCmpPriority, ActiveQCmpUid};
+use crate::scheduler::queues::backoff_queue::{BackOffQConstant, BackOffQExponential, IBackOffQ};
+use std::collections::HashMap;
+
+pub type ActiveQConstructor = fn(&PluginArgs) -> Result<Box<dyn IActiveQ + Send>, KuberSimError>;
+pub type BackOffQConstructor = fn(&PluginArgs) -> Result<Box<dyn IBackOffQ + Send>, KuberSimError>;
+pub type FilterPluginConstructor = fn(&PluginArgs) -> Result<Box<dyn IFilterPlugin + Send>, KuberSimError>;
+pub type ScorePluginConstructor = fn(&PluginArgs) -> Result<Box<dyn IScorePlugin + Send>, KuberSimError>;
+pub type ScoreNormalizePluginConstructor =
+    fn(&PluginArgs) -> Result<Box<dyn IScoreNormalizePlugin + Send>, KuberSimError>;
+pub type ReservePluginConstructor = fn(&PluginArgs) -> Result<Box<dyn IReservePlugin + Send>, KuberSimError>;
+pub type PermitPluginConstructor = fn(&PluginArgs) -> Result<Box<dyn IPermitPlugin + Send>, KuberSimError>;
+pub type PreBindPluginConstructor = fn(&PluginArgs) -> Result<Box<dyn IPreBindPlugin + Send>, KuberSimError>;
+
+/// Parameters of a plugin as YAML mapping. Missing parameters take their default values.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct PluginArgs(pub serde_yaml::Value);
+
+impl PluginArgs {
+    pub fn get<T: serde::de::DeserializeOwned>(&self, key: &str, default: T) -> Result<T, KuberSimError> {
+        return match self.0.get(key) {
+            Some(value) => serde_yaml::from_value(value.clone())
+                .map_err(|e| KuberSimError::invalid(format!("PluginArgs.{}", key), format!("Invalid value: {}", e))),
+            None => Ok(default),
+        };
+    }
+}
+
+/// Maps plugin names to their constructors.
+/// Plugin is registered under the same name as its name() returns, so profiles refer to plugins
+/// by the names which the simulation reports.
+pub struct PluginRegistry {
+    pub active_queues: HashMap<String, ActiveQConstructor>,
+    pub backoff_queues: HashMap<String, BackOffQConstructor>,
+    pub filters: HashMap<String, FilterPluginConstructor>,
+    pub scorers: HashMap<String, ScorePluginConstructor>,
+    pub score_normalizers: HashMap<String, ScoreNormalizePluginConstructor>,
+    pub reserves: HashMap<String, ReservePluginConstructor>,
+    pub permits: HashMap<String, PermitPluginConstructor>,
+    pub pre_binds: HashMap<String, PreBindPluginConstructor>,
+}
+
+impl PluginRegistry {
+    /// Creates registry without any plugins.
+    pub fn new() -> Self {
+        Self {
+            active_queues: HashMap::new(),
+            backoff_queues: HashMap::new(),
+            filters: HashMap::new(),
+            scorers: HashMap::new(),
+            score_normalizers: HashMap::new(),
+            reserves: HashMap::new(),
+            permits: HashMap::new(),
+            pre_binds: HashMap::new(),
+        }
+    }
+
+    /// Creates registry with all built-in plugins.
+    pub fn default() -> Self {
+        let mut registry = PluginRegistry::new();
+
+        // Active queues
+        registry.register_active_queue("ActiveQDefault", |_| {
+            Ok(Box::new(ActiveMinQ::<ActiveQCmpPriority>::new()))
+        });
+        registry.register_active_queue("ActiveMinQ<ActiveQCmpPriority>", |_| {
+            Ok(Box::new(ActiveMinQ::<ActiveQCmpPriority>::new()))
+        });
+        registry.register_active_queue("ActiveMaxQ<ActiveQCmpPriority>", |_| {
+            Ok(Box::new(ActiveMaxQ::<ActiveQCmpPriority>::new()))
+        });
+        registry.register_active_queue("ActiveMinQ<ActiveQCmpUid>", |_| {
+            Ok(Box::new(ActiveMinQ::<ActiveQCmpUid>::new()))
+        });
+        registry.register_active_queue("ActiveMaxQ<ActiveQCmpUid>", |_| {
+            Ok(Box::new(ActiveMaxQ::<ActiveQCmpUid>::new()))
+        });
+
+        // BackOff queues
+        registry.register_backoff_queue("BackOffQDefault", |_| Ok(Box::new(BackOffQExponential::default())));
+        registry.register_backoff_queue("BackOffQExponential", |args| {
+            Ok(Box::new(BackOffQExponential::new(
+                args.get("initial_backoff", 1.0)?,
+                args.get("max_backoff", 10.0)?,
+            )))
+        });
+        registry.register_backoff_queue("BackOffQConstant", |args| {
+            Ok(Box::new(BackOffQConstant::new(args.get("backoff_delay", 1.0)?)))
+        });
+
+        // Filters
+        registry.register_filter("FilterAlwaysTrue", |_| Ok(Box::new(FilterAlwaysTrue)));
+        registry.register_filter("FilterAlwaysFalse", |_| Ok(Box::new(FilterAlwaysFalse)));
+        registry.register_filter("FilterNodeSelector", |_| Ok(Box::new(FilterNodeSelector)));
+        registry.register_filter("FilterRequestedResourcesAvailable", |_| {
+            Ok(Box::new(FilterRequestedResourcesAvailable))
+        });
+        registry.register_filter("FilterTaintsTolerations", |_| Ok(Box::new(FilterTaintsTolerations)));
+        registry.register_filter("FilterNodeAffinity", |_| Ok(Box::new(FilterNodeAffinity)));
+        registry.register_filter("FilterPodAffinity", |_| Ok(Box::new(FilterPodAffinity::default())));
+        registry.register_filter("FilterTopologySpread", |_| {
+            Ok(Box::new(FilterTopologySpread::default()))
+        });
+        registry.register_filter("FilterPreemption", |_| Ok(Box::new(FilterPreemption)));
+        registry.register_filter("FilterCoscheduling", |_| Ok(Box::new(FilterCoscheduling)));
+
+        // Scorers
+        registry.register_scorer("ScoreIsNodeEmpty", |_| Ok(Box::new(ScoreIsNodeEmpty)));
+        registry.register_scorer("ScoreCountRunningPods", |_| Ok(Box::new(ScoreCountRunningPods)));
+        registry.register_scorer("ScoreConsolidation", |_| Ok(Box::new(ScoreConsolidation)));
+        registry.register_scorer("ScoreTetris", |_| Ok(Box::new(ScoreTetris)));
+        registry.register_scorer("ScoreTaintsTolerations", |_| Ok(Box::new(ScoreTaintsTolerations)));
+        registry.register_scorer("ScoreNodeAffinity", |_| Ok(Box::new(ScoreNodeAffinity)));
+        registry.register_scorer("ScorePodAffinity", |_| Ok(Box::new(ScorePodAffinity::default())));
+        registry.register_scorer("ScoreTopologySpread", |_| Ok(Box::new(ScoreTopologySpread::default())));
+        registry.register_scorer("ScoreLeastAllocated", |args| {
+            Ok(Box::new(ScoreLeastAllocated::new(
+                args.get("cpu_weight", 1)?,
+                args.get("memory_weight", 1)?,
+            )?))
+        });
+        registry.register_scorer("ScoreMostAllocated", |args| {
+            Ok(Box::new(ScoreMostAllocated::new(
+                args.get("cpu_weight", 1)?,
+                args.get("memory_weight", 1)?,
+            )?))
+        });
+        registry.register_scorer("ScoreRequestedToCapacityRatio", |args| {
+            Ok(Box::new(ScoreRequestedToCapacityRatio::new(
+                args.get(
+                    "shape",
+                    vec![(0, 0), (100, ScoreRequestedToCapacityRatio::MAX_SHAPE_SCORE)],
+                )?,
+                args.get("cpu_weight", 1)?,
+                args.get("memory_weight", 1)?,
+            )?))
+        });
+        registry.register_scorer("ScoreBalancedAllocation", |args| {
+            Ok(Box::new(ScoreBalancedAllocation::new(
+                args.get("cpu_weight", 1)?,
+                args.get("memory_weight", 1)?,
+            )?))
+        });
+
+        // Score normalizers
+        registry.register_score_normalizer("ScoreNormalizeSkip", |_| Ok(Box::new(ScoreNormalizeSkip)));
+        registry.register_score_normalizer("ScoreNormalizeNeg", |_| Ok(Box::new(ScoreNormalizeNeg)));
+        registry.register_score_normalizer("ScoreNormalizeLinear", |_| Ok(Box::new(ScoreNormalizeLinear)));
+        registry.register_score_normalizer("ScoreNormalizeLinearReversed", |_| {
+            Ok(Box::new(ScoreNormalizeLinearReversed))
+        });
+        registry.register_score_normalizer("ScoreNormalizeMinMax", |_| Ok(Box::new(ScoreNormalizeMinMax)));
+
+        // Permits
+        registry.register_permit("PermitCoscheduling", |args| {
+            Ok(Box::new(PermitCoscheduling::new(args.get("timeout", 60.0)?)?))
+        });
+
+        return registry;
+    }
+
+    ////////////////// Registration //////////////////
+
+    pub fn register_active_queue(&mut self, name: &str, constructor: ActiveQConstructor) {
+        self.active_queues.insert(name.to_string(), constructor);
+    }
+
+    pub fn register_backoff_queue(&mut self, name: &str, constructor: BackOffQConstructor) {
+        self.backoff_queues.insert(name.to_string(), constructor);
+    }
+
+    pub fn register_filter(&mut self, name: &str, constructor: FilterPluginConstructor) {
+        self.filters.insert(name.to_string(), constructor);
+    }
+
+    pub fn register_scorer(&mut self, name: &str, constructor: ScorePluginConstructor) {
+        self.scorers.insert(name.to_string(), constructor);
+    }
+
+    pub fn register_score_normalizer(&mut self, name: &str, constructor: ScoreNormalizePluginConstructor) {
+        self.score_normalizers.insert(name.to_string(), constructor);
+    }
+
+    pub fn register_reserve(&mut self, name: &str, constructor: ReservePluginConstructor) {
+        self.reserves.insert(name.to_string(), constructor);
+    }
+
+    pub fn register_permit(&mut self, name: &str, constructor: PermitPluginConstructor) {
+        self.permits.insert(name.to_string(), constructor);
+    }
+
+    pub fn register_pre_bind(&mut self, name: &str, constructor: PreBindPluginConstructor) {
+        self.pre_binds.insert(name.to_string(), constructor);
+    }
+
+    ////////////////// Construction //////////////////
+
+    pub fn make_active_queue(&self, name: &str, args: &PluginArgs) -> Result<Box<dyn IActiveQ + Send>, KuberSimError> {
+        let constructor = self.active_queues.get(name).ok_or_else(|| {
+            KuberSimError::invalid("PluginSpec.name", format!("Active queue {} is not registered", name))
+        })?;
+        return constructor(args);
+    }
+
+    pub fn make_backoff_queue(
+        &self,
+        name: &str,
+        args: &PluginArgs,
+    ) -> Result<Box<dyn IBackOffQ + Send>, KuberSimError> {
+        let constructor = self.backoff_queues.get(name).ok_or_else(|| {
+            KuberSimError::invalid("PluginSpec.name", format!("Backoff queue {} is not registered", name))
+        })?;
+        return constructor(args);
+    }
+
+    pub fn make_filter(&self, name: &str, args: &PluginArgs) -> Result<Box<dyn IFilterPlugin + Send>, KuberSimError> {
+        let constructor = self.filters.get(name).ok_or_else(|| {
+            KuberSimError::invalid("PluginSpec.name", format!("Filter plugin {} is not registered", name))
+        })?;
+        return constructor(args);
+    }
+
+    pub fn make_scorer(&self, name: &str, args: &PluginArgs) -> Result<Box<dyn IScorePlugin + Send>, KuberSimError> {
+        let constructor = self.scorers.get(name).ok_or_else(|| {
+            KuberSimError::invalid("PluginSpec.name", format!("Score plugin {} is not registered", name))
+        })?;
+        return constructor(args);
+    }
+
+    pub fn make_score_normalizer(
+        &self,
+        name: &str,
+        args: &PluginArgs,
+    ) -> Result<Box<dyn IScoreNormalizePlugin + Send>, KuberSimError> {
+        let constructor = self.score_normalizers.get(name).ok_or_else(|| {
+            KuberSimError::invalid(
+                "PluginSpec.name",
+                format!("Score normalize plugin {} is not registered", name),
+            )
+        })?;
+        return constructor(args);
+    }
+
+    pub fn make_reserve(&self, name: &str, args: &PluginArgs) -> Result<Box<dyn IReservePlugin + Send>, KuberSimError> {
+        let constructor = self.reserves.get(name).ok_or_else(|| {
+            KuberSimError::invalid("PluginSpec.name", format!("Reserve plugin {} is not registered", name))
+        })?;
+        return constructor(args);
+    }
+
+    pub fn make_permit(&self, name: &str, args: &PluginArgs) -> Result<Box<dyn IPermitPlugin + Send>, KuberSimError> {
+        let constructor = self.permits.get(name).ok_or_else(|| {
+            KuberSimError::invalid("PluginSpec.name", format!("Permit plugin {} is not registered", name))
+        })?;
+        return constructor(args);
+    }
+
+    pub fn make_pre_bind(
+        &self,
+        name: &str,
+        args: &PluginArgs,
+    ) -> Result<Box<dyn IPreBindPlugin + Send>, KuberSimError> {
+        let constructor = self.pre_binds.get(name).ok_or_else(|| {
+            KuberSimError::invalid("PluginSpec.name", format!("Pre bind plugin {} is not registered", name))
+        })?;
+        return constructor(args);
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_registry_names() {
+        let registry = PluginRegistry::default();
+        let args = PluginArgs::default();
+        for (name, constructor) in registry.filters.iter() {
+            assert_eq!(&constructor(&args).unwrap().name(), name);
+        }
+        for (name, constructor) in registry.scorers.iter() {
+            assert_eq!(&constructor(&args).unwrap().name(), name);
+        }
+        for (name, constructor) in registry.score_normalizers.iter() {
+            assert_eq!(&constructor(&args).unwrap().name(), name);
+        }
+        for (name, constructor) in registry.permits.iter() {
+            assert_eq!(&constructor(&args).unwrap().name(), name);
+        }
+    }
+}
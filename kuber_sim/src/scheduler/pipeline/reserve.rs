@@ -0,0 +1,31 @@
+use crate::objects::node::Node;
+use crate::objects::pod::Pod;
+use std::collections::HashMap;
+
+/// Called after pod is assumed on the chosen node, so running_pods already contains it.
+pub trait IReservePlugin {
+    fn name(&self) -> String;
+
+    /// Returns false to cancel the placement. Then unreserve is called for all reserve plugins.
+    fn reserve(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        pending_pods: &HashMap<u64, Pod>,
+        nodes: &HashMap<u64, Node>,
+        pod: &Pod,
+        node: &Node,
+    ) -> bool;
+
+    /// Called in reverse order if reserve, permit or pre_bind of the pod fails, or if waiting pod is removed.
+    /// Must be idempotent, because it is called even for the plugin whose reserve failed.
+    fn unreserve(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        pending_pods: &HashMap<u64, Pod>,
+        nodes: &HashMap<u64, Node>,
+        pod: &Pod,
+        node: &Node,
+    );
+
+    fn clone(&self) -> Box<dyn IReservePlugin + Send>;
+}
@@ -1,13 +1,29 @@
+use crate::error::KuberSimError;
 use crate::objects::node::Node;
 use crate::objects::pod::Pod;
+use crate::scheduler::features::pod_affinity::{self, TopologyCounts};
 use crate::scheduler::features::taints_tolerations::TaintTolerationEffect;
+use crate::scheduler::features::topology_spread::{TopologySpreadCounts, WhenUnsatisfiable};
+use std::cell::RefCell;
 use std::collections::HashMap;
 
 pub type ScorePluginF = fn(&HashMap<u64, Pod>, &HashMap<u64, Pod>, &HashMap<u64, Node>, &Pod, &Node) -> i64;
 
+/// Maximal score of a node. Analog of MaxNodeScore of kube-scheduler.
+pub const MAX_NODE_SCORE: i64 = 100;
+
 pub trait IScorePlugin {
     fn name(&self) -> String;
 
+    /// Called once per scheduling attempt before score is called for each feasible node.
+    /// Plugins may precompute here the state shared across nodes.
+    fn pre_score(&self, _: &HashMap<u64, Pod>, _: &HashMap<u64, Pod>, _: &HashMap<u64, Node>, _: &Pod) {}
+
+    /// Is score already in range [0, MAX_NODE_SCORE], so it needs no normalization.
+    fn is_normalized(&self) -> bool {
+        return false;
+    }
+
     fn score(
         &self,
         running_pods: &HashMap<u64, Pod>,
@@ -58,6 +74,34 @@ impl IScorePlugin for ScoreCountRunningPods {
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
 
+/// Prefers nodes which already run pods, so pods are consolidated and empty nodes stay empty
+/// (and may be removed by CA). Power models of nodes are not taken into account: energy is saved
+/// because nodes removed by CA do not consume idle power.
+pub struct ScoreConsolidation;
+
+impl IScorePlugin for ScoreConsolidation {
+    fn name(&self) -> String {
+        return "ScoreConsolidation".to_string();
+    }
+
+    fn is_normalized(&self) -> bool {
+        return true;
+    }
+
+    fn score(&self, _: &HashMap<u64, Pod>, _: &HashMap<u64, Pod>, _: &HashMap<u64, Node>, _: &Pod, node: &Node) -> i64 {
+        if node.status.pods.is_empty() {
+            return 0;
+        }
+        return MAX_NODE_SCORE;
+    }
+
+    fn clone(&self) -> Box<dyn IScorePlugin + Send> {
+        return Box::new(ScoreConsolidation);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
 pub struct ScoreTetris;
 
 impl IScorePlugin for ScoreTetris {
@@ -187,12 +231,448 @@ impl IScorePlugin for ScoreNodeAffinity {
     }
 }
 
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Default)]
+pub struct ScorePodAffinity {
+    /// Weighted sums of pod's and running pods' preferred terms per domain computed in pre_score.
+    counts: RefCell<TopologyCounts>,
+}
+
+impl IScorePlugin for ScorePodAffinity {
+    fn name(&self) -> String {
+        return "ScorePodAffinity".to_string();
+    }
+
+    fn pre_score(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        nodes: &HashMap<u64, Node>,
+        pod: &Pod,
+    ) {
+        *self.counts.borrow_mut() = pod_affinity::count_preferred_domains(running_pods, nodes, pod);
+    }
+
+    fn score(&self, _: &HashMap<u64, Pod>, _: &HashMap<u64, Pod>, _: &HashMap<u64, Node>, _: &Pod, node: &Node) -> i64 {
+        return pod_affinity::sum_in_domains(&self.counts.borrow(), node);
+    }
+
+    fn clone(&self) -> Box<dyn IScorePlugin + Send> {
+        return Box::new(ScorePodAffinity::default());
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Default)]
+pub struct ScoreTopologySpread {
+    /// Domain counts of pod's ScheduleAnyway constraints computed in pre_score.
+    counts: RefCell<Vec<TopologySpreadCounts>>,
+}
+
+impl IScorePlugin for ScoreTopologySpread {
+    fn name(&self) -> String {
+        return "ScoreTopologySpread".to_string();
+    }
+
+    fn pre_score(
+        &self,
+        running_pods: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        nodes: &HashMap<u64, Node>,
+        pod: &Pod,
+    ) {
+        let mut counts = self.counts.borrow_mut();
+        counts.clear();
+        for constraint in pod.spec.topology_spread_constraints.iter() {
+            if constraint.when_unsatisfiable == WhenUnsatisfiable::ScheduleAnyway {
+                counts.push(constraint.count_domains(running_pods, nodes, pod));
+            }
+        }
+    }
+
+    fn score(
+        &self,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        pod: &Pod,
+        node: &Node,
+    ) -> i64 {
+        let counts = self.counts.borrow();
+        let constraints = pod
+            .spec
+            .topology_spread_constraints
+            .iter()
+            .filter(|constraint| constraint.when_unsatisfiable == WhenUnsatisfiable::ScheduleAnyway);
+
+        // The fewer matching pods in node's domains, the better. Nodes without domain are the worst.
+        let mut match_sum = 0;
+        for (constraint, constraint_counts) in constraints.zip(counts.iter()) {
+            match node.metadata.labels.get(&constraint.topology_key) {
+                Some(domain) => match_sum += constraint_counts.domains.get(domain).copied().unwrap_or(0),
+                None => match_sum += constraint_counts.max_count + 1,
+            }
+        }
+        return -match_sum;
+    }
+
+    fn clone(&self) -> Box<dyn IScorePlugin + Send> {
+        return Box::new(ScoreTopologySpread::default());
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Returns [(requested, capacity)] for cpu and memory of node as if pod was placed to it.
+fn requested_with_pod(pod: &Pod, node: &Node) -> [(i64, i64); 2] {
+    let requested_cpu = node.spec.installed_cpu - node.spec.available_cpu + pod.spec.request_cpu;
+    let requested_memory = node.spec.installed_memory - node.spec.available_memory + pod.spec.request_memory;
+    return [
+        (requested_cpu, node.spec.installed_cpu),
+        (requested_memory, node.spec.installed_memory),
+    ];
+}
+
+/// Weighted mean of resource_score(requested, capacity) over cpu and memory.
+fn weighted_resource_score<F>(pod: &Pod, node: &Node, weights: [i64; 2], resource_score: F) -> i64
+where
+    F: Fn(i64, i64) -> i64,
+{
+    let (mut score_sum, mut weight_sum) = (0, 0);
+    for (&(requested, capacity), &weight) in requested_with_pod(pod, node).iter().zip(weights.iter()) {
+        if capacity == 0 || weight == 0 {
+            continue;
+        }
+        score_sum += resource_score(requested, capacity) * weight;
+        weight_sum += weight;
+    }
+
+    if weight_sum == 0 {
+        return 0;
+    }
+    return score_sum / weight_sum;
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Analog of NodeResourcesFit LeastAllocated strategy. Favors nodes with fewer requested resources.
+/// Score is in range [0, MAX_NODE_SCORE].
+pub struct ScoreLeastAllocated {
+    cpu_weight: i64,
+    memory_weight: i64,
+}
+
+impl ScoreLeastAllocated {
+    pub fn new(cpu_weight: i64, memory_weight: i64) -> Result<Self, KuberSimError> {
+        sim_ensure!(cpu_weight >= 0, "ScoreLeastAllocated.cpu_weight", "must be >= 0");
+        sim_ensure!(memory_weight >= 0, "ScoreLeastAllocated.memory_weight", "must be >= 0");
+        return Ok(Self {
+            cpu_weight,
+            memory_weight,
+        });
+    }
+
+    pub fn default() -> Self {
+        Self {
+            cpu_weight: 1,
+            memory_weight: 1,
+        }
+    }
+}
+
+impl IScorePlugin for ScoreLeastAllocated {
+    fn name(&self) -> String {
+        return "ScoreLeastAllocated".to_string();
+    }
+
+    fn is_normalized(&self) -> bool {
+        return true;
+    }
+
+    fn score(
+        &self,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        pod: &Pod,
+        node: &Node,
+    ) -> i64 {
+        let weights = [self.cpu_weight, self.memory_weight];
+        return weighted_resource_score(pod, node, weights, |requested, capacity| {
+            if requested > capacity {
+                return 0;
+            }
+            return (capacity - requested) * MAX_NODE_SCORE / capacity;
+        });
+    }
+
+    fn clone(&self) -> Box<dyn IScorePlugin + Send> {
+        return Box::new(ScoreLeastAllocated {
+            cpu_weight: self.cpu_weight,
+            memory_weight: self.memory_weight,
+        });
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Analog of NodeResourcesFit MostAllocated strategy. Favors nodes with more requested resources.
+/// Score is in range [0, MAX_NODE_SCORE].
+pub struct ScoreMostAllocated {
+    cpu_weight: i64,
+    memory_weight: i64,
+}
+
+impl ScoreMostAllocated {
+    pub fn new(cpu_weight: i64, memory_weight: i64) -> Result<Self, KuberSimError> {
+        sim_ensure!(cpu_weight >= 0, "ScoreMostAllocated.cpu_weight", "must be >= 0");
+        sim_ensure!(memory_weight >= 0, "ScoreMostAllocated.memory_weight", "must be >= 0");
+        return Ok(Self {
+            cpu_weight,
+            memory_weight,
+        });
+    }
+
+    pub fn default() -> Self {
+        Self {
+            cpu_weight: 1,
+            memory_weight: 1,
+        }
+    }
+}
+
+impl IScorePlugin for ScoreMostAllocated {
+    fn name(&self) -> String {
+        return "ScoreMostAllocated".to_string();
+    }
+
+    fn is_normalized(&self) -> bool {
+        return true;
+    }
+
+    fn score(
+        &self,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        pod: &Pod,
+        node: &Node,
+    ) -> i64 {
+        let weights = [self.cpu_weight, self.memory_weight];
+        return weighted_resource_score(pod, node, weights, |requested, capacity| {
+            return requested.min(capacity) * MAX_NODE_SCORE / capacity;
+        });
+    }
+
+    fn clone(&self) -> Box<dyn IScorePlugin + Send> {
+        return Box::new(ScoreMostAllocated {
+            cpu_weight: self.cpu_weight,
+            memory_weight: self.memory_weight,
+        });
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Analog of NodeResourcesFit RequestedToCapacityRatio strategy.
+/// Score of each resource is a broken linear function (shape) of its utilization.
+/// Score is in range [0, MAX_NODE_SCORE].
+pub struct ScoreRequestedToCapacityRatio {
+    /// Vec<(utilization, score)> with strictly increasing utilization in [0, 100] and score in [0, 10].
+    shape: Vec<(i64, i64)>,
+    cpu_weight: i64,
+    memory_weight: i64,
+}
+
+impl ScoreRequestedToCapacityRatio {
+    /// Maximal score of a shape point. Analog of MaxCustomPriorityScore of kube-scheduler.
+    pub const MAX_SHAPE_SCORE: i64 = 10;
+
+    pub fn new(shape: Vec<(i64, i64)>, cpu_weight: i64, memory_weight: i64) -> Result<Self, KuberSimError> {
+        sim_ensure!(
+            cpu_weight >= 0,
+            "ScoreRequestedToCapacityRatio.cpu_weight",
+            "must be >= 0"
+        );
+        sim_ensure!(
+            memory_weight >= 0,
+            "ScoreRequestedToCapacityRatio.memory_weight",
+            "must be >= 0"
+        );
+        sim_ensure!(
+            !shape.is_empty(),
+            "ScoreRequestedToCapacityRatio.shape",
+            "must not be empty"
+        );
+        for (i, &(utilization, score)) in shape.iter().enumerate() {
+            sim_ensure!(
+                (0..=100).contains(&utilization),
+                format!("ScoreRequestedToCapacityRatio.shape[{}]", i),
+                "Utilization must be in [0, 100]"
+            );
+            sim_ensure!(
+                (0..=Self::MAX_SHAPE_SCORE).contains(&score),
+                format!("ScoreRequestedToCapacityRatio.shape[{}]", i),
+                format!("Score must be in [0, {}]", Self::MAX_SHAPE_SCORE)
+            );
+            sim_ensure!(
+                i == 0 || shape[i - 1].0 < utilization,
+                format!("ScoreRequestedToCapacityRatio.shape[{}]", i),
+                "Utilization must be greater than in previous point"
+            );
+        }
+
+        return Ok(Self {
+            shape,
+            cpu_weight,
+            memory_weight,
+        });
+    }
+
+    /// Bin packing shape: the more utilized node, the better.
+    pub fn default() -> Self {
+        Self {
+            shape: vec![(0, 0), (100, Self::MAX_SHAPE_SCORE)],
+            cpu_weight: 1,
+            memory_weight: 1,
+        }
+    }
+
+    /// Broken linear function of utilization defined by shape points.
+    fn shape_score(&self, utilization: i64) -> i64 {
+        for (i, &(point_utilization, point_score)) in self.shape.iter().enumerate() {
+            if utilization <= point_utilization {
+                if i == 0 {
+                    return point_score;
+                }
+                let (prev_utilization, prev_score) = self.shape[i - 1];
+                return prev_score
+                    + (point_score - prev_score) * (utilization - prev_utilization)
+                        / (point_utilization - prev_utilization);
+            }
+        }
+        return self.shape.last().unwrap().1;
+    }
+}
+
+impl IScorePlugin for ScoreRequestedToCapacityRatio {
+    fn name(&self) -> String {
+        return "ScoreRequestedToCapacityRatio".to_string();
+    }
+
+    fn is_normalized(&self) -> bool {
+        return true;
+    }
+
+    fn score(
+        &self,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        pod: &Pod,
+        node: &Node,
+    ) -> i64 {
+        let weights = [self.cpu_weight, self.memory_weight];
+        return weighted_resource_score(pod, node, weights, |requested, capacity| {
+            let utilization = requested.min(capacity) * 100 / capacity;
+            return self.shape_score(utilization) * MAX_NODE_SCORE / Self::MAX_SHAPE_SCORE;
+        });
+    }
+
+    fn clone(&self) -> Box<dyn IScorePlugin + Send> {
+        return Box::new(ScoreRequestedToCapacityRatio {
+            shape: self.shape.clone(),
+            cpu_weight: self.cpu_weight,
+            memory_weight: self.memory_weight,
+        });
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Analog of NodeResourcesBalancedAllocation. Favors nodes with balanced resource usage fractions.
+/// Score is (1 - weighted standard deviation of fractions) * MAX_NODE_SCORE.
+pub struct ScoreBalancedAllocation {
+    cpu_weight: i64,
+    memory_weight: i64,
+}
+
+impl ScoreBalancedAllocation {
+    pub fn new(cpu_weight: i64, memory_weight: i64) -> Result<Self, KuberSimError> {
+        sim_ensure!(cpu_weight >= 0, "ScoreBalancedAllocation.cpu_weight", "must be >= 0");
+        sim_ensure!(
+            memory_weight >= 0,
+            "ScoreBalancedAllocation.memory_weight",
+            "must be >= 0"
+        );
+        return Ok(Self {
+            cpu_weight,
+            memory_weight,
+        });
+    }
+
+    pub fn default() -> Self {
+        Self {
+            cpu_weight: 1,
+            memory_weight: 1,
+        }
+    }
+}
+
+impl IScorePlugin for ScoreBalancedAllocation {
+    fn name(&self) -> String {
+        return "ScoreBalancedAllocation".to_string();
+    }
+
+    fn is_normalized(&self) -> bool {
+        return true;
+    }
+
+    fn score(
+        &self,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        pod: &Pod,
+        node: &Node,
+    ) -> i64 {
+        let weights = [self.cpu_weight, self.memory_weight];
+
+        let (mut fractions, mut weight_sum) = (Vec::with_capacity(2), 0.0);
+        for (&(requested, capacity), &weight) in requested_with_pod(pod, node).iter().zip(weights.iter()) {
+            if capacity == 0 || weight == 0 {
+                continue;
+            }
+            fractions.push(((requested as f64 / capacity as f64).min(1.0), weight as f64));
+            weight_sum += weight as f64;
+        }
+
+        if fractions.is_empty() {
+            return 0;
+        }
+
+        let mean = fractions.iter().map(|&(f, w)| f * w).sum::<f64>() / weight_sum;
+        let variance = fractions.iter().map(|&(f, w)| (f - mean).powi(2) * w).sum::<f64>() / weight_sum;
+        return ((1.0 - variance.sqrt()) * MAX_NODE_SCORE as f64) as i64;
+    }
+
+    fn clone(&self) -> Box<dyn IScorePlugin + Send> {
+        return Box::new(ScoreBalancedAllocation {
+            cpu_weight: self.cpu_weight,
+            memory_weight: self.memory_weight,
+        });
+    }
+}
+
 ///////////////////////////////////////////// Test /////////////////////////////////////////////////
 
 #[rustfmt::skip]
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::simulation::uid_allocator::UidAllocator;
 
     #[test]
     fn test_score_tetris() {
@@ -203,7 +683,7 @@ mod tests {
 
         // Create Node
         let mut n1 = Node::default(); n1.spec.installed_cpu = 50; n1.spec.installed_memory = 100;
-        n1.prepare(1);
+        n1.prepare(1, &mut UidAllocator::new());
 
         // Create Pod
         let mut p1 = Pod::default(); p1.spec.request_cpu = 10; p1.spec.request_memory = 10;
@@ -220,4 +700,46 @@ mod tests {
 
         assert_eq!(s3, s1.max(s2).max(s3));
     }
+
+    #[test]
+    fn test_score_resources_fit() {
+        let (r, p): (HashMap<u64, Pod>, HashMap<u64, Pod>) = (HashMap::new(), HashMap::new());
+        let n: HashMap<u64, Node> = HashMap::new();
+
+        // Create Node with 40% of cpu and 20% of memory requested
+        let mut n1 = Node::default(); n1.spec.installed_cpu = 100; n1.spec.installed_memory = 200;
+        n1.prepare(1, &mut UidAllocator::new());
+        n1.consume(30, 20);
+
+        // Create Pod
+        let mut p1 = Pod::default(); p1.spec.request_cpu = 10; p1.spec.request_memory = 20;
+
+        assert_eq!(ScoreLeastAllocated::new(1, 1).unwrap().score(&r, &p, &n, &p1, &n1), 70);
+        assert_eq!(ScoreLeastAllocated::new(1, 0).unwrap().score(&r, &p, &n, &p1, &n1), 60);
+        assert_eq!(ScoreLeastAllocated::new(0, 0).unwrap().score(&r, &p, &n, &p1, &n1), 0);
+
+        assert_eq!(ScoreMostAllocated::new(1, 1).unwrap().score(&r, &p, &n, &p1, &n1), 30);
+        assert_eq!(ScoreMostAllocated::new(3, 1).unwrap().score(&r, &p, &n, &p1, &n1), 35);
+
+        let bin_packing = ScoreRequestedToCapacityRatio::default();
+        assert_eq!(bin_packing.score(&r, &p, &n, &p1, &n1), 30);
+        let spreading = ScoreRequestedToCapacityRatio::new(vec![(0, 10), (50, 10), (100, 0)], 1, 1).unwrap();
+        assert_eq!(spreading.score(&r, &p, &n, &p1, &n1), 100);
+        let spreading = ScoreRequestedToCapacityRatio::new(vec![(0, 10), (100, 0)], 0, 1).unwrap();
+        assert_eq!(spreading.score(&r, &p, &n, &p1, &n1), 80);
+
+        assert_eq!(ScoreBalancedAllocation::new(1, 1).unwrap().score(&r, &p, &n, &p1, &n1), 90);
+        assert_eq!(ScoreBalancedAllocation::new(1, 0).unwrap().score(&r, &p, &n, &p1, &n1), 100);
+
+        // Requests over capacity are capped
+        let mut p2 = Pod::default(); p2.spec.request_cpu = 100; p2.spec.request_memory = 20;
+        assert_eq!(ScoreLeastAllocated::new(1, 0).unwrap().score(&r, &p, &n, &p2, &n1), 0);
+        assert_eq!(ScoreMostAllocated::new(1, 0).unwrap().score(&r, &p, &n, &p2, &n1), 100);
+
+        // Invalid parameters
+        assert_eq!(ScoreLeastAllocated::new(-1, 1).err().unwrap().field, Some("ScoreLeastAllocated.cpu_weight".to_string()));
+        assert!(ScoreRequestedToCapacityRatio::new(vec![], 1, 1).is_err());
+        assert!(ScoreRequestedToCapacityRatio::new(vec![(0, 10), (120, 0)], 1, 1).is_err());
+        assert!(ScoreRequestedToCapacityRatio::new(vec![(50, 10), (50, 0)], 1, 1).is_err());
+    }
 }
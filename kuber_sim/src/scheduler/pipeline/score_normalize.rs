@@ -1,5 +1,6 @@
 use crate::objects::node::Node;
 use crate::objects::pod::Pod;
+use crate::scheduler::pipeline::score::MAX_NODE_SCORE;
 use std::collections::HashMap;
 
 pub type ScoreNormalizePluginF =
@@ -8,6 +9,11 @@ pub type ScoreNormalizePluginF =
 pub trait IScoreNormalizePlugin {
     fn name(&self) -> String;
 
+    /// Are normalized scores always in range [0, MAX_NODE_SCORE], whatever the plugin scores are.
+    fn maps_to_node_score_range(&self) -> bool {
+        return false;
+    }
+
     fn normalize(
         &self,
         running_pods: &HashMap<u64, Pod>,
@@ -75,3 +81,181 @@ impl IScoreNormalizePlugin for ScoreNormalizeNeg {
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Analog of DefaultNormalizeScore of kube-scheduler with reverse = false.
+/// Linearly maps scores [0, max_score] to [0, MAX_NODE_SCORE]. Negative scores are mapped to 0.
+pub struct ScoreNormalizeLinear;
+
+impl IScoreNormalizePlugin for ScoreNormalizeLinear {
+    fn name(&self) -> String {
+        return "ScoreNormalizeLinear".to_string();
+    }
+
+    fn maps_to_node_score_range(&self) -> bool {
+        return true;
+    }
+
+    fn normalize(
+        &self,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        _: &Pod,
+        score_nodes: &Vec<Node>,
+        scores: &mut Vec<i64>,
+    ) {
+        // Row of score matrix is longer than the list of scored nodes, the rest is left from other pods
+        let scores = &mut scores[..score_nodes.len()];
+        let max_score = scores.iter().copied().max().unwrap_or(0);
+        for score in scores.iter_mut() {
+            *score = if max_score > 0 {
+                (*score).max(0) * MAX_NODE_SCORE / max_score
+            } else {
+                0
+            };
+        }
+    }
+
+    fn clone(&self) -> Box<dyn IScoreNormalizePlugin + Send> {
+        return Box::new(ScoreNormalizeLinear);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Analog of DefaultNormalizeScore of kube-scheduler with reverse = true.
+/// Linearly maps scores [0, max_score] to [MAX_NODE_SCORE, 0]. Negative scores are mapped to MAX_NODE_SCORE.
+pub struct ScoreNormalizeLinearReversed;
+
+impl IScoreNormalizePlugin for ScoreNormalizeLinearReversed {
+    fn name(&self) -> String {
+        return "ScoreNormalizeLinearReversed".to_string();
+    }
+
+    fn maps_to_node_score_range(&self) -> bool {
+        return true;
+    }
+
+    fn normalize(
+        &self,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        _: &Pod,
+        score_nodes: &Vec<Node>,
+        scores: &mut Vec<i64>,
+    ) {
+        // Row of score matrix is longer than the list of scored nodes, the rest is left from other pods
+        let scores = &mut scores[..score_nodes.len()];
+        let max_score = scores.iter().copied().max().unwrap_or(0);
+        for score in scores.iter_mut() {
+            *score = if max_score > 0 {
+                MAX_NODE_SCORE - (*score).max(0) * MAX_NODE_SCORE / max_score
+            } else {
+                MAX_NODE_SCORE
+            };
+        }
+    }
+
+    fn clone(&self) -> Box<dyn IScoreNormalizePlugin + Send> {
+        return Box::new(ScoreNormalizeLinearReversed);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Linearly maps scores [min_score, max_score] to [0, MAX_NODE_SCORE].
+/// Works with negative scores. If all scores are equal, they are mapped to MAX_NODE_SCORE.
+pub struct ScoreNormalizeMinMax;
+
+impl IScoreNormalizePlugin for ScoreNormalizeMinMax {
+    fn name(&self) -> String {
+        return "ScoreNormalizeMinMax".to_string();
+    }
+
+    fn maps_to_node_score_range(&self) -> bool {
+        return true;
+    }
+
+    fn normalize(
+        &self,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Pod>,
+        _: &HashMap<u64, Node>,
+        _: &Pod,
+        score_nodes: &Vec<Node>,
+        scores: &mut Vec<i64>,
+    ) {
+        // Row of score matrix is longer than the list of scored nodes, the rest is left from other pods
+        let scores = &mut scores[..score_nodes.len()];
+        let min_score = scores.iter().copied().min().unwrap_or(0);
+        let max_score = scores.iter().copied().max().unwrap_or(0);
+        for score in scores.iter_mut() {
+            *score = if max_score > min_score {
+                (*score - min_score) * MAX_NODE_SCORE / (max_score - min_score)
+            } else {
+                MAX_NODE_SCORE
+            };
+        }
+    }
+
+    fn clone(&self) -> Box<dyn IScoreNormalizePlugin + Send> {
+        return Box::new(ScoreNormalizeMinMax);
+    }
+}
+
+///////////////////////////////////////////// Test /////////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(normalizer: &dyn IScoreNormalizePlugin, scores: &[i64]) -> Vec<i64> {
+        let (r, p): (HashMap<u64, Pod>, HashMap<u64, Pod>) = (HashMap::new(), HashMap::new());
+        let n: HashMap<u64, Node> = HashMap::new();
+
+        let mut scores = scores.to_vec();
+        normalizer.normalize(&r, &p, &n, &Pod::default(), &vec![Node::default(); scores.len()], &mut scores);
+        return scores;
+    }
+
+    #[test]
+    fn test_score_normalize() {
+        assert_eq!(normalize(&ScoreNormalizeLinear, &[0, 5, 20, -3]), vec![0, 25, 100, 0]);
+        assert_eq!(normalize(&ScoreNormalizeLinear, &[0, 0]), vec![0, 0]);
+        assert_eq!(normalize(&ScoreNormalizeLinear, &[15708, 7854]), vec![100, 50]);
+
+        assert_eq!(normalize(&ScoreNormalizeLinearReversed, &[0, 5, 20, -3]), vec![100, 75, 0, 100]);
+        assert_eq!(normalize(&ScoreNormalizeLinearReversed, &[0, 0]), vec![100, 100]);
+
+        assert_eq!(normalize(&ScoreNormalizeMinMax, &[-1, 0, 1]), vec![0, 50, 100]);
+        assert_eq!(normalize(&ScoreNormalizeMinMax, &[-7, -2, -3]), vec![0, 100, 80]);
+        assert_eq!(normalize(&ScoreNormalizeMinMax, &[4, 4]), vec![100, 100]);
+        assert_eq!(normalize(&ScoreNormalizeMinMax, &[]), Vec::<i64>::new());
+
+        assert!(ScoreNormalizeLinear.maps_to_node_score_range());
+        assert!(ScoreNormalizeMinMax.maps_to_node_score_range());
+        assert!(!ScoreNormalizeSkip.maps_to_node_score_range());
+        assert!(!ScoreNormalizeNeg.maps_to_node_score_range());
+    }
+
+    #[test]
+    fn test_score_normalize_scored_nodes_only() {
+        let (r, p): (HashMap<u64, Pod>, HashMap<u64, Pod>) = (HashMap::new(), HashMap::new());
+        let n: HashMap<u64, Node> = HashMap::new();
+        let score_nodes = vec![Node::default(); 2];
+
+        // Tail of the row is left from other pods and does not affect scores of scored nodes
+        let normalizers: Vec<(Box<dyn IScoreNormalizePlugin>, Vec<i64>)> = vec![
+            (Box::new(ScoreNormalizeLinear), vec![50, 100]),
+            (Box::new(ScoreNormalizeLinearReversed), vec![50, 0]),
+            (Box::new(ScoreNormalizeMinMax), vec![0, 100]),
+        ];
+        for (normalizer, expected) in normalizers {
+            let mut scores = vec![5, 10, 1000, -1000];
+            normalizer.normalize(&r, &p, &n, &Pod::default(), &score_nodes, &mut scores);
+            assert_eq!(scores[..2].to_vec(), expected);
+        }
+    }
+}
@@ -46,6 +46,7 @@ impl Eq for ActiveQCmpUid {}
 
 //////////////////////////////////////// Cmp Priority //////////////////////////////////////////////
 
+/// Orders pods by priority, which is resolved from PriorityClass before simulation starts.
 #[derive(Debug, Clone)]
 pub struct ActiveQCmpPriority(pub Pod);
 
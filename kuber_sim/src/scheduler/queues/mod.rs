@@ -1,3 +1,4 @@
 pub mod active_queue;
 pub mod active_queue_cmp;
 pub mod backoff_queue;
+pub mod queueing_hints;
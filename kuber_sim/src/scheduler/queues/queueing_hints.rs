@@ -0,0 +1,87 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+// https://kubernetes.io/docs/concepts/scheduling-eviction/scheduling-framework/#queueinghint
+/// Cluster changes which may make an unschedulable pod schedulable again.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ClusterEvent {
+    NodeAdded,
+    NodeLabelsChanged,
+    NodeTaintsChanged,
+    /// Pod left its node (finished, removed or rescheduled) and released node resources.
+    PodTerminated,
+}
+
+impl ClusterEvent {
+    pub fn all() -> Vec<ClusterEvent> {
+        return vec![
+            ClusterEvent::NodeAdded,
+            ClusterEvent::NodeLabelsChanged,
+            ClusterEvent::NodeTaintsChanged,
+            ClusterEvent::PodTerminated,
+        ];
+    }
+}
+
+/// Events registered by plugins which rejected pods waiting in UnschedulableQ.
+/// When any of them happens in cluster, pod is moved to ActiveQ without waiting for its backoff delay.
+#[derive(Debug, Default)]
+pub struct QueueingHints {
+    hints: BTreeMap<u64, BTreeSet<ClusterEvent>>, // BTreeMap<pod_uid, events>
+}
+
+impl QueueingHints {
+    pub fn new() -> Self {
+        Self { hints: BTreeMap::new() }
+    }
+
+    pub fn insert(&mut self, pod_uid: u64, events: BTreeSet<ClusterEvent>) {
+        self.hints.insert(pod_uid, events);
+    }
+
+    pub fn remove(&mut self, pod_uid: u64) {
+        self.hints.remove(&pod_uid);
+    }
+
+    /// Removes and returns pods (sorted by uid) which may become schedulable after any of events.
+    pub fn take_matching(&mut self, events: &[ClusterEvent]) -> Vec<u64> {
+        let matching: Vec<u64> = self
+            .hints
+            .iter()
+            .filter(|(_, hints)| events.iter().any(|event| hints.contains(event)))
+            .map(|(&pod_uid, _)| pod_uid)
+            .collect();
+
+        for pod_uid in matching.iter() {
+            self.hints.remove(pod_uid);
+        }
+        return matching;
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queueing_hints() {
+        let mut hints = QueueingHints::new();
+        hints.insert(3, BTreeSet::from([ClusterEvent::NodeAdded, ClusterEvent::PodTerminated]));
+        hints.insert(1, BTreeSet::from([ClusterEvent::NodeAdded, ClusterEvent::NodeLabelsChanged]));
+        hints.insert(2, BTreeSet::from([ClusterEvent::NodeTaintsChanged]));
+        hints.insert(4, BTreeSet::new());
+
+        assert!(hints.take_matching(&[]).is_empty());
+        assert_eq!(hints.take_matching(&[ClusterEvent::PodTerminated]), vec![3]);
+        assert_eq!(hints.take_matching(&[ClusterEvent::NodeAdded, ClusterEvent::NodeTaintsChanged]), vec![1, 2]);
+
+        // Already moved pods are not matched again
+        assert!(hints.take_matching(&ClusterEvent::all()).is_empty());
+
+        hints.insert(5, BTreeSet::from([ClusterEvent::NodeAdded]));
+        hints.remove(5);
+        assert!(hints.take_matching(&ClusterEvent::all()).is_empty());
+    }
+}
@@ -1,40 +1,76 @@
+use crate::api_server::disruption_budgets::DisruptionBudgets;
 use crate::api_server::events::*;
 use crate::common_imports::*;
 use crate::dp_scheduler;
 use crate::objects::node::Node;
 use crate::objects::pod::{Pod, PodPhase};
-use crate::scheduler::node_index::NodeRTree;
+use crate::scheduler::cache::SchedulerCache;
+use crate::scheduler::features::preemption::{self, PreemptionCandidate};
 use crate::scheduler::pipeline::filter::IFilterPlugin;
+use crate::scheduler::pipeline::permit::{IPermitPlugin, PermitStatus};
+use crate::scheduler::pipeline::pre_bind::IPreBindPlugin;
+use crate::scheduler::pipeline::reserve::IReservePlugin;
 use crate::scheduler::pipeline::score::IScorePlugin;
 use crate::scheduler::pipeline::score_normalize::IScoreNormalizePlugin;
 use crate::scheduler::queues::active_queue::IActiveQ;
 use crate::scheduler::queues::backoff_queue::{BackOffQConstant, IBackOffQ};
+use crate::scheduler::queues::queueing_hints::{ClusterEvent, QueueingHints};
+use crate::simulation::decision_log::{DecisionStatus, NodeScores, SchedulingDecision};
 use crate::simulation::init_config::InitConfig;
 use crate::simulation::monitoring::Monitoring;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::rc::Rc;
 
+/// Name of the scheduler which places pods with empty PodSpec.scheduler_name.
+pub const DEFAULT_SCHEDULER_NAME: &str = "default-scheduler";
+
+/// Pod which passed Reserve and waits for Permit. Its resources stay consumed on node in cache.
+pub struct WaitingPod {
+    pub pod: Pod,
+    pub node: Node,
+    /// Time when pod started to wait.
+    pub since: f64,
+    /// Pod is rejected if it is not allowed before this time.
+    pub deadline: f64,
+}
+
 pub struct Scheduler {
+    /// Pods with the same PodSpec.scheduler_name are placed by this scheduler.
+    pub name: String,
+
     ctx: dsc::SimulationContext,
     init_config: Rc<RefCell<InitConfig>>,
     monitoring: Rc<RefCell<Monitoring>>,
+    disruption_budgets: Rc<RefCell<DisruptionBudgets>>,
 
     api_sim_id: dsc::Id,
 
     self_update_enabled: bool,
 
     // Cache
-    pub running_pods: HashMap<u64, Pod>, // HashMap<pod_uid, Pod>
-    pub pending_pods: HashMap<u64, Pod>, // HashMap<pod_uid, Pod>
-    pub nodes: HashMap<u64, Node>,       // HashMap<node_uid, Node>
-    pub node_rtree: NodeRTree,
+    pub pending_pods: HashMap<u64, Pod>,         // HashMap<pod_uid, Pod>
+    pub waiting_pods: BTreeMap<u64, WaitingPod>, // BTreeMap<pod_uid, WaitingPod>
+    pub cache: Rc<RefCell<SchedulerCache>>,
+    /// Index of pending events of this scheduler in SchedulerCache.cluster_events.
+    cluster_events_index: usize,
+    /// Filtering of sampled nodes starts from the node with this uid (or the next one), round-robin.
+    next_start_node_uid: u64,
 
     // Queues
     pub active_queue: Box<dyn IActiveQ + Send>,
     pub unschedulable_queue: BackOffQConstant,
+    pub queueing_hints: QueueingHints,
     pub backoff_queue: Box<dyn IBackOffQ + Send>,
     pub failed_attempts: HashMap<u64, u64>,
+    /// Time when pending pod entered ActiveQ the last time. Removed when pod is bound.
+    queued_since: HashMap<u64, f64>, // HashMap<pod_uid, time>
+
+    // Cost model
+    /// Simulated time spent by scheduler in the current cycle. Pods are bound when their attempt ends.
+    cycle_time_spent: f64,
+    /// Scheduler does not start new attempts before this time.
+    busy_until: f64,
 
     // Pipeline
     pub filters: Vec<Box<dyn IFilterPlugin + Send>>,
@@ -42,13 +78,19 @@ pub struct Scheduler {
     pub scorers: Vec<Box<dyn IScorePlugin + Send>>,
     pub score_normalizers: Vec<Box<dyn IScoreNormalizePlugin + Send>>,
     pub scorer_weights: Vec<i64>,
+    pub reserves: Vec<Box<dyn IReservePlugin + Send>>,
+    pub permits: Vec<Box<dyn IPermitPlugin + Send>>,
+    pub pre_binds: Vec<Box<dyn IPreBindPlugin + Send>>,
 }
 
 impl Scheduler {
     pub fn new(
+        name: String,
         ctx: dsc::SimulationContext,
         init_config: Rc<RefCell<InitConfig>>,
         monitoring: Rc<RefCell<Monitoring>>,
+        cache: Rc<RefCell<SchedulerCache>>,
+        disruption_budgets: Rc<RefCell<DisruptionBudgets>>,
         api_sim_id: dsc::Id,
 
         // Queues
@@ -61,27 +103,40 @@ impl Scheduler {
         scorers: Vec<Box<dyn IScorePlugin + Send>>,
         score_normalizers: Vec<Box<dyn IScoreNormalizePlugin + Send>>,
         scorer_weights: Vec<i64>,
+        reserves: Vec<Box<dyn IReservePlugin + Send>>,
+        permits: Vec<Box<dyn IPermitPlugin + Send>>,
+        pre_binds: Vec<Box<dyn IPreBindPlugin + Send>>,
     ) -> Scheduler {
+        let cluster_events_index = cache.borrow_mut().register_scheduler();
         Self {
+            name,
             ctx,
             init_config: init_config.clone(),
             api_sim_id,
             monitoring,
+            disruption_budgets,
             self_update_enabled: false,
 
             // Cache
-            running_pods: HashMap::new(),
             pending_pods: HashMap::new(),
-            nodes: HashMap::new(),
-            node_rtree: NodeRTree::new(),
+            waiting_pods: BTreeMap::new(),
+            cache,
+            cluster_events_index,
+            next_start_node_uid: 0,
 
             // Queues
             active_queue,
             unschedulable_queue: BackOffQConstant::new(
                 init_config.borrow().scheduler.unschedulable_queue_backoff_delay,
             ),
+            queueing_hints: QueueingHints::new(),
             backoff_queue,
             failed_attempts: HashMap::new(),
+            queued_since: HashMap::new(),
+
+            // Cost model
+            cycle_time_spent: 0.0,
+            busy_until: 0.0,
 
             // Pipeline
             filters,
@@ -89,6 +144,9 @@ impl Scheduler {
             scorers,
             score_normalizers,
             scorer_weights,
+            reserves,
+            permits,
+            pre_binds,
         }
     }
 
@@ -111,26 +169,46 @@ impl Scheduler {
     ////////////////// Main scheduling cycle //////////////////
 
     pub fn schedule(&mut self) {
+        // From unschedulableQ to activeQ on cluster events
+        self.process_cluster_events();
+
         // From unschedulableQ to activeQ
         while let Some(pod_uid) = self.unschedulable_queue.try_pop(self.ctx.time()) {
-            self.active_queue.push(self.pending_pods.get(&pod_uid).unwrap().clone());
+            self.queueing_hints.remove(pod_uid);
+            self.activate_pod(pod_uid);
         }
 
         // From backoffQ to activeQ
         while let Some(pod_uid) = self.backoff_queue.try_pop(self.ctx.time()) {
-            self.active_queue.push(self.pending_pods.get(&pod_uid).unwrap().clone());
+            self.activate_pod(pod_uid);
         }
 
+        // Cache is shared with other schedulers
+        let cache_ptr = self.cache.clone();
+
         let mut possible_nodes: Vec<Node> = Vec::new();
         let mut resulted_nodes: Vec<Node> = Vec::new();
         let mut is_schedulable: Vec<bool> = Vec::new();
-        let mut score_matrix: Vec<Vec<i64>> = vec![vec![0; self.nodes.len()]; self.scorers.len()];
+        let mut rejected_counts: Vec<u64> = Vec::new();
+        let top_k = self.monitoring.borrow().scheduler_decision_top_k();
+        let mut score_matrix: Vec<Vec<i64>> = vec![vec![0; cache_ptr.borrow().nodes.len()]; self.scorers.len()];
 
         let (mut scheduled_left, mut try_schedule_left): (u64, u64) = (
             self.init_config.borrow().scheduler.cycle_max_scheduled,
             self.init_config.borrow().scheduler.cycle_max_to_try,
         );
 
+        // Attempts are not started after the end of cycle, and not before the end of the previous one
+        let cost = self
+            .init_config
+            .borrow()
+            .scheduler
+            .cost_model
+            .clone()
+            .unwrap_or_default();
+        let cycle_end = self.ctx.time() + self.init_config.borrow().scheduler.self_update_period;
+        self.cycle_time_spent = (self.busy_until - self.ctx.time()).max(0.0);
+
         dp_scheduler!(
             "{:.3} scheduler cycle activeQ:{:?}",
             self.ctx.time(),
@@ -138,18 +216,46 @@ impl Scheduler {
         );
 
         // Main scheduling cycle
-        while let Some(mut pod) = self.active_queue.try_pop() {
-            if scheduled_left == 0 || try_schedule_left == 0 {
-                break;
-            }
+        while scheduled_left > 0 && try_schedule_left > 0 && self.now() < cycle_end {
+            let mut pod = match self.active_queue.try_pop() {
+                Some(pod) => pod,
+                None => break,
+            };
             try_schedule_left -= 1;
 
             let pod_uid = pod.metadata.uid;
+            self.monitoring
+                .borrow_mut()
+                .scheduler_on_pod_attempt(pod_uid, self.now());
             let cpu = pod.spec.request_cpu;
             let memory = pod.spec.request_memory;
+            let cache = cache_ptr.borrow();
+            let mut decision = self.new_decision(pod_uid);
+            decision.total_nodes = cache.nodes.len() as u64;
+            self.cycle_time_spent += cost.pod_base_time;
+
+            // PreFilter. Filter plugins prepare their state and may reject pod before any node is looked at
+            let pre_filter_rejection: Option<(String, BTreeSet<ClusterEvent>)> = self
+                .filters
+                .iter()
+                .find(|plugin| !plugin.pre_filter(&cache.running_pods, &self.pending_pods, &cache.nodes, &pod))
+                .map(|plugin| (plugin.name(), plugin.events_to_register().into_iter().collect()));
+            if let Some((plugin_name, events)) = pre_filter_rejection {
+                self.pending_pods
+                    .get_mut(&pod_uid)
+                    .unwrap()
+                    .status
+                    .cluster_resource_starvation = false;
+                decision.pre_filter_rejected_by = Some(plugin_name);
+                decision.reason = Some(decision.unschedulable_reason());
+                self.log_decision(&decision);
+                self.push_unschedulable_pod(pod_uid, events);
+                continue;
+            }
 
             // Query all suitable nodes
-            self.node_rtree.find_suitable_nodes(cpu, memory, &mut possible_nodes);
+            cache.node_rtree.find_suitable_nodes(cpu, memory, &mut possible_nodes);
+            decision.insufficient_resources = (cache.nodes.len() - possible_nodes.len()) as u64;
             pod.status.cluster_resource_starvation = possible_nodes.is_empty();
             self.pending_pods
                 .get_mut(&pod_uid)
@@ -157,21 +263,63 @@ impl Scheduler {
                 .status
                 .cluster_resource_starvation = possible_nodes.is_empty();
 
+            // Only a part of nodes is filtered in large clusters (percentage_of_nodes_to_score)
+            let num_to_find = self
+                .init_config
+                .borrow()
+                .scheduler
+                .num_feasible_nodes_to_find(cache.nodes.len());
+            if num_to_find < possible_nodes.len() {
+                // Start from the node where the previous attempt stopped, but try nominated node first
+                possible_nodes.sort_by_key(|node| node.metadata.uid);
+                let start = possible_nodes.partition_point(|node| node.metadata.uid < self.next_start_node_uid);
+                possible_nodes.rotate_left(start);
+                if let Some(index) = possible_nodes
+                    .iter()
+                    .position(|node| Some(node.metadata.uid) == pod.status.nominated_node_uid)
+                {
+                    let nominated = possible_nodes.remove(index);
+                    possible_nodes.insert(0, nominated);
+                }
+            }
+
             // Prepare node description
             is_schedulable.clear();
             is_schedulable.resize(possible_nodes.len(), true);
+            rejected_counts.clear();
+            rejected_counts.resize(self.filters.len(), 0);
 
             // Filter
             let mut suitable_count: usize = 0;
+            let mut processed_count: usize = possible_nodes.len();
+            let mut filter_calls: usize = 0;
             for (i, node) in possible_nodes.iter().enumerate() {
-                for filter_plugin in self.filters.iter() {
+                // Enough feasible nodes are found
+                if suitable_count == num_to_find {
+                    processed_count = i;
+                    break;
+                }
+
+                // Resources held for nominated pods are not available
+                let nominated = cache.node_with_nominated_pods(node, &pod);
+                let node = nominated.as_ref().unwrap_or(node);
+                if !Scheduler::is_node_consumable(node, cpu, memory) {
+                    is_schedulable[i] = false;
+                    decision.insufficient_resources += 1;
+                }
+
+                for (j, filter_plugin) in self.filters.iter().enumerate() {
                     // If node marked as infeasible, the remaining plugins will not be called
                     if !is_schedulable[i] {
                         break;
                     }
 
                     is_schedulable[i] =
-                        filter_plugin.filter(&self.running_pods, &self.pending_pods, &self.nodes, &pod, node);
+                        filter_plugin.filter(&cache.running_pods, &self.pending_pods, &cache.nodes, &pod, node);
+                    filter_calls += 1;
+                    if !is_schedulable[i] {
+                        rejected_counts[j] += 1;
+                    }
                 }
 
                 if is_schedulable[i] {
@@ -179,39 +327,54 @@ impl Scheduler {
                 }
             }
 
-            // Apply PostFilter if necessary
-            if suitable_count == 0 {
-                for (i, node) in possible_nodes.iter().enumerate() {
-                    for post_filter_plugin in self.post_filters.iter() {
-                        is_schedulable[i] =
-                            post_filter_plugin.filter(&self.running_pods, &self.pending_pods, &self.nodes, &pod, node);
-
-                        //  If any marks the node as Schedulable, the remaining will not be called
-                        if is_schedulable[i] {
-                            break;
-                        }
-                    }
+            self.cycle_time_spent += filter_calls as f64 * cost.filter_call_time;
 
-                    if is_schedulable[i] {
-                        suitable_count += 1;
-                    }
-                }
+            // The next attempt starts from the first node which is not processed
+            if processed_count < possible_nodes.len() {
+                self.next_start_node_uid = possible_nodes[processed_count].metadata.uid;
+                possible_nodes.truncate(processed_count);
+                is_schedulable.truncate(processed_count);
             }
 
-            // If PostFilter does not help
+            // Apply PostFilter if necessary
             if suitable_count == 0 {
-                let attempts = self.failed_attempts.entry(pod_uid).or_default();
-
-                if *attempts == 0 {
-                    // Place pod to UnschedulableQ
-                    self.unschedulable_queue.push(pod_uid, 0, self.ctx.time());
-                } else {
-                    // Place pod to BackoffQ
-                    self.backoff_queue.push(pod_uid, *attempts, self.ctx.time());
+                // Pod does not preempt again while victims on its nominated node terminate
+                let preemption = match self.is_eligible_to_preempt(&cache, &pod) {
+                    true => Some(self.find_preemption_candidate(&cache, &pod)),
+                    false => None,
+                };
+                drop(cache);
+                decision.filter_rejections = self.filter_rejections(&rejected_counts);
+                let mut events = self.rejection_events(&rejected_counts, decision.insufficient_resources > 0);
+
+                match preemption {
+                    Some(Some(candidate)) => {
+                        decision.status = DecisionStatus::Nominated;
+                        decision.node_uid = Some(candidate.node_uid);
+                        decision.victims = candidate.victims.clone();
+                        decision.reason = Some(format!(
+                            "Pod is nominated to node {} and waits for victims to terminate.",
+                            candidate.node_uid
+                        ));
+                        self.log_decision(&decision);
+                        self.preempt(pod_uid, &candidate);
+
+                        // Pod is retried when victims terminate
+                        events.insert(ClusterEvent::PodTerminated);
+                    }
+                    Some(None) => {
+                        // Nomination is cleared if PostFilter does not help
+                        decision.reason = Some(decision.unschedulable_reason());
+                        self.log_decision(&decision);
+                        self.nominate(pod_uid, None);
+                    }
+                    None => {
+                        decision.reason = Some(decision.unschedulable_reason());
+                        self.log_decision(&decision);
+                    }
                 }
 
-                *attempts += 1;
-
+                self.push_unschedulable_pod(pod_uid, events);
                 continue;
             }
 
@@ -227,18 +390,21 @@ impl Scheduler {
 
             // Score
             for (i, score_plugin) in self.scorers.iter().enumerate() {
+                score_plugin.pre_score(&cache.running_pods, &self.pending_pods, &cache.nodes, &pod);
                 for (j, node) in resulted_nodes.iter().enumerate() {
                     score_matrix[i][j] =
-                        score_plugin.score(&self.running_pods, &self.pending_pods, &self.nodes, &pod, node);
+                        score_plugin.score(&cache.running_pods, &self.pending_pods, &cache.nodes, &pod, node);
                 }
             }
 
+            self.cycle_time_spent += (self.scorers.len() * resulted_nodes.len()) as f64 * cost.score_call_time;
+
             // Normalize Score
             for (i, score_normalizer) in self.score_normalizers.iter().enumerate() {
                 score_normalizer.normalize(
-                    &self.running_pods,
+                    &cache.running_pods,
                     &self.pending_pods,
-                    &self.nodes,
+                    &cache.nodes,
                     &pod,
                     &resulted_nodes,
                     &mut score_matrix[i],
@@ -253,6 +419,7 @@ impl Scheduler {
             }
 
             let mut tmp_score: i64;
+            let mut totals: Vec<(i64, usize)> = Vec::new();
             for i in 0..resulted_nodes.len() {
                 tmp_score = 0;
                 for j in 0..self.scorers.len() {
@@ -262,59 +429,523 @@ impl Scheduler {
                     max_score = tmp_score;
                     best_node_index = i;
                 }
+                if top_k.is_some() {
+                    totals.push((-tmp_score, i));
+                }
+            }
+
+            // Describe the best nodes in decision log
+            if let Some(top_k) = top_k {
+                totals.sort();
+                for &(total, i) in totals.iter().take(top_k) {
+                    decision.top_nodes.push(NodeScores {
+                        node_uid: resulted_nodes[i].metadata.uid,
+                        scores: self
+                            .scorers
+                            .iter()
+                            .enumerate()
+                            .map(|(j, scorer)| (scorer.name(), score_matrix[j][i]))
+                            .collect(),
+                        total: -total,
+                    });
+                }
+            }
+
+            // Nominated node is preferred if pod fits it
+            if let Some(index) = resulted_nodes
+                .iter()
+                .position(|node| Some(node.metadata.uid) == pod.status.nominated_node_uid)
+            {
+                best_node_index = index;
             }
 
-            // Get best node
             let node_uid = resulted_nodes[best_node_index].metadata.uid;
-            let node = self.nodes.get(&node_uid).unwrap();
-
-            // If not enough resources -> build preemption list
-            let mut preempt_uids: Vec<u64> = Vec::new();
-            if !node.is_both_consumable(cpu, memory) {
-                let (mut cpu, mut memory) = (node.spec.available_cpu, node.spec.available_memory);
-                for &tmp_uid in node.status.pods.iter() {
-                    let tmp_pod = self.running_pods.get(&tmp_uid).unwrap();
-                    if tmp_pod.spec.priority >= pod.spec.priority {
-                        continue;
+            drop(cache);
+            decision.filter_rejections = self.filter_rejections(&rejected_counts);
+            decision.node_uid = Some(node_uid);
+
+            // Reserve
+            self.assume_pod(pod_uid, node_uid);
+            let node = cache_ptr.borrow().nodes.get(&node_uid).unwrap().clone();
+            if !self.run_reserve_plugins(&pod, &node) {
+                decision.status = DecisionStatus::Rejected;
+                decision.reason = Some(format!("Pod is rejected by Reserve on node {}.", node_uid));
+                self.log_decision(&decision);
+                self.unreserve_pod(&pod, &node);
+                continue;
+            }
+
+            // Permit
+            match self.run_permit_plugins(&pod, &node) {
+                PermitStatus::Allow => {
+                    if self.bind_pod(pod, &node) {
+                        decision.status = DecisionStatus::Bound;
+                        scheduled_left -= 1;
+                    } else {
+                        decision.status = DecisionStatus::Rejected;
+                        decision.reason = Some(format!("Pod is rejected by PreBind on node {}.", node_uid));
                     }
+                    self.log_decision(&decision);
+                }
+                PermitStatus::Wait(timeout) => {
+                    decision.status = DecisionStatus::WaitingOnPermit;
+                    self.log_decision(&decision);
+
+                    dp_scheduler!(
+                        "{:.3} scheduler pod_uid:{:?} waits on permit -> node_uid:{:?}",
+                        self.ctx.time(),
+                        pod_uid,
+                        node_uid
+                    );
 
-                    cpu += tmp_pod.spec.request_cpu;
-                    memory += tmp_pod.spec.request_memory;
-                    preempt_uids.push(tmp_uid);
+                    self.waiting_pods.insert(
+                        pod_uid,
+                        WaitingPod {
+                            pod,
+                            node,
+                            since: self.ctx.time(),
+                            deadline: self.ctx.time() + timeout,
+                        },
+                    );
+                }
+                PermitStatus::Reject => {
+                    decision.status = DecisionStatus::Rejected;
+                    decision.reason = Some(format!("Pod is rejected by Permit on node {}.", node_uid));
+                    self.log_decision(&decision);
+                    self.unreserve_pod(&pod, &node);
+                }
+            }
+        }
 
-                    if cpu >= pod.spec.request_cpu && memory >= pod.spec.request_memory {
-                        break;
+        // Pods reserved in this cycle may allow waiting ones
+        self.process_waiting_pods();
+
+        self.busy_until = self.now();
+        self.cycle_time_spent = 0.0;
+    }
+
+    /// Current time of scheduler: simulation time plus time spent in the current cycle.
+    pub fn now(&self) -> f64 {
+        return self.ctx.time() + self.cycle_time_spent;
+    }
+
+    ////////////////// Preemption //////////////////
+
+    /// Pod may not preempt while lower priority victims on its nominated node are terminating.
+    /// Analog of PodEligibleToPreemptOthers of kube-scheduler.
+    pub fn is_eligible_to_preempt(&self, cache: &SchedulerCache, pod: &Pod) -> bool {
+        let node = match pod.status.nominated_node_uid.and_then(|uid| cache.nodes.get(&uid)) {
+            Some(node) => node,
+            None => return true,
+        };
+        return !node.status.pods.iter().any(|uid| {
+            let other = cache.running_pods.get(uid).unwrap();
+            other.status.phase == PodPhase::Preempted && other.spec.priority < pod.spec.priority
+        });
+    }
+
+    /// PostFilter. Among nodes marked by post filter plugins chooses the best candidate by
+    /// PreemptionCandidate::is_better_than. Resources held for other nominated pods stay used, and filters
+    /// are checked against cluster where victims are already removed. Nominated node is preferred.
+    pub fn find_preemption_candidate(&self, cache: &SchedulerCache, pod: &Pod) -> Option<PreemptionCandidate> {
+        // Post filter plugin which rejects pod in PreFilter marks no node
+        let post_filters: Vec<&Box<dyn IFilterPlugin + Send>> = self
+            .post_filters
+            .iter()
+            .filter(|plugin| plugin.pre_filter(&cache.running_pods, &self.pending_pods, &cache.nodes, pod))
+            .collect();
+
+        // Nodes are sorted to make choice independent of HashMap order
+        let mut node_uids: Vec<u64> = cache.nodes.keys().copied().collect();
+        node_uids.sort();
+
+        // Victims are removed from the copy of running pods while filters are checked
+        let mut running_pods: Option<HashMap<u64, Pod>> = None;
+
+        let mut best: Option<PreemptionCandidate> = None;
+        for node_uid in node_uids {
+            let node = cache.nodes.get(&node_uid).unwrap();
+            let nominated = cache.node_with_nominated_pods(node, pod);
+            let node = nominated.as_ref().unwrap_or(node);
+
+            //  If any marks the node as Schedulable, the remaining will not be called
+            let is_post_filtered = post_filters
+                .iter()
+                .any(|plugin| plugin.filter(&cache.running_pods, &self.pending_pods, &cache.nodes, pod, node));
+            if !is_post_filtered {
+                continue;
+            }
+
+            let candidate = match preemption::select_victims(&cache.running_pods, pod, node) {
+                Some(candidate) => candidate,
+                None => continue,
+            };
+            let node = preemption::node_without_victims(&cache.running_pods, node, &candidate.victims);
+            let running_pods = running_pods.get_or_insert_with(|| cache.running_pods.clone());
+            let victims: Vec<Pod> = candidate
+                .victims
+                .iter()
+                .map(|uid| running_pods.remove(uid).unwrap())
+                .collect();
+            let is_schedulable = self.filters.iter().all(|plugin| {
+                plugin.pre_filter(running_pods, &self.pending_pods, &cache.nodes, pod)
+                    && plugin.filter(running_pods, &self.pending_pods, &cache.nodes, pod, &node)
+            });
+            for victim in victims.iter() {
+                running_pods.insert(victim.metadata.uid, victim.clone());
+            }
+            if !is_schedulable {
+                continue;
+            }
+
+            let mut candidate = candidate;
+            let victims: Vec<(u64, u64)> = victims
+                .iter()
+                .map(|victim| (victim.metadata.uid, victim.metadata.group_uid))
+                .collect();
+            candidate.pdb_violations = self.disruption_budgets.borrow().count_violations(&victims);
+
+            if pod.status.nominated_node_uid == Some(node_uid) {
+                return Some(candidate);
+            }
+            if best.as_ref().map_or(true, |best| candidate.is_better_than(best)) {
+                best = Some(candidate);
+            }
+        }
+
+        return best;
+    }
+
+    /// Asks kubelet to preempt victims and nominates pod to their node. Victims stay in cache until
+    /// kubelet preempts them, but can not be chosen as victims again.
+    pub fn preempt(&mut self, pod_uid: u64, candidate: &PreemptionCandidate) {
+        dp_scheduler!(
+            "{:.3} scheduler pod_uid:{:?} nominated -> node_uid:{:?} victims:{:?}",
+            self.ctx.time(),
+            pod_uid,
+            candidate.node_uid,
+            candidate.victims
+        );
+
+        let mut victims: Vec<(u64, u64)> = Vec::new();
+        for uid in candidate.victims.iter() {
+            let mut cache = self.cache.borrow_mut();
+            let victim = cache.running_pods.get_mut(uid).unwrap();
+            victim.status.phase = PodPhase::Preempted;
+            victims.push((*uid, victim.metadata.group_uid));
+        }
+        self.disruption_budgets.borrow_mut().record_disruption(&victims);
+
+        for &(uid, _) in victims.iter() {
+            self.send_pod_phase_update(None, uid, None, candidate.node_uid, PodPhase::Preempted);
+        }
+        self.nominate(pod_uid, Some(candidate.node_uid));
+    }
+
+    /// Sets or clears nominated node of pending pod.
+    pub fn nominate(&mut self, pod_uid: u64, node_uid: Option<u64>) {
+        let pod = self.pending_pods.get_mut(&pod_uid).unwrap();
+        pod.status.nominated_node_uid = node_uid;
+
+        let mut cache = self.cache.borrow_mut();
+        cache.nominated_pods.remove(&pod_uid);
+        cache.nominate(pod);
+    }
+
+    ////////////////// Reserve, Permit and Bind //////////////////
+
+    /// Places pod on node in cache before it is bound, so next pods see node resources as used.
+    pub fn assume_pod(&mut self, pod_uid: u64, node_uid: u64) {
+        let mut cached = self.pending_pods.remove(&pod_uid).unwrap();
+        cached.status.node_uid = Some(node_uid);
+        let (cpu, memory) = (cached.spec.request_cpu, cached.spec.request_memory);
+        {
+            // Resources of assumed pod are consumed on node, nomination does not hold them any more
+            let mut cache = self.cache.borrow_mut();
+            cache.nominated_pods.remove(&pod_uid);
+            cache.running_pods.insert(pod_uid, cached);
+        }
+
+        // Consume node resources
+        self.place_pod_to_node(pod_uid, node_uid, cpu, memory);
+    }
+
+    pub fn run_reserve_plugins(&self, pod: &Pod, node: &Node) -> bool {
+        let cache = self.cache.borrow();
+        return self
+            .reserves
+            .iter()
+            .all(|plugin| plugin.reserve(&cache.running_pods, &self.pending_pods, &cache.nodes, pod, node));
+    }
+
+    pub fn run_unreserve_plugins(&self, pod: &Pod, node: &Node) {
+        let cache = self.cache.borrow();
+        for plugin in self.reserves.iter().rev() {
+            plugin.unreserve(&cache.running_pods, &self.pending_pods, &cache.nodes, pod, node);
+        }
+    }
+
+    pub fn run_permit_plugins(&self, pod: &Pod, node: &Node) -> PermitStatus {
+        let cache = self.cache.borrow();
+        let mut status = PermitStatus::Allow;
+        for plugin in self.permits.iter() {
+            status = status.combine(plugin.permit(&cache.running_pods, &self.pending_pods, &cache.nodes, pod, node));
+        }
+        return status;
+    }
+
+    /// Reverts assume_pod and returns pod to the queues.
+    pub fn unreserve_pod(&mut self, pod: &Pod, node: &Node) {
+        let pod_uid = pod.metadata.uid;
+        self.run_unreserve_plugins(pod, node);
+
+        // Move cached pod from running back to pending
+        let mut cached = self.cache.borrow_mut().running_pods.remove(&pod_uid).unwrap();
+        let node_uid = cached.status.node_uid.unwrap();
+        if self.cache.borrow().nodes.contains_key(&node_uid) {
+            self.remove_pod_from_node(pod_uid, node_uid, cached.spec.request_cpu, cached.spec.request_memory);
+        }
+        cached.status.node_uid = None;
+        self.cache.borrow_mut().nominate(&cached);
+        self.pending_pods.insert(pod_uid, cached);
+
+        // Reserve and Permit plugins do not register events
+        self.push_unschedulable_pod(pod_uid, ClusterEvent::all().into_iter().collect());
+    }
+
+    /// Runs PreBind and sends pod to kubelet. Returns false if pod is unreserved by PreBind.
+    pub fn bind_pod(&mut self, mut pod: Pod, node: &Node) -> bool {
+        let pod_uid = pod.metadata.uid;
+        let node_uid = node.metadata.uid;
+
+        // PreBind
+        let is_ready = {
+            let cache = self.cache.borrow();
+            self.pre_binds
+                .iter()
+                .all(|plugin| plugin.pre_bind(&cache.running_pods, &self.pending_pods, &cache.nodes, &pod, node))
+        };
+        if !is_ready {
+            self.unreserve_pod(&pod, node);
+            return false;
+        }
+
+        // Assumed pod becomes running
+        {
+            let mut cache = self.cache.borrow_mut();
+            let cached = cache.running_pods.get_mut(&pod_uid).unwrap();
+            cached.status.phase = PodPhase::Running;
+            cached.status.nominated_node_uid = None;
+        }
+
+        // Update pod status
+        pod.status.node_uid = Some(node_uid);
+        pod.status.phase = PodPhase::Running;
+        pod.status.nominated_node_uid = None;
+
+        // Clear failed attempts
+        self.failed_attempts.remove(&pod_uid);
+
+        if let Some(since) = self.queued_since.remove(&pod_uid) {
+            let now = self.now();
+            self.monitoring
+                .borrow_mut()
+                .scheduler_on_pod_scheduled(pod_uid, node_uid, now, now - since);
+        }
+
+        // Send PodPhase update
+        self.send_pod_phase_update(Some(pod), pod_uid, None, node_uid, PodPhase::Running);
+
+        dp_scheduler!(
+            "{:.3} scheduler pod_uid:{:?} placed -> node_uid:{:?}",
+            self.ctx.time(),
+            pod_uid,
+            node_uid
+        );
+        return true;
+    }
+
+    /// Asks Permit again for waiting pods. Pod is bound when all plugins allow it,
+    /// and unreserved when any plugin rejects it, its timeout expires or its node is removed.
+    /// Pods of a gang (group_min_member > 0) are unreserved together.
+    pub fn process_waiting_pods(&mut self) {
+        let waiting_uids: Vec<u64> = self.waiting_pods.keys().copied().collect();
+        for pod_uid in waiting_uids {
+            // Pod may be already released with its gang
+            let waiting = match self.waiting_pods.get(&pod_uid) {
+                Some(waiting) => waiting,
+                None => continue,
+            };
+            let node_uid = waiting.node.metadata.uid;
+            let deadline = waiting.deadline;
+
+            let node = self.cache.borrow().nodes.get(&node_uid).cloned();
+            let status = match node.as_ref() {
+                Some(node) => self.run_permit_plugins(&waiting.pod, node),
+                None => PermitStatus::Reject,
+            };
+
+            let mut decision = self.new_decision(pod_uid);
+            decision.node_uid = Some(node_uid);
+            match status {
+                PermitStatus::Allow => {
+                    let waiting = self.waiting_pods.remove(&pod_uid).unwrap();
+                    self.on_wait_finished(&waiting);
+                    if self.bind_pod(waiting.pod, &node.unwrap()) {
+                        decision.status = DecisionStatus::Bound;
+                    } else {
+                        decision.status = DecisionStatus::Rejected;
+                        decision.reason = Some(format!("Pod is rejected by PreBind on node {}.", node_uid));
+                    }
+                    self.log_decision(&decision);
+                }
+                PermitStatus::Wait(_) if self.ctx.time() < deadline => {
+                    // Keep waiting
+                }
+                _ => {
+                    decision.status = DecisionStatus::Rejected;
+                    decision.reason = Some(match (node.is_some(), status) {
+                        (false, _) => format!("Node {} is removed while pod waits on Permit.", node_uid),
+                        (true, PermitStatus::Wait(_)) => format!("Permit timeout expired on node {}.", node_uid),
+                        (true, _) => format!("Pod is rejected by Permit on node {}.", node_uid),
+                    });
+                    self.log_decision(&decision);
+
+                    let waiting = self.waiting_pods.remove(&pod_uid).unwrap();
+                    self.on_wait_finished(&waiting);
+                    self.unreserve_pod(&waiting.pod, &waiting.node);
+
+                    // Release the rest of the gang, so it does not hold node resources
+                    if waiting.pod.metadata.group_min_member > 0 {
+                        self.release_waiting_group(waiting.pod.metadata.group_uid);
                     }
                 }
             }
+        }
+    }
 
-            // Move cached pod from pending to running
-            let mut cached = self.pending_pods.remove(&pod_uid).unwrap();
-            cached.status.phase = PodPhase::Running;
-            cached.status.node_uid = Some(node_uid);
-            self.running_pods.insert(pod_uid, cached);
+    pub fn release_waiting_group(&mut self, group_uid: u64) {
+        let group_uids: Vec<u64> = self
+            .waiting_pods
+            .iter()
+            .filter(|(_, waiting)| waiting.pod.metadata.group_uid == group_uid)
+            .map(|(&pod_uid, _)| pod_uid)
+            .collect();
+
+        for pod_uid in group_uids {
+            let waiting = self.waiting_pods.remove(&pod_uid).unwrap();
+
+            let mut decision = self.new_decision(pod_uid);
+            decision.status = DecisionStatus::Rejected;
+            decision.node_uid = Some(waiting.node.metadata.uid);
+            decision.reason = Some("Another pod of the gang is rejected while waiting on Permit.".to_string());
+            self.log_decision(&decision);
+
+            self.on_wait_finished(&waiting);
+            self.unreserve_pod(&waiting.pod, &waiting.node);
+        }
+    }
+
+    fn on_wait_finished(&self, waiting: &WaitingPod) {
+        if waiting.pod.metadata.group_min_member > 0 {
+            self.monitoring
+                .borrow_mut()
+                .scheduler_on_gang_wait_finished(self.ctx.time() - waiting.since);
+        }
+    }
 
-            // Update pod status
-            pod.status.node_uid = Some(node_uid);
-            pod.status.phase = PodPhase::Running;
+    ////////////////// Decision log //////////////////
 
-            // Consume node resources
-            self.place_pod_to_node(pod_uid, node_uid, cpu, memory);
+    pub fn new_decision(&self, pod_uid: u64) -> SchedulingDecision {
+        return SchedulingDecision {
+            time: self.now(),
+            scheduler_name: self.name.clone(),
+            pod_uid,
+            ..Default::default()
+        };
+    }
+
+    /// Names of filter plugins which rejected any node, with number of rejected nodes.
+    pub fn filter_rejections(&self, rejected_counts: &[u64]) -> Vec<(String, u64)> {
+        return self
+            .filters
+            .iter()
+            .zip(rejected_counts)
+            .filter(|(_, &count)| count > 0)
+            .map(|(plugin, &count)| (plugin.name(), count))
+            .collect();
+    }
 
-            // Clear failed attempts
-            self.failed_attempts.remove(&pod_uid);
+    pub fn log_decision(&self, decision: &SchedulingDecision) {
+        self.monitoring.borrow_mut().scheduler_on_decision(decision);
+    }
 
-            // Send PodPhase update
-            self.send_pod_phase_update(Some(pod), pod_uid, Some(preempt_uids), node_uid, PodPhase::Running);
-            scheduled_left -= 1;
+    ////////////////// Queueing hints //////////////////
 
-            dp_scheduler!(
-                "{:.3} scheduler pod_uid:{:?} placed -> node_uid:{:?}",
-                self.ctx.time(),
-                pod_uid,
-                node_uid
-            );
+    /// Events which may make pod schedulable after no node passed filters and post filters.
+    /// New node may always fit pod. Nodes without enough free resources are not passed to filters at all,
+    /// so they may fit pod after other pods terminate.
+    pub fn rejection_events(&self, rejected_counts: &[u64], has_unfit_nodes: bool) -> BTreeSet<ClusterEvent> {
+        let mut events: BTreeSet<ClusterEvent> = BTreeSet::from([ClusterEvent::NodeAdded]);
+        if has_unfit_nodes {
+            events.insert(ClusterEvent::PodTerminated);
         }
+        for (plugin, _) in self.filters.iter().zip(rejected_counts).filter(|(_, &count)| count > 0) {
+            events.extend(plugin.events_to_register());
+        }
+        for plugin in self.post_filters.iter() {
+            events.extend(plugin.events_to_register());
+        }
+        return events;
+    }
+
+    /// Moves pods from UnschedulableQ to ActiveQ if any event registered for them happened since the last call.
+    pub fn process_cluster_events(&mut self) {
+        let events: Vec<ClusterEvent> = self
+            .cache
+            .borrow_mut()
+            .take_cluster_events(self.cluster_events_index)
+            .into_iter()
+            .collect();
+        if events.is_empty() {
+            return;
+        }
+
+        for pod_uid in self.queueing_hints.take_matching(&events) {
+            if self.unschedulable_queue.try_remove(pod_uid) {
+                dp_scheduler!(
+                    "{:.3} scheduler pod_uid:{:?} requeued on cluster events:{:?}",
+                    self.ctx.time(),
+                    pod_uid,
+                    events
+                );
+                self.activate_pod(pod_uid);
+            }
+        }
+    }
+
+    /// Places pod which failed this scheduling attempt to UnschedulableQ or BackoffQ.
+    /// Pod leaves UnschedulableQ after backoff delay or earlier, when any of events happens in cluster.
+    pub fn push_unschedulable_pod(&mut self, pod_uid: u64, events: BTreeSet<ClusterEvent>) {
+        let now = self.now();
+        let attempts = self.failed_attempts.entry(pod_uid).or_default();
+
+        if *attempts == 0 {
+            // Place pod to UnschedulableQ
+            self.unschedulable_queue.push(pod_uid, 0, now);
+            self.queueing_hints.insert(pod_uid, events);
+        } else {
+            // Place pod to BackoffQ
+            self.backoff_queue.push(pod_uid, *attempts, now);
+        }
+
+        *attempts += 1;
+    }
+
+    /// Places pending pod to ActiveQ. Queue wait time of pod is measured from this moment.
+    pub fn activate_pod(&mut self, pod_uid: u64) {
+        self.queued_since.insert(pod_uid, self.ctx.time());
+        self.active_queue.push(self.pending_pods.get(&pod_uid).unwrap().clone());
     }
 
     ////////////////// Helpers for Node cache and RTree cache //////////////////
@@ -324,41 +955,27 @@ impl Scheduler {
     }
 
     pub fn place_pod_to_node(&mut self, pod_uid: u64, node_uid: u64, cpu: i64, memory: i64) {
-        // Get pod's node
-        let node = self.nodes.get_mut(&node_uid).unwrap();
-
-        // Remove node from RTree
-        self.node_rtree.remove(&node);
-
-        // Update node
-        node.consume(cpu, memory);
-        let _not_presented = node.status.pods.insert(pod_uid);
-        assert!(_not_presented);
-
-        // Add node to RTree
-        self.node_rtree.insert(node.clone());
+        // Update node and RTree
+        self.cache
+            .borrow_mut()
+            .place_pod_to_node(pod_uid, node_uid, cpu, memory);
 
         // Update monitoring
-        self.monitoring.borrow_mut().scheduler_on_node_consume(cpu, memory);
+        self.monitoring
+            .borrow_mut()
+            .scheduler_on_node_consume(node_uid, cpu, memory);
     }
 
     pub fn remove_pod_from_node(&mut self, pod_uid: u64, node_uid: u64, cpu: i64, memory: i64) {
-        // Get pod's node
-        let node = self.nodes.get_mut(&node_uid).unwrap();
-
-        // Remove node from RTree
-        self.node_rtree.remove(&node);
-
-        // Update node
-        node.restore(cpu, memory);
-        let _was_presented = node.status.pods.remove(&pod_uid);
-        assert!(_was_presented);
-
-        // Add node to RTree
-        self.node_rtree.insert(node.clone());
+        // Update node and RTree
+        self.cache
+            .borrow_mut()
+            .remove_pod_from_node(pod_uid, node_uid, cpu, memory);
 
         // Update monitoring
-        self.monitoring.borrow_mut().scheduler_on_node_restore(cpu, memory);
+        self.monitoring
+            .borrow_mut()
+            .scheduler_on_node_restore(node_uid, cpu, memory);
     }
 
     ////////////////// Process pod by phase ////////////////////////////////////
@@ -366,43 +983,44 @@ impl Scheduler {
     pub fn process_new_pod(&mut self, pod: Pod) {
         let pod_uid = pod.metadata.uid;
 
-        assert_eq!(self.running_pods.contains_key(&pod_uid), false);
+        assert_eq!(self.cache.borrow().running_pods.contains_key(&pod_uid), false);
         assert_eq!(self.pending_pods.contains_key(&pod_uid), false);
         assert_eq!(pod.status.phase, PodPhase::Pending);
         assert_eq!(pod.status.cluster_resource_starvation, false);
 
-        self.pending_pods.insert(pod_uid, pod.clone());
-        self.active_queue.push(pod);
+        self.pending_pods.insert(pod_uid, pod);
+        self.activate_pod(pod_uid);
     }
 
     pub fn process_reschedule_pod(&mut self, pod_uid: u64, phase: PodPhase) {
-        assert_eq!(self.running_pods.contains_key(&pod_uid), true);
+        assert_eq!(self.cache.borrow().running_pods.contains_key(&pod_uid), true);
         assert_eq!(self.pending_pods.contains_key(&pod_uid), false);
 
         // Remove pod from running set
-        let mut pod = self.running_pods.remove(&pod_uid).unwrap();
+        let mut pod = self.cache.borrow_mut().running_pods.remove(&pod_uid).unwrap();
 
         // Restore node resources
         let node_uid = pod.status.node_uid.unwrap();
-        if self.nodes.contains_key(&node_uid) {
+        if self.cache.borrow().nodes.contains_key(&node_uid) {
             self.remove_pod_from_node(pod_uid, node_uid, pod.spec.request_cpu, pod.spec.request_memory);
+            self.cache.borrow_mut().push_cluster_event(ClusterEvent::PodTerminated);
             pod.status.node_uid = None;
         }
 
         // Place pod to pending set
         pod.status.phase = PodPhase::Pending;
-        self.pending_pods.insert(pod_uid, pod.clone());
+        self.pending_pods.insert(pod_uid, pod);
 
         // Place pod to ActiveQ
-        self.active_queue.push(pod);
+        self.activate_pod(pod_uid);
 
         // Update monitoring
         match phase {
             PodPhase::Evicted => {
-                self.monitoring.borrow_mut().scheduler_on_pod_evicted();
+                self.monitoring.borrow_mut().scheduler_on_pod_evicted(pod_uid);
             }
             PodPhase::Preempted => {
-                self.monitoring.borrow_mut().scheduler_on_pod_preempted();
+                self.monitoring.borrow_mut().scheduler_on_pod_preempted(pod_uid);
             }
             PodPhase::Pending => {
                 // Do nothing
@@ -414,16 +1032,17 @@ impl Scheduler {
     }
 
     pub fn process_finished_pod(&mut self, pod_uid: u64, phase: PodPhase) {
-        assert_eq!(self.running_pods.contains_key(&pod_uid), true);
+        assert_eq!(self.cache.borrow().running_pods.contains_key(&pod_uid), true);
         assert_eq!(self.pending_pods.contains_key(&pod_uid), false);
 
         // Remove pod from running
-        let pod = self.running_pods.remove(&pod_uid).unwrap();
+        let pod = self.cache.borrow_mut().running_pods.remove(&pod_uid).unwrap();
 
         // Restore node resources if node exist
         let node_uid = pod.status.node_uid.unwrap();
-        if self.nodes.contains_key(&node_uid) {
+        if self.cache.borrow().nodes.contains_key(&node_uid) {
             self.remove_pod_from_node(pod_uid, node_uid, pod.spec.request_cpu, pod.spec.request_memory);
+            self.cache.borrow_mut().push_cluster_event(ClusterEvent::PodTerminated);
         }
 
         // Remove pod's failed attempts
@@ -432,10 +1051,10 @@ impl Scheduler {
         // Update monitoring
         match phase {
             PodPhase::Succeeded => {
-                self.monitoring.borrow_mut().scheduler_on_pod_succeed();
+                self.monitoring.borrow_mut().scheduler_on_pod_succeed(pod_uid);
             }
             PodPhase::Failed => {
-                self.monitoring.borrow_mut().scheduler_on_pod_failed();
+                self.monitoring.borrow_mut().scheduler_on_pod_failed(pod_uid);
             }
             _ => {
                 panic!("Logic error. This fn can be called only with phase Succeeded or Failed.");
@@ -446,29 +1065,41 @@ impl Scheduler {
     pub fn process_removed_pod(&mut self, pod_uid: u64) {
         // Remove pod's failed attempts
         self.failed_attempts.remove(&pod_uid);
+        self.queued_since.remove(&pod_uid);
+
+        // Release reservation of pod waiting on permit
+        if let Some(waiting) = self.waiting_pods.remove(&pod_uid) {
+            self.run_unreserve_plugins(&waiting.pod, &waiting.node);
+        }
 
         // Remove pod from cache
-        match (self.running_pods.remove(&pod_uid), self.pending_pods.remove(&pod_uid)) {
+        let running = self.cache.borrow_mut().running_pods.remove(&pod_uid);
+        match (running, self.pending_pods.remove(&pod_uid)) {
             (Some(pod), None) => {
                 // Restore node resources if node exist
                 let node_uid = pod.status.node_uid.unwrap();
-                if self.nodes.contains_key(&node_uid) {
+                if self.cache.borrow().nodes.contains_key(&node_uid) {
                     self.remove_pod_from_node(pod_uid, node_uid, pod.spec.request_cpu, pod.spec.request_memory);
+                    self.cache.borrow_mut().push_cluster_event(ClusterEvent::PodTerminated);
                 }
 
                 // Update monitoring
-                self.monitoring.borrow_mut().scheduler_on_pod_removed();
+                self.monitoring.borrow_mut().scheduler_on_pod_removed(pod_uid);
             }
             (None, Some(pod)) => {
-                // Try remove from BackOffQ or UnschedulableQ or ActiveQ
+                // Release resources held for nominated pod
                 let pod_uid = pod.metadata.uid;
+                self.cache.borrow_mut().nominated_pods.remove(&pod_uid);
+
+                // Try remove from BackOffQ or UnschedulableQ or ActiveQ
+                self.queueing_hints.remove(pod_uid);
                 let result = self.backoff_queue.try_remove(pod_uid)
                     || self.unschedulable_queue.try_remove(pod_uid)
                     || self.active_queue.try_remove(pod);
                 assert!(result, "Invariant violated. Pending pod not in any queue.");
 
                 // Update monitoring
-                self.monitoring.borrow_mut().scheduler_on_pod_removed()
+                self.monitoring.borrow_mut().scheduler_on_pod_removed(pod_uid)
             }
             (None, None) => {
                 // Nothing to do
@@ -482,7 +1113,7 @@ impl Scheduler {
     ////////////////// Helpers //////////////////
 
     pub fn is_pod_cached(&self, pod_uid: u64) -> bool {
-        return self.running_pods.contains_key(&pod_uid) || self.pending_pods.contains_key(&pod_uid);
+        return self.cache.borrow().running_pods.contains_key(&pod_uid) || self.pending_pods.contains_key(&pod_uid);
     }
 
     ////////////////// Export metrics //////////////////
@@ -504,7 +1135,7 @@ impl Scheduler {
                 node_uid,
             },
             self.api_sim_id,
-            self.init_config.borrow().network_delays.kubelet2api,
+            self.init_config.borrow().network_delays.kubelet2api + self.cycle_time_spent,
         );
     }
 }
@@ -578,11 +1209,16 @@ impl dsc::EventHandler for Scheduler {
                         pod2remove.push(pod_uid);
                     }
                 }
-                // Search in running pods
-                for (&pod_uid, pod) in self.running_pods.iter() {
-                    if pod.metadata.group_uid == group_uid {
+                // Search in running pods. Running pods are shared, so take only pods placed by this scheduler.
+                for (&pod_uid, pod) in self.cache.borrow().running_pods.iter() {
+                    if pod.metadata.group_uid == group_uid && pod.spec.scheduler_name == self.name {
                         pod2remove.push(pod_uid);
 
+                        // Pods waiting on permit are not on kubelet yet
+                        if self.waiting_pods.contains_key(&pod_uid) {
+                            continue;
+                        }
+
                         //Notify kubelet to remove this pod
                         let node_uid = pod.status.node_uid.unwrap();
                         self.send_pod_phase_update(None, pod_uid, None, node_uid, PodPhase::Removed);
@@ -599,10 +1235,15 @@ impl dsc::EventHandler for Scheduler {
             EventRemovePod { pod_uid } => {
                 dp_scheduler!("{:.3} scheduler EventRemovePod pod_uid:{:?}", self.ctx.time(), pod_uid);
 
-                // If pod is running -> notify kubelet to remove this pod
-                if self.running_pods.contains_key(&pod_uid) {
-                    let node_uid = self.running_pods.get(&pod_uid).unwrap().status.node_uid.unwrap();
-                    self.send_pod_phase_update(None, pod_uid, None, node_uid, PodPhase::Removed);
+                // If pod is running (not waiting on permit) -> notify kubelet to remove this pod
+                let running_node_uid = self
+                    .cache
+                    .borrow()
+                    .running_pods
+                    .get(&pod_uid)
+                    .map(|pod| pod.status.node_uid);
+                if let (Some(node_uid), false) = (running_node_uid, self.waiting_pods.contains_key(&pod_uid)) {
+                    self.send_pod_phase_update(None, pod_uid, None, node_uid.unwrap(), PodPhase::Removed);
                 }
 
                 // Update inner state
@@ -629,12 +1270,31 @@ impl dsc::EventHandler for Scheduler {
                     node.metadata.uid
                 );
 
-                // Update monitoring
-                self.monitoring.borrow_mut().scheduler_on_node_added(&node);
+                // Update cache and monitoring if node is not added by another scheduler
+                let is_added = self.cache.borrow_mut().add_node(node.clone());
+                if is_added {
+                    self.monitoring.borrow_mut().scheduler_on_node_added(&node);
+                }
+            }
 
-                // Update cache
-                self.nodes.insert(node.metadata.uid, node.clone());
-                self.node_rtree.insert(node);
+            EventUpdateNode {
+                node_uid,
+                labels,
+                taints,
+            } => {
+                dp_scheduler!(
+                    "{:.3} scheduler EventUpdateNode node_uid:{:?}",
+                    self.ctx.time(),
+                    node_uid
+                );
+
+                // Update cache and monitoring if node is not updated by another scheduler
+                let is_updated = self.cache.borrow_mut().update_node(node_uid, labels, taints.clone());
+                if let (true, Some(taints)) = (is_updated, taints) {
+                    self.monitoring
+                        .borrow_mut()
+                        .scheduler_on_node_taints_changed(node_uid, &taints);
+                }
             }
 
             EventRemoveNode { node_uid } => {
@@ -644,12 +1304,10 @@ impl dsc::EventHandler for Scheduler {
                     node_uid
                 );
 
-                // Update cache if necessary
-                match self.nodes.remove(&node_uid) {
+                // Update cache if necessary (node may be already removed by another scheduler)
+                let removed = self.cache.borrow_mut().remove_node(node_uid);
+                match removed {
                     Some(node) => {
-                        // Remove node from RTree
-                        self.node_rtree.remove(&node);
-
                         // Update monitoring
                         self.monitoring.borrow_mut().scheduler_on_node_removed(&node);
                     }
@@ -667,8 +1325,17 @@ impl dsc::EventHandler for Scheduler {
                 // Main scheduling cycle
                 self.schedule();
 
-                // If there are pending pods -> continue SelfUpdate
-                if self.pending_pods.len() > 0 {
+                // Update monitoring with pending pods of each group
+                let mut counts: BTreeMap<u64, u64> = BTreeMap::new();
+                for pod in self.pending_pods.values() {
+                    *counts.entry(pod.metadata.group_uid).or_default() += 1;
+                }
+                self.monitoring
+                    .borrow_mut()
+                    .scheduler_update_pending_pods_by_group(&self.name, counts);
+
+                // If there are pending or waiting pods -> continue SelfUpdate
+                if self.pending_pods.len() > 0 || self.waiting_pods.len() > 0 {
                     self.ctx.emit_self(
                         EventSelfUpdate {},
                         self.init_config.borrow().scheduler.self_update_period,
@@ -682,9 +1349,9 @@ impl dsc::EventHandler for Scheduler {
         // Update monitoring
         self.monitoring
             .borrow_mut()
-            .scheduler_update_pending_pod_count(self.pending_pods.len());
+            .scheduler_update_pending_pod_count(&self.name, self.pending_pods.len());
         self.monitoring
             .borrow_mut()
-            .scheduler_update_running_pod_count(self.running_pods.len());
+            .scheduler_update_running_pod_count(self.cache.borrow().running_pods.len());
     }
 }
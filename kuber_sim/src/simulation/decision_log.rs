@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum DecisionStatus {
+    /// Pod is bound to node.
+    Bound,
+    /// Victims are preempted from node and pod waits for them to terminate.
+    Nominated,
+    /// Pod is reserved on node and waits for Permit.
+    WaitingOnPermit,
+    /// No node passed PreFilter, Filter and PostFilter.
+    #[default]
+    Unschedulable,
+    /// Node was chosen, but pod was rejected by Reserve, Permit or PreBind.
+    Rejected,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NodeScores {
+    pub node_uid: u64,
+    /// Normalized score of each scorer, in pipeline order.
+    pub scores: Vec<(String, i64)>,
+    /// Weighted sum of scores.
+    pub total: i64,
+}
+
+/// One scheduling attempt of a pod. Written as a JSON line.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SchedulingDecision {
+    pub time: f64,
+    pub scheduler_name: String,
+    pub pod_uid: u64,
+    pub status: DecisionStatus,
+    /// Nodes in cluster at the moment of attempt.
+    pub total_nodes: u64,
+    /// Nodes without enough free resources. They are not passed to filter plugins.
+    pub insufficient_resources: u64,
+    /// Filter plugin which rejected pod in PreFilter.
+    pub pre_filter_rejected_by: Option<String>,
+    /// Number of nodes rejected by each filter plugin (only plugins which rejected any), in pipeline order.
+    pub filter_rejections: Vec<(String, u64)>,
+    /// Best scored nodes, the best first.
+    pub top_nodes: Vec<NodeScores>,
+    pub node_uid: Option<u64>,
+    pub victims: Vec<u64>,
+    /// Why pod is not bound (None if it is bound or waits on permit).
+    pub reason: Option<String>,
+}
+
+impl SchedulingDecision {
+    /// Explanation of Unschedulable status in kube-scheduler style:
+    /// "0/5 nodes are available: 3 insufficient resources, 2 FilterTaintsTolerations."
+    pub fn unschedulable_reason(&self) -> String {
+        if let Some(plugin) = self.pre_filter_rejected_by.as_ref() {
+            return format!("Pod is rejected by PreFilter plugin {}.", plugin);
+        }
+
+        let mut causes: Vec<String> = Vec::new();
+        if self.insufficient_resources > 0 {
+            causes.push(format!("{} insufficient resources", self.insufficient_resources));
+        }
+        for (plugin, count) in self.filter_rejections.iter() {
+            causes.push(format!("{} {}", count, plugin));
+        }
+        if causes.is_empty() {
+            return format!("0/{} nodes are available.", self.total_nodes);
+        }
+        return format!("0/{} nodes are available: {}.", self.total_nodes, causes.join(", "));
+    }
+}
+
+/// Optional JSON lines log of scheduling decisions and the last failure reason of each pod.
+/// Failure reasons are tracked even if the log file is disabled.
+#[derive(Debug, Default)]
+pub struct DecisionLog {
+    fout: Option<BufWriter<File>>,
+    /// How many best scored nodes are written for each decision.
+    top_k: usize,
+    last_failure_reasons: HashMap<u64, String>, // HashMap<pod_uid, reason>
+}
+
+impl DecisionLog {
+    pub fn new() -> Self {
+        Self {
+            fout: None,
+            top_k: 0,
+            last_failure_reasons: HashMap::new(),
+        }
+    }
+
+    pub fn enable(&mut self, path: &String, top_k: usize) {
+        let file = sim_ok!(
+            File::create(path),
+            format!("DecisionLog. Can not create file {}.", path)
+        );
+        self.fout = Some(BufWriter::new(file));
+        self.top_k = top_k;
+    }
+
+    pub fn disable(&mut self) {
+        if let Some(mut fout) = self.fout.take() {
+            fout.flush().unwrap();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        return self.fout.is_some();
+    }
+
+    pub fn top_k(&self) -> usize {
+        return self.top_k;
+    }
+
+    pub fn record(&mut self, decision: &SchedulingDecision) {
+        match decision.reason.as_ref() {
+            Some(reason) => {
+                self.last_failure_reasons.insert(decision.pod_uid, reason.clone());
+            }
+            None => {
+                self.last_failure_reasons.remove(&decision.pod_uid);
+            }
+        }
+
+        if let Some(fout) = self.fout.as_mut() {
+            serde_json::to_writer(&mut *fout, decision).unwrap();
+            fout.write_all(b"\n").unwrap();
+        }
+    }
+
+    pub fn last_failure_reason(&self, pod_uid: u64) -> Option<String> {
+        return self.last_failure_reasons.get(&pod_uid).cloned();
+    }
+
+    /// Pod is not scheduled any more (finished or removed), so its failure reason is dropped.
+    pub fn forget(&mut self, pod_uid: u64) {
+        self.last_failure_reasons.remove(&pod_uid);
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheduling_decision() {
+        let mut decision = SchedulingDecision { pod_uid: 7, total_nodes: 5, insufficient_resources: 3, ..Default::default() };
+        decision.filter_rejections = vec![("FilterTaintsTolerations".to_string(), 2)];
+        assert_eq!(decision.unschedulable_reason(), "0/5 nodes are available: 3 insufficient resources, 2 FilterTaintsTolerations.");
+
+        decision.pre_filter_rejected_by = Some("FilterCoscheduling".to_string());
+        assert_eq!(decision.unschedulable_reason(), "Pod is rejected by PreFilter plugin FilterCoscheduling.");
+
+        // JSON line round trip
+        let line = serde_json::to_string(&decision).unwrap();
+        assert!(!line.contains('\n'));
+        assert_eq!(serde_json::from_str::<SchedulingDecision>(&line).unwrap(), decision);
+
+        // The last attempt wins
+        let mut log = DecisionLog::new();
+        decision.reason = Some(decision.unschedulable_reason());
+        log.record(&decision);
+        assert_eq!(log.last_failure_reason(7), decision.reason);
+        decision.reason = None;
+        log.record(&decision);
+        assert_eq!(log.last_failure_reason(7), None);
+
+        // Reason of pod which leaves the scheduler is dropped
+        decision.reason = Some(decision.unschedulable_reason());
+        log.record(&decision);
+        log.forget(7);
+        assert_eq!(log.last_failure_reason(7), None);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::KuberSimError;
 use crate::simulation::init_config::InitConfig;
 use crate::simulation::init_nodes::InitNodes;
 use crate::simulation::init_trace::InitTrace;
@@ -12,7 +13,7 @@ struct SimConfig {
     pub init_config: InitConfig,
     pub init_nodes: InitNodes,
     pub init_trace: InitTrace,
-    pub pipeline_config: PipelineConfig,
+    pub pipeline_configs: Vec<(String, PipelineConfig)>, // Vec<(scheduler_name, pipeline_config)>
     pub seed: u64,
     pub flag_add_ca: bool,
     pub flag_add_hpa: bool,
@@ -22,7 +23,7 @@ struct SimConfig {
 pub struct Experiment {
     is_done: bool,
     simulations: LinkedList<(SimConfig, fn(&mut Simulation))>,
-    pids: LinkedList<thread::JoinHandle<()>>,
+    pids: LinkedList<thread::JoinHandle<Result<(), KuberSimError>>>,
     pid_names: LinkedList<String>,
 }
 
@@ -42,7 +43,7 @@ impl Experiment {
         init_config: &InitConfig,
         init_nodes: &InitNodes,
         init_trace: &InitTrace,
-        pipeline_config: &PipelineConfig,
+        pipeline_configs: &[(&str, &PipelineConfig)], // &[(scheduler_name, pipeline_config)]
         seed: u64,
         flag_add_ca: bool,
         flag_add_hpa: bool,
@@ -57,7 +58,10 @@ impl Experiment {
                 init_config: init_config.clone(),
                 init_nodes: init_nodes.clone(),
                 init_trace: init_trace.clone(),
-                pipeline_config: pipeline_config.clone(),
+                pipeline_configs: pipeline_configs
+                    .iter()
+                    .map(|&(name, config)| (name.to_string(), config.clone()))
+                    .collect(),
                 seed,
                 flag_add_ca,
                 flag_add_hpa,
@@ -74,29 +78,40 @@ impl Experiment {
         while let Some(sim_config) = self.simulations.pop_front() {
             self.pid_names.push_back(sim_config.0.out_path_prefix.clone());
             self.pids.push_back(thread::spawn(move || {
+                let pipeline_configs: Vec<(&str, &PipelineConfig)> = sim_config
+                    .0
+                    .pipeline_configs
+                    .iter()
+                    .map(|(name, config)| (name.as_str(), config))
+                    .collect();
                 let mut sim = Simulation::new(
                     sim_config.0.out_path_prefix,
                     &sim_config.0.init_config,
                     &sim_config.0.init_nodes,
                     &sim_config.0.init_trace,
-                    &sim_config.0.pipeline_config,
+                    &pipeline_configs,
                     sim_config.0.seed.clone(),
                     sim_config.0.flag_add_ca,
                     sim_config.0.flag_add_hpa,
                     sim_config.0.flag_add_vpa,
-                );
+                )?;
 
                 sim_config.1(&mut sim);
+                return Ok(());
             }));
         }
     }
 
+    /// Simulations with invalid input are reported and do not affect the others.
     pub fn join_all(&mut self) {
         while let (Some(pid), pid_name) = (self.pids.pop_front(), self.pid_names.pop_front()) {
             match pid.join() {
-                Ok(_) => {
+                Ok(Ok(_)) => {
                     println!("Finished Ok: {:?}", pid_name.unwrap());
                 }
+                Ok(Err(error)) => {
+                    println!("Finished Err: {:?}: {}", pid_name.unwrap(), error);
+                }
                 Err(_) => {
                     println!("Finished Err: {:?}", pid_name.unwrap());
                 }
@@ -1,3 +1,7 @@
+use crate::error::KuberSimError;
+use crate::objects::priority_class::PriorityClass;
+use crate::simulation::metrics_sink::MetricsFormat;
+
 /////////////////////////////////////////// NetworkDelays //////////////////////////////////////////
 
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
@@ -37,17 +41,25 @@ pub struct NetworkDelays {
 }
 
 impl NetworkDelays {
-    pub fn prepare(&mut self) {
-        assert!(self.api2scheduler >= 0.0, "NetworkDelays.api2scheduler must be >= 0.0");
-        assert!(self.scheduler2api >= 0.0, "NetworkDelays.scheduler2api must be >= 0.0");
-        assert!(self.api2kubelet >= 0.0, "NetworkDelays.api2kubelet must be >= 0.0");
-        assert!(self.kubelet2api >= 0.0, "NetworkDelays.kubelet2api must be >= 0.0");
-        assert!(self.api2ca >= 0.0, "NetworkDelays.api2ca must be >= 0.0");
-        assert!(self.ca2api >= 0.0, "NetworkDelays.ca2api must be >= 0.0");
-        assert!(self.api2hpa >= 0.0, "NetworkDelays.api2hpa must be >= 0.0");
-        assert!(self.hpa2api >= 0.0, "NetworkDelays.hpa2api must be >= 0.0");
-        assert!(self.api2vpa >= 0.0, "NetworkDelays.api2vpa must be >= 0.0");
-        assert!(self.vpa2api >= 0.0, "NetworkDelays.vpa2api must be >= 0.0");
+    pub fn prepare(&mut self) -> Result<(), KuberSimError> {
+        sim_ensure!(
+            self.api2scheduler >= 0.0,
+            "NetworkDelays.api2scheduler",
+            "must be >= 0.0"
+        );
+        sim_ensure!(
+            self.scheduler2api >= 0.0,
+            "NetworkDelays.scheduler2api",
+            "must be >= 0.0"
+        );
+        sim_ensure!(self.api2kubelet >= 0.0, "NetworkDelays.api2kubelet", "must be >= 0.0");
+        sim_ensure!(self.kubelet2api >= 0.0, "NetworkDelays.kubelet2api", "must be >= 0.0");
+        sim_ensure!(self.api2ca >= 0.0, "NetworkDelays.api2ca", "must be >= 0.0");
+        sim_ensure!(self.ca2api >= 0.0, "NetworkDelays.ca2api", "must be >= 0.0");
+        sim_ensure!(self.api2hpa >= 0.0, "NetworkDelays.api2hpa", "must be >= 0.0");
+        sim_ensure!(self.hpa2api >= 0.0, "NetworkDelays.hpa2api", "must be >= 0.0");
+        sim_ensure!(self.api2vpa >= 0.0, "NetworkDelays.api2vpa", "must be >= 0.0");
+        sim_ensure!(self.vpa2api >= 0.0, "NetworkDelays.vpa2api", "must be >= 0.0");
 
         self.max_delay = self
             .api2scheduler
@@ -60,6 +72,7 @@ impl NetworkDelays {
             .max(self.hpa2api)
             .max(self.api2vpa)
             .max(self.vpa2api);
+        return Ok(());
     }
 }
 
@@ -68,14 +81,44 @@ impl NetworkDelays {
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ConfigMonitoring {
     pub self_update_period: f64,
+    /// Sampling period of per-node and per-NodeGroup utilization. Without it they are not recorded.
+    #[serde(default)]
+    pub node_utilization_period: Option<f64>,
+    /// Format of cluster-wide statistics file.
+    #[serde(default)]
+    pub format: MetricsFormat,
+    /// All statistics are written to files during simulation instead of being kept in memory until dump.
+    #[serde(default)]
+    pub streaming: bool,
+    /// Cluster-wide statistics are replaced by their min, max and mean over windows of this length.
+    #[serde(default)]
+    pub downsampling_window: Option<f64>,
 }
 
 impl ConfigMonitoring {
-    pub fn prepare(&mut self) {
-        assert!(
+    pub fn prepare(&mut self) -> Result<(), KuberSimError> {
+        sim_ensure!(
             self.self_update_period > 0.0,
-            "ConfigMonitoring.self_update_period must be > 0.0"
+            "ConfigMonitoring.self_update_period",
+            "must be > 0.0"
+        );
+        if let Some(period) = self.node_utilization_period {
+            sim_ensure!(
+                period > 0.0,
+                "ConfigMonitoring.node_utilization_period",
+                "must be > 0.0"
+            );
+        }
+        if let Some(window) = self.downsampling_window {
+            sim_ensure!(window > 0.0, "ConfigMonitoring.downsampling_window", "must be > 0.0");
+        }
+        // OpenMetricsSink keeps all samples until finish, which defeats streaming
+        sim_ensure!(
+            !self.streaming || self.format != MetricsFormat::OpenMetrics,
+            "ConfigMonitoring.format",
+            "OpenMetrics can not be used with streaming"
         );
+        return Ok(());
     }
 }
 
@@ -89,17 +132,43 @@ pub struct ConfigScheduler {
     pub cycle_max_scheduled: u64,
     #[serde(default)]
     pub cycle_max_to_try: u64,
+    /// Analog of percentageOfNodesToScore. Filtering stops after this percentage of all nodes is found feasible.
+    /// Zero is special value: adaptive percentage, as in kube-scheduler.
+    #[serde(default)]
+    pub percentage_of_nodes_to_score: u64,
+    /// Simulated time spent on scheduling. Without it scheduling is instantaneous.
+    #[serde(default)]
+    pub cost_model: Option<ConfigSchedulerCostModel>,
 }
 
+/// Each scheduling attempt takes pod_base_time plus the cost of every filter and score plugin call.
+/// Scheduler does not start new attempts while it is busy, and pods are bound when their attempt ends.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ConfigSchedulerCostModel {
+    #[serde(default)]
+    pub pod_base_time: f64,
+    #[serde(default)]
+    pub filter_call_time: f64,
+    #[serde(default)]
+    pub score_call_time: f64,
+}
+
+/// Clusters with fewer nodes are always filtered completely.
+const MIN_FEASIBLE_NODES_TO_FIND: usize = 100;
+/// Lower bound of adaptive percentage_of_nodes_to_score.
+const MIN_FEASIBLE_NODES_PERCENTAGE_TO_FIND: usize = 5;
+
 impl ConfigScheduler {
-    pub fn prepare(&mut self) {
-        assert!(
+    pub fn prepare(&mut self) -> Result<(), KuberSimError> {
+        sim_ensure!(
             self.self_update_period > 0.0,
-            "ConfigScheduler.self_update_period must be > 0.0"
+            "ConfigScheduler.self_update_period",
+            "must be > 0.0"
         );
-        assert!(
+        sim_ensure!(
             self.unschedulable_queue_backoff_delay >= 0.0,
-            "ConfigScheduler.unschedulable_queue_backoff_delay must be >= 0.0"
+            "ConfigScheduler.unschedulable_queue_backoff_delay",
+            "must be >= 0.0"
         );
 
         // Zero is special value
@@ -109,6 +178,46 @@ impl ConfigScheduler {
         if self.cycle_max_to_try == 0 {
             self.cycle_max_to_try = u64::MAX;
         }
+        sim_ensure!(
+            self.percentage_of_nodes_to_score <= 100,
+            "ConfigScheduler.percentage_of_nodes_to_score",
+            "must be <= 100"
+        );
+
+        if let Some(cost_model) = self.cost_model.as_ref() {
+            sim_ensure!(
+                cost_model.pod_base_time >= 0.0,
+                "ConfigSchedulerCostModel.pod_base_time",
+                "must be >= 0.0"
+            );
+            sim_ensure!(
+                cost_model.filter_call_time >= 0.0,
+                "ConfigSchedulerCostModel.filter_call_time",
+                "must be >= 0.0"
+            );
+            sim_ensure!(
+                cost_model.score_call_time >= 0.0,
+                "ConfigSchedulerCostModel.score_call_time",
+                "must be >= 0.0"
+            );
+        }
+        return Ok(());
+    }
+
+    /// How many feasible nodes are enough to stop filtering in cluster of num_all_nodes nodes.
+    /// Adaptive percentage is 50% for small clusters and decreases by 1% per 125 nodes down to 5%.
+    pub fn num_feasible_nodes_to_find(&self, num_all_nodes: usize) -> usize {
+        if num_all_nodes < MIN_FEASIBLE_NODES_TO_FIND || self.percentage_of_nodes_to_score >= 100 {
+            return num_all_nodes;
+        }
+
+        let mut percentage = self.percentage_of_nodes_to_score as usize;
+        if percentage == 0 {
+            percentage = 50usize
+                .saturating_sub(num_all_nodes / 125)
+                .max(MIN_FEASIBLE_NODES_PERCENTAGE_TO_FIND);
+        }
+        return (num_all_nodes * percentage / 100).max(MIN_FEASIBLE_NODES_TO_FIND);
     }
 }
 
@@ -165,23 +274,28 @@ impl Default for ConfigCA {
 }
 
 impl ConfigCA {
-    pub fn prepare(&mut self) {
-        assert!(
+    pub fn prepare(&mut self) -> Result<(), KuberSimError> {
+        sim_ensure!(
             self.self_update_period > 0.0,
-            "ConfigCA.self_update_period must be > 0.0"
+            "ConfigCA.self_update_period",
+            "must be > 0.0"
         );
-        assert!(
+        sim_ensure!(
             self.add_node_isp_delay >= 0.0,
-            "ConfigCA.add_node_isp_delay must be >= 0.0"
+            "ConfigCA.add_node_isp_delay",
+            "must be >= 0.0"
         );
-        assert!(
+        sim_ensure!(
             0.0 <= self.remove_node_cpu_fraction && self.remove_node_cpu_fraction <= 1.0,
-            "ConfigCA.remove_node_cpu_fraction must be in [0.0, 1.0]"
+            "ConfigCA.remove_node_cpu_fraction",
+            "must be in [0.0, 1.0]"
         );
-        assert!(
+        sim_ensure!(
             0.0 <= self.remove_node_memory_fraction && self.remove_node_memory_fraction <= 1.0,
-            "ConfigCA.remove_node_memory_fraction must be in [0.0, 1.0]"
+            "ConfigCA.remove_node_memory_fraction",
+            "must be in [0.0, 1.0]"
         );
+        return Ok(());
     }
 }
 
@@ -207,11 +321,13 @@ impl Default for ConfigHPA {
 }
 
 impl ConfigHPA {
-    pub fn prepare(&mut self) {
-        assert!(
+    pub fn prepare(&mut self) -> Result<(), KuberSimError> {
+        sim_ensure!(
             self.self_update_period > 0.0,
-            "ConfigHPA.self_update_period must be > 0.0"
+            "ConfigHPA.self_update_period",
+            "must be > 0.0"
         );
+        return Ok(());
     }
 }
 
@@ -282,26 +398,36 @@ impl Default for ConfigVPA {
 }
 
 impl ConfigVPA {
-    pub fn prepare(&mut self) {
-        assert!(
+    pub fn prepare(&mut self) -> Result<(), KuberSimError> {
+        sim_ensure!(
             self.self_update_period > 0.0,
-            "ConfigVPA.self_update_period must be > 0.0"
+            "ConfigVPA.self_update_period",
+            "must be > 0.0"
         );
-        assert!(self.reschedule_delay > 0.0, "ConfigVPA.reschedule_delay must be > 0.0");
-        assert!(
+        sim_ensure!(
+            self.reschedule_delay > 0.0,
+            "ConfigVPA.reschedule_delay",
+            "must be > 0.0"
+        );
+        sim_ensure!(
             self.histogram_update_frequency > 0.0,
-            "ConfigVPA.histogram_update_frequency must be > 0.0"
+            "ConfigVPA.histogram_update_frequency",
+            "must be > 0.0"
         );
-        assert!(self.gap_cpu >= 0.0, "ConfigVPA.gap_cpu must be > 0.0");
-        assert!(self.gap_memory >= 0.0, "ConfigVPA.gap_memory must be > 0.0");
-        assert!(
+        sim_ensure!(self.gap_cpu >= 0.0, "ConfigVPA.gap_cpu", "must be >= 0.0");
+        sim_ensure!(self.gap_memory >= 0.0, "ConfigVPA.gap_memory", "must be >= 0.0");
+        sim_ensure!(
             self.recommendation_margin_fraction >= 0.0,
-            "ConfigVPA.recommendation_margin_fraction must be > 0.0"
+            "ConfigVPA.recommendation_margin_fraction",
+            "must be >= 0.0"
         );
-        assert!(
-            self.limit_margin_fraction >= 0.0,
-            "ConfigVPA.limit_margin_fraction must be > 0.0"
+        // Limits of pods rescheduled by VPA must not be below their requests
+        sim_ensure!(
+            self.limit_margin_fraction >= 1.0,
+            "ConfigVPA.limit_margin_fraction",
+            "must be >= 1.0"
         );
+        return Ok(());
     }
 }
 
@@ -321,26 +447,79 @@ pub struct InitConfig {
     pub hpa: ConfigHPA,
     #[serde(default)]
     pub vpa: ConfigVPA,
+
+    /// Pods refer to them by PodSpec.priority_class_name.
+    #[serde(default)]
+    pub priority_classes: Vec<PriorityClass>,
 }
 
 impl InitConfig {
-    pub fn from_yaml(path: &String) -> Self {
+    pub fn from_yaml(path: &String) -> Result<Self, KuberSimError> {
         // Read file to string
-        let s: String = std::fs::read_to_string(path).expect(format!("Unable to read file: {0}", path).as_str());
+        let s: String = std::fs::read_to_string(path).map_err(|e| KuberSimError::from_io(path, e))?;
         // Build struct from string
-        let mut init_config: InitConfig = serde_yaml::from_str(s.as_str()).unwrap();
+        let mut init_config: InitConfig =
+            serde_yaml::from_str(s.as_str()).map_err(|e| KuberSimError::from_yaml(path, e))?;
 
         // Prepare init_config
-        init_config.prepare();
-        return init_config;
+        init_config.prepare().map_err(|e| e.with_file(path))?;
+        return Ok(init_config);
+    }
+
+    pub fn prepare(&mut self) -> Result<(), KuberSimError> {
+        self.network_delays.prepare()?;
+        self.monitoring.prepare()?;
+        self.scheduler.prepare()?;
+        self.ca.prepare()?;
+        self.hpa.prepare()?;
+        self.vpa.prepare()?;
+        PriorityClass::prepare_all(&self.priority_classes)?;
+        return Ok(());
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_config(percentage_of_nodes_to_score: u64) -> ConfigScheduler {
+        ConfigScheduler {
+            unschedulable_queue_backoff_delay: 1.0,
+            self_update_period: 1.0,
+            cycle_max_scheduled: 0,
+            cycle_max_to_try: 0,
+            percentage_of_nodes_to_score,
+            cost_model: None,
+        }
+    }
+
+    #[test]
+    fn test_monitoring_prepare() {
+        let mut config: ConfigMonitoring = serde_yaml::from_str("{self_update_period: 1, format: OpenMetrics}").unwrap();
+        assert!(config.prepare().is_ok());
+        config.streaming = true;
+        assert!(config.prepare().is_err());
+        config.format = MetricsFormat::Csv;
+        assert!(config.prepare().is_ok());
     }
 
-    pub fn prepare(&mut self) {
-        self.network_delays.prepare();
-        self.monitoring.prepare();
-        self.scheduler.prepare();
-        self.ca.prepare();
-        self.hpa.prepare();
-        self.vpa.prepare();
+    #[test]
+    fn test_num_feasible_nodes_to_find() {
+        // Small clusters are filtered completely
+        assert_eq!(make_config(10).num_feasible_nodes_to_find(99), 99);
+        assert_eq!(make_config(100).num_feasible_nodes_to_find(5000), 5000);
+
+        // Explicit percentage, but at least 100 nodes
+        assert_eq!(make_config(10).num_feasible_nodes_to_find(5000), 500);
+        assert_eq!(make_config(10).num_feasible_nodes_to_find(500), 100);
+
+        // Adaptive percentage
+        assert_eq!(make_config(0).num_feasible_nodes_to_find(200), 100);
+        assert_eq!(make_config(0).num_feasible_nodes_to_find(1000), 420);
+        assert_eq!(make_config(0).num_feasible_nodes_to_find(5000), 500);
+        assert_eq!(make_config(0).num_feasible_nodes_to_find(10000), 500);
     }
 }
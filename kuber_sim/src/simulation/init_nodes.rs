@@ -1,9 +1,11 @@
 use crate::api_server::events::*;
 use crate::common_imports::*;
+use crate::error::KuberSimError;
 use crate::kubelet::kubelet::Kubelet;
 use crate::objects::node_group::NodeGroup;
 use crate::simulation::init_config::InitConfig;
 use crate::simulation::monitoring::Monitoring;
+use crate::simulation::uid_allocator::UidAllocator;
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -17,27 +19,43 @@ pub struct InitNodes {
 }
 
 impl InitNodes {
-    pub fn from_yaml(path: &String) -> Self {
+    pub fn from_yaml(path: &String) -> Result<Self, KuberSimError> {
         // Read file to string
-        let s: String = std::fs::read_to_string(path).expect(format!("Unable to read file: {0}", path).as_str());
+        let s: String = std::fs::read_to_string(path).map_err(|e| KuberSimError::from_io(path, e))?;
         // Build struct from string
-        let mut cluster_state: InitNodes = serde_yaml::from_str(s.as_str()).unwrap();
+        let cluster_state: InitNodes =
+            serde_yaml::from_str(s.as_str()).map_err(|e| KuberSimError::from_yaml(path, e))?;
 
-        // Prepare cluster_state
-        cluster_state.prepare();
-        return cluster_state;
+        // Check cluster_state, group uids are assigned by simulation
+        cluster_state.check().map_err(|e| e.with_file(path))?;
+        return Ok(cluster_state);
     }
 
-    pub fn prepare(&mut self) {
-        // Prepare each node group for nodes
-        for node_group in self.nodes.iter_mut() {
-            node_group.prepare();
+    /// Assigns group uids from uids of simulation, so they do not depend on other simulations.
+    pub fn prepare(&mut self, uids: &mut UidAllocator) -> Result<(), KuberSimError> {
+        self.check()?;
+
+        for node_group in self.nodes.iter_mut().chain(self.ca_nodes.iter_mut()) {
+            node_group.group_uid = uids.next_node_group_uid();
+        }
+        return Ok(());
+    }
+
+    fn check(&self) -> Result<(), KuberSimError> {
+        // Check each node group for nodes
+        for (i, node_group) in self.nodes.iter().enumerate() {
+            node_group
+                .prepare()
+                .map_err(|e| e.with_parent(&format!("nodes[{}]", i)))?;
         }
 
-        // Prepare each node group for CA nodes
-        for node_group in self.ca_nodes.iter_mut() {
-            node_group.prepare();
+        // Check each node group for CA nodes
+        for (i, node_group) in self.ca_nodes.iter().enumerate() {
+            node_group
+                .prepare()
+                .map_err(|e| e.with_parent(&format!("ca_nodes[{}]", i)))?;
         }
+        return Ok(());
     }
 
     pub fn submit(
@@ -46,6 +64,7 @@ impl InitNodes {
         emitter: &dsc::SimulationContext,
         init_config: Rc<RefCell<InitConfig>>,
         monitoring: Rc<RefCell<Monitoring>>,
+        uids: Rc<RefCell<UidAllocator>>,
         api_sim_id: dsc::Id,
     ) {
         for node_group in self.nodes.iter() {
@@ -53,7 +72,7 @@ impl InitNodes {
                 // Get node template
                 let mut node = node_group.node.clone();
                 // Prepare node from template
-                node.prepare(node_group.group_uid);
+                node.prepare(node_group.group_uid, &mut uids.borrow_mut());
 
                 // Create unique kubelet name
                 let name = "kubelet_".to_owned() + &*node.metadata.uid.to_string();
@@ -1,6 +1,11 @@
 use crate::api_server::events::*;
 use crate::common_imports::*;
+use crate::error::{parse_field, split_field, KuberSimError};
 use crate::objects::pod_group::PodGroup;
+use crate::objects::priority_class::PriorityClass;
+use crate::scheduler::scheduler::DEFAULT_SCHEDULER_NAME;
+use crate::simulation::init_config::InitConfig;
+use crate::simulation::uid_allocator::UidAllocator;
 use std::collections::BTreeSet;
 use std::io::{BufRead, BufReader};
 
@@ -8,19 +13,23 @@ use std::io::{BufRead, BufReader};
 pub enum TraceEvent {
     AddPodGroup(PodGroup),
     RemovePodGroup(EventRemovePodGroup),
+    UpdateNode(EventUpdateNode),
 }
 
 impl std::str::FromStr for TraceEvent {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "<enum_index: u8>;<enum_payload: { PodGroup }>"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (enum_index, enum_inner) = s.split_once(';').unwrap();
+        let (enum_index, enum_inner) = split_field(s, ';', "TraceEvent")?;
         let enum_inner = enum_inner.trim();
 
         match enum_index {
-            "0" => Ok(Self::AddPodGroup(str::parse(enum_inner).unwrap())),
-            _ => panic!("Unexpected enum_index: '{:?}'", enum_index),
+            "0" => Ok(Self::AddPodGroup(str::parse(enum_inner)?)),
+            _ => Err(KuberSimError::invalid(
+                "TraceEvent",
+                format!("Unexpected enum_index '{}'", enum_index),
+            )),
         }
     }
 }
@@ -34,20 +43,35 @@ pub struct TraceEventWrapper {
 }
 
 impl std::str::FromStr for TraceEventWrapper {
-    type Err = ();
+    type Err = KuberSimError;
 
     /// Expects "<submit_time: f64>;<TraceEvent>"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (submit_time, event) = s.split_once(';').unwrap();
+        let (submit_time, event) = split_field(s, ';', "TraceEventWrapper.submit_time")?;
         let event = event.trim();
 
         Ok(Self {
-            submit_time: str::parse(submit_time).unwrap(),
-            event: str::parse(event).unwrap(),
+            submit_time: parse_field(submit_time, "TraceEventWrapper.submit_time")?,
+            event: str::parse(event)?,
         })
     }
 }
 
+impl TraceEventWrapper {
+    fn prepare(&mut self) -> Result<(), KuberSimError> {
+        sim_ensure!(
+            self.submit_time >= 0.0,
+            "TraceEventWrapper.submit_time",
+            "must be >= 0.0"
+        );
+
+        return match &mut self.event {
+            TraceEvent::AddPodGroup(pod_group) => pod_group.prepare(),
+            TraceEvent::RemovePodGroup(_) | TraceEvent::UpdateNode(_) => Ok(()),
+        };
+    }
+}
+
 impl PartialOrd for TraceEventWrapper {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -77,98 +101,95 @@ pub struct InitTrace {
 }
 
 impl InitTrace {
-    pub fn from_file(path: &String) -> Self {
+    pub fn from_file(path: &String) -> Result<Self, KuberSimError> {
         if path.ends_with(".yaml") {
             return InitTrace::from_yaml(path);
         } else if path.ends_with(".csv") {
             return InitTrace::from_csv(path);
         } else {
-            panic!("File format and extension must be '.yaml' or '.csv'")
+            return Err(KuberSimError::new("File format and extension must be '.yaml' or '.csv'").with_file(path));
         }
     }
 
-    pub fn from_yaml(path: &String) -> Self {
-        let fin = std::fs::File::open(path).unwrap();
-        let mut trace: InitTrace = serde_yaml::from_reader(fin).unwrap();
+    pub fn from_yaml(path: &String) -> Result<Self, KuberSimError> {
+        let fin = std::fs::File::open(path).map_err(|e| KuberSimError::from_io(path, e))?;
+        let mut trace: InitTrace = serde_yaml::from_reader(fin).map_err(|e| KuberSimError::from_yaml(path, e))?;
 
-        // Prepare trace
-        trace.prepare();
-        return trace;
+        // Check trace, group uids are assigned by simulation
+        trace.check().map_err(|e| e.with_file(path))?;
+        return Ok(trace);
     }
 
-    pub fn from_csv(path: &String) -> Self {
+    pub fn from_csv(path: &String) -> Result<Self, KuberSimError> {
         // Open file
-        let file = std::fs::File::open(path).unwrap();
+        let file = std::fs::File::open(path).map_err(|e| KuberSimError::from_io(path, e))?;
         // Create file reader
         let reader = BufReader::new(file);
         // Create empty trace
         let mut init_trace = InitTrace::default();
 
-        // Read trace
-        for line in reader.lines() {
-            let s = line.unwrap().trim().to_string();
+        // Read and check trace, so errors refer to their lines
+        for (i, line) in reader.lines().enumerate() {
+            let s = line.map_err(|e| KuberSimError::from_io(path, e).with_line(i + 1))?;
+            let s = s.trim();
             if s.is_empty() {
                 continue;
             }
 
-            init_trace.trace.push(str::parse::<TraceEventWrapper>(&s).unwrap());
+            let mut wrapper = str::parse::<TraceEventWrapper>(s).map_err(|e| e.with_line(i + 1).with_file(path))?;
+            wrapper.prepare().map_err(|e| e.with_line(i + 1).with_file(path))?;
+            init_trace.trace.push(wrapper);
         }
 
-        return init_trace;
+        return Ok(init_trace);
     }
 
-    pub fn prepare(&mut self) {
-        // Prepare trace events
+    /// Assigns group uids from uids of simulation, so they do not depend on other simulations.
+    pub fn prepare(&mut self, uids: &mut UidAllocator) -> Result<(), KuberSimError> {
+        self.check()?;
+
         for wrapper in self.trace.iter_mut() {
-            match &mut wrapper.event {
-                TraceEvent::AddPodGroup(pod_group) => {
-                    pod_group.prepare();
-
-                    // Check HPA invariants
-                    if pod_group.hpa_profile.is_some() {
-                        let profile = pod_group.hpa_profile.clone().unwrap();
-                        assert!(
-                            profile.min_size <= profile.max_size,
-                            "ConfigHPA.min_size must be <= ConfigHPA.max_size"
-                        );
-                        assert!(
-                            profile.scale_down_mean_cpu_fraction >= 0.0,
-                            "ConfigHPA.scale_down_mean_cpu_fraction must be >= 0"
-                        );
-                        assert!(
-                            profile.scale_down_mean_memory_fraction >= 0.0,
-                            "ConfigHPA.scale_down_mean_memory_fraction must be >= 0"
-                        );
-                        assert!(
-                            profile.scale_down_mean_cpu_fraction <= profile.scale_up_mean_cpu_fraction,
-                            "ConfigHPA.scale_down_mean_cpu_fraction must be <= ConfigHPA.scale_up_mean_cpu_fraction"
-                        );
-                        assert!(profile.scale_down_mean_memory_fraction <= profile.scale_up_mean_memory_fraction, "ConfigHPA.scale_down_mean_memory_fraction must be <= ConfigHPA.scale_up_mean_memory_fraction");
-                    }
+            if let TraceEvent::AddPodGroup(pod_group) = &mut wrapper.event {
+                pod_group.group_uid = uids.next_pod_group_uid();
+            }
+        }
+        return Ok(());
+    }
 
-                    // Check VPA invariants
-                    if pod_group.vpa_profile.is_some() {
-                        let profile = pod_group.vpa_profile.clone().unwrap();
-                        assert!(
-                            profile.min_allowed_cpu <= profile.max_allowed_cpu,
-                            "ConfigVPA.min_allowed_cpu must be <= ConfigVPA.max_allowed_cpu"
-                        );
-                        assert!(
-                            profile.min_allowed_memory <= profile.max_allowed_memory,
-                            "ConfigVPA.min_allowed_memory must be <= ConfigVPA.max_allowed_memory"
-                        );
-                        assert!(profile.min_allowed_cpu > 0, "ConfigVPA.min_allowed_cpu must be > 0");
-                        assert!(
-                            profile.min_allowed_memory > 0,
-                            "ConfigVPA.min_allowed_memory must be > 0"
-                        );
-                    }
-                }
-                TraceEvent::RemovePodGroup(_) => {
-                    // Do nothing
-                }
+    fn check(&mut self) -> Result<(), KuberSimError> {
+        // Check trace events
+        for (i, wrapper) in self.trace.iter_mut().enumerate() {
+            wrapper.prepare().map_err(|e| e.with_parent(&format!("trace[{}]", i)))?;
+        }
+        return Ok(());
+    }
+
+    /// Resolves pod priorities from PriorityClasses of init_config, as the Priority admission controller does,
+    /// and checks that pods refer to existing schedulers. Pods created from group templates during simulation
+    /// (e.g. by HPA and VPA) get the resolved priority, so API-Server never meets an unknown reference.
+    pub fn resolve_references(
+        &mut self,
+        init_config: &InitConfig,
+        scheduler_names: &[&str],
+    ) -> Result<(), KuberSimError> {
+        for (i, wrapper) in self.trace.iter_mut().enumerate() {
+            if let TraceEvent::AddPodGroup(pod_group) = &mut wrapper.event {
+                PriorityClass::resolve(&init_config.priority_classes, &mut pod_group.pod.spec)
+                    .map_err(|e| e.with_parent(&format!("trace[{}]", i)))?;
+
+                // Pod of unknown scheduler would never be scheduled nor reported as pending
+                let name = match pod_group.pod.spec.scheduler_name.as_str() {
+                    "" => DEFAULT_SCHEDULER_NAME,
+                    name => name,
+                };
+                sim_ensure!(
+                    scheduler_names.contains(&name),
+                    format!("trace[{}].PodSpec.scheduler_name", i),
+                    format!("No scheduler {}", name)
+                );
             }
         }
+        return Ok(());
     }
 
     pub fn submit(&self, emitter: &dsc::SimulationContext, api_sim_id: dsc::Id) {
@@ -180,7 +201,7 @@ impl InitTrace {
                     TraceEvent::RemovePodGroup(inner_event) => {
                         emitter.emit_ordered(inner_event, api_sim_id, delayed.submit_time);
                     }
-                    TraceEvent::AddPodGroup(_) => {
+                    TraceEvent::AddPodGroup(_) | TraceEvent::UpdateNode(_) => {
                         panic!("Unexpected TraceEvent.")
                     }
                 }
@@ -213,6 +234,9 @@ impl InitTrace {
                         });
                     }
                 }
+                TraceEvent::UpdateNode(inner_event) => {
+                    emitter.emit_ordered(inner_event.clone(), api_sim_id, wrapper.submit_time);
+                }
                 TraceEvent::RemovePodGroup(_) => {
                     panic!("Unexpected TraceEvent.");
                 }
@@ -224,7 +248,7 @@ impl InitTrace {
 
     pub fn find_matching_bracket(s: &str, start_index: usize) -> Option<usize> {
         let mut count = 0;
-        for (i, c) in s[start_index..].char_indices() {
+        for (i, c) in s.get(start_index..)?.char_indices() {
             match c {
                 '{' => count += 1,
                 '}' => {
@@ -264,5 +288,54 @@ mod tests {
         println!("{:?}", str::parse::<TraceEventWrapper>("1;0;5;30;{{};{10;10;20;20;1;{1;5;15};{};{};{}}};{1;2;3;4;5;6};{1;2;3;4}\n"));
         println!("{:?}", str::parse::<TraceEventWrapper>("1;0;5;;{{gpu:amd,env:test};{1;2;3;4;5;{0;5;15;30};{};{};{}}};{};{}\n"));
         println!("{:?}", str::parse::<TraceEventWrapper>("1;0;5;;{{};{10;10;20;20;1;{2;15;16;20;21;5;45};{gpu:amd,env:test};{gpu,amd,0,1;test,,1,0};{}}};{};{}\n"));
+        println!("{:?}", str::parse::<TraceEventWrapper>("1;0;5;;{{app:web};{10;10;20;20;1;{1;5;15};{};{};{};{{zone,app:cache};{}};{{};{10,host,app:web}}}};{};{}\n"));
+        println!("{:?}", str::parse::<TraceEventWrapper>("1;0;5;;{{app:web};{10;10;20;20;1;{1;5;15};{};{};{};{};{};{1,zone,0,app:web;2,host,1}}};{};{}\n"));
+        println!("{:?}", str::parse::<TraceEventWrapper>("1;0;5;;{{};{10;10;20;20;1;{1;5;15};{};{};{}}};{};{};3\n"));
+        println!("{:?}", str::parse::<TraceEventWrapper>("1;0;5;;{{};{10;10;20;20;production;{1;5;15};{};{};{}}};{};{}\n"));
+    }
+
+    #[test]
+    fn test_csv_errors() {
+        let path = std::env::temp_dir().join(format!("kuber_sim_{}_trace.csv", std::process::id())).to_string_lossy().to_string();
+        let check = |content: &str| {
+            std::fs::write(&path, content).unwrap();
+            let error = InitTrace::from_csv(&path).unwrap_err();
+            std::fs::remove_file(&path).unwrap();
+            (error.line, error.field.unwrap())
+        };
+
+        let good = "1;0;5;30;{{};{10;10;20;20;1;{0;5;15;30};{};{};{}}};{};{}\n";
+        // Parsing errors
+        assert_eq!(check(&(good.to_string() + "\n2;0;5;30;{{};{10;10;20;20;1;{0;x;15;30};{};{};{}}};{};{}\n")), (Some(3), "Constant.cpu".to_string()));
+        assert_eq!(check("1;0;5;30;{{};{10;10;20;20;1;{7;5;15;30};{};{};{}}};{};{}\n"), (Some(1), "LoadType".to_string()));
+        assert_eq!(check("1;0;5;30;{{};{10;10;20;20;1;{0;5;15;30};{};{};{}};{};{}\n"), (Some(1), "PodGroup.pod".to_string()));
+        // Validation errors
+        assert_eq!(check(&(good.to_string() + "2;0;5;30;{{};{0;10;20;20;1;{0;5;15;30};{};{};{}}};{};{}\n")), (Some(2), "PodSpec.request_cpu".to_string()));
+        assert_eq!(check("1;0;5;30;{{};{10;10;20;20;1;{0;5;15;30};{};{};{}}};{5;1;0.1;0.1;0.8;0.8};{}\n"), (Some(1), "HPAProfile.min_size".to_string()));
+        assert_eq!(check("1;0;5;30;{{};{10;10;20;20;1;{0;5;15;30};{};{};{};{};{};{0,zone,0}}};{};{}\n"), (Some(1), "TopologySpreadConstraint.max_skew".to_string()));
+
+        let error = InitTrace::from_file(&"missing.txt".to_string()).unwrap_err();
+        assert_eq!(error.file, Some("missing.txt".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_references() {
+        let mut init_trace = InitTrace { trace: vec![str::parse("1;0;5;30;{{};{10;10;20;20;1;{0;5;15;30};{};{};{}}};{};{}").unwrap()] };
+        let init_config: InitConfig = serde_yaml::from_str("{monitoring: {self_update_period: 1}, scheduler: {self_update_period: 1, unschedulable_queue_backoff_delay: 5}}").unwrap();
+        assert!(init_trace.resolve_references(&init_config, &[DEFAULT_SCHEDULER_NAME]).is_ok());
+        assert!(init_trace.resolve_references(&init_config, &["batch-scheduler"]).is_err());
+
+        if let TraceEvent::AddPodGroup(pod_group) = &mut init_trace.trace[0].event {
+            pod_group.pod.spec.scheduler_name = "batch-scheduler".to_string();
+        }
+        assert!(init_trace.resolve_references(&init_config, &[DEFAULT_SCHEDULER_NAME, "batch-scheduler"]).is_ok());
+        let error = init_trace.resolve_references(&init_config, &[DEFAULT_SCHEDULER_NAME]).unwrap_err();
+        assert_eq!(error.field, Some("trace[0].PodSpec.scheduler_name".to_string()));
+
+        if let TraceEvent::AddPodGroup(pod_group) = &mut init_trace.trace[0].event {
+            pod_group.pod.spec.priority_class_name = "production".to_string();
+        }
+        let error = init_trace.resolve_references(&init_config, &[DEFAULT_SCHEDULER_NAME, "batch-scheduler"]).unwrap_err();
+        assert_eq!(error.field, Some("trace[0].PodSpec.priority_class_name".to_string()));
     }
 }
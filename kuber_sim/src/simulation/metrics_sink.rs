@@ -0,0 +1,467 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Cluster-wide statistics at one moment. One row is produced by each Monitoring update.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct MetricsRow {
+    pub time: f64,
+    pub nodes: u64,
+    pub total_cpu: i64,
+    pub total_memory: i64,
+    pub scheduler_used_cpu: i64,
+    pub scheduler_used_memory: i64,
+    pub kubelets_used_cpu: i64,
+    pub kubelets_used_memory: i64,
+    pub pending: usize,
+    pub running: usize,
+    pub succeed: u64,
+    pub failed: u64,
+    pub evicted: u64,
+    pub removed: u64,
+    pub preempted: u64,
+    pub gang_wait_time: f64,
+    pub disruptions_blocked: u64,
+    pub scheduled: u64,
+    pub queue_wait_time: f64,
+    pub cost: f64,
+    pub power: f64,
+    pub energy: f64,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MetricKind {
+    Gauge,
+    /// Only grows during simulation.
+    Counter,
+}
+
+/// Description of one value in rows written to MetricsSink.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsColumn {
+    pub name: String,
+    pub kind: MetricKind,
+    /// Integer values are written without fractional part.
+    pub is_integer: bool,
+    pub help: String,
+}
+
+impl MetricsColumn {
+    pub fn format(&self, value: f64) -> String {
+        if self.is_integer {
+            return format!("{:?}", value as i64);
+        }
+        return format!("{:?}", value);
+    }
+}
+
+impl MetricsRow {
+    /// Name, kind, is_integer and help of each metric, in column order (time is not a metric).
+    #[rustfmt::skip]
+    const METRICS: [(&'static str, MetricKind, bool, &'static str); 21] = [
+        ("nodes", MetricKind::Gauge, true, "Nodes in cluster."),
+        ("total_cpu", MetricKind::Gauge, true, "Installed CPU of all nodes."),
+        ("total_memory", MetricKind::Gauge, true, "Installed memory of all nodes."),
+        ("scheduler_used_cpu", MetricKind::Gauge, true, "CPU requested by pods bound by schedulers."),
+        ("scheduler_used_memory", MetricKind::Gauge, true, "Memory requested by pods bound by schedulers."),
+        ("kubelets_used_cpu", MetricKind::Gauge, true, "CPU actually used by pods on kubelets."),
+        ("kubelets_used_memory", MetricKind::Gauge, true, "Memory actually used by pods on kubelets."),
+        ("pending", MetricKind::Gauge, true, "Pending pods."),
+        ("running", MetricKind::Gauge, true, "Running pods."),
+        ("succeed", MetricKind::Counter, true, "Succeeded pods."),
+        ("failed", MetricKind::Counter, true, "Failed pods."),
+        ("evicted", MetricKind::Counter, true, "Pod evictions."),
+        ("removed", MetricKind::Counter, true, "Removed pods."),
+        ("preempted", MetricKind::Counter, true, "Pod preemptions."),
+        ("gang_wait_time", MetricKind::Counter, false, "Time pods of gangs have spent waiting on permit."),
+        ("disruptions_blocked", MetricKind::Counter, true, "Disruptions not done because of PodDisruptionBudgets."),
+        ("scheduled", MetricKind::Counter, true, "Pods bound by schedulers."),
+        ("queue_wait_time", MetricKind::Counter, false, "Time bound pods have spent in ActiveQ before the attempt which bound them."),
+        ("cost", MetricKind::Counter, false, "Cost of nodes by cost_per_hour of their NodeGroups."),
+        ("power", MetricKind::Gauge, false, "Power of nodes by power_model of their NodeGroups, W."),
+        ("energy", MetricKind::Counter, false, "Energy consumed by nodes by power_model of their NodeGroups, Wh."),
+    ];
+
+    pub fn columns() -> Vec<MetricsColumn> {
+        return Self::METRICS
+            .iter()
+            .map(|&(name, kind, is_integer, help)| MetricsColumn {
+                name: name.to_string(),
+                kind,
+                is_integer,
+                help: help.to_string(),
+            })
+            .collect();
+    }
+
+    /// Values of metrics in the order of columns.
+    pub fn values(&self) -> Vec<f64> {
+        return vec![
+            self.nodes as f64,
+            self.total_cpu as f64,
+            self.total_memory as f64,
+            self.scheduler_used_cpu as f64,
+            self.scheduler_used_memory as f64,
+            self.kubelets_used_cpu as f64,
+            self.kubelets_used_memory as f64,
+            self.pending as f64,
+            self.running as f64,
+            self.succeed as f64,
+            self.failed as f64,
+            self.evicted as f64,
+            self.removed as f64,
+            self.preempted as f64,
+            self.gang_wait_time,
+            self.disruptions_blocked as f64,
+            self.scheduled as f64,
+            self.queue_wait_time,
+            self.cost,
+            self.power,
+            self.energy,
+        ];
+    }
+}
+
+/// Output format of cluster-wide statistics.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum MetricsFormat {
+    #[default]
+    Csv,
+    JsonLines,
+    /// Prometheus/OpenMetrics text exposition format, samples are timestamped with simulation time.
+    OpenMetrics,
+}
+
+impl MetricsFormat {
+    pub fn extension(&self) -> &'static str {
+        return match self {
+            MetricsFormat::Csv => "csv",
+            MetricsFormat::JsonLines => "jsonl",
+            MetricsFormat::OpenMetrics => "prom",
+        };
+    }
+
+    pub fn make_sink<W: Write + 'static>(&self, fout: W, columns: Vec<MetricsColumn>) -> Box<dyn MetricsSink> {
+        return match self {
+            MetricsFormat::Csv => Box::new(CsvSink::new(fout, columns)),
+            MetricsFormat::JsonLines => Box::new(JsonLinesSink::new(fout, columns)),
+            MetricsFormat::OpenMetrics => Box::new(OpenMetricsSink::new(fout, columns)),
+        };
+    }
+}
+
+/// Writes rows of values one by one, so rows may be streamed during simulation instead of kept in memory.
+pub trait MetricsSink {
+    /// Values are given in the order of columns the sink is created with.
+    fn write_row(&mut self, time: f64, values: &[f64]);
+
+    /// Writes everything what is left and flushes output. No rows are written after it.
+    fn finish(&mut self);
+}
+
+////////////////////////////////////////////// CsvSink /////////////////////////////////////////////
+
+pub struct CsvSink<W: Write> {
+    fout: W,
+    columns: Vec<MetricsColumn>,
+}
+
+impl<W: Write> CsvSink<W> {
+    pub fn new(mut fout: W, columns: Vec<MetricsColumn>) -> Self {
+        let names: Vec<&str> = columns.iter().map(|column| column.name.as_str()).collect();
+        write!(fout, "time,{}\n", names.join(",")).unwrap();
+        Self { fout, columns }
+    }
+}
+
+impl<W: Write> MetricsSink for CsvSink<W> {
+    fn write_row(&mut self, time: f64, values: &[f64]) {
+        write!(self.fout, "{:?}", time).unwrap();
+        for (column, &value) in self.columns.iter().zip(values.iter()) {
+            write!(self.fout, ",{}", column.format(value)).unwrap();
+        }
+        write!(self.fout, "\n").unwrap();
+    }
+
+    fn finish(&mut self) {
+        self.fout.flush().unwrap();
+    }
+}
+
+/////////////////////////////////////////// JsonLinesSink //////////////////////////////////////////
+
+pub struct JsonLinesSink<W: Write> {
+    fout: W,
+    columns: Vec<MetricsColumn>,
+}
+
+impl<W: Write> JsonLinesSink<W> {
+    pub fn new(fout: W, columns: Vec<MetricsColumn>) -> Self {
+        Self { fout, columns }
+    }
+}
+
+impl<W: Write> MetricsSink for JsonLinesSink<W> {
+    fn write_row(&mut self, time: f64, values: &[f64]) {
+        // Keys are written in column order. Non-finite values become null.
+        write!(self.fout, "{{\"time\":{}", serde_json::Value::from(time)).unwrap();
+        for (column, &value) in self.columns.iter().zip(values.iter()) {
+            let value = match column.is_integer {
+                true => serde_json::Value::from(value as i64),
+                false => serde_json::Value::from(value),
+            };
+            write!(
+                self.fout,
+                ",{}:{}",
+                serde_json::Value::from(column.name.as_str()),
+                value
+            )
+            .unwrap();
+        }
+        write!(self.fout, "}}\n").unwrap();
+    }
+
+    fn finish(&mut self) {
+        self.fout.flush().unwrap();
+    }
+}
+
+////////////////////////////////////////// OpenMetricsSink /////////////////////////////////////////
+
+// https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md
+/// Samples of one metric family must not be interleaved with other families,
+/// so samples are kept formatted until finish. That is why it can not be used in streaming mode.
+pub struct OpenMetricsSink<W: Write> {
+    fout: W,
+    columns: Vec<MetricsColumn>,
+    samples: BTreeMap<usize, String>, // BTreeMap<column_index, samples>
+}
+
+impl<W: Write> OpenMetricsSink<W> {
+    pub const PREFIX: &'static str = "kuber_sim_";
+
+    pub fn new(fout: W, columns: Vec<MetricsColumn>) -> Self {
+        Self {
+            fout,
+            columns,
+            samples: BTreeMap::new(),
+        }
+    }
+}
+
+impl<W: Write> MetricsSink for OpenMetricsSink<W> {
+    fn write_row(&mut self, time: f64, values: &[f64]) {
+        for (i, (column, &value)) in self.columns.iter().zip(values.iter()).enumerate() {
+            let suffix = if column.kind == MetricKind::Counter {
+                "_total"
+            } else {
+                ""
+            };
+            let samples = self.samples.entry(i).or_default();
+            samples.push_str(&format!(
+                "{}{}{} {} {:?}\n",
+                Self::PREFIX,
+                column.name,
+                suffix,
+                column.format(value),
+                time
+            ));
+        }
+    }
+
+    fn finish(&mut self) {
+        for (i, column) in self.columns.iter().enumerate() {
+            let kind = if column.kind == MetricKind::Counter {
+                "counter"
+            } else {
+                "gauge"
+            };
+            write!(self.fout, "# TYPE {}{} {}\n", Self::PREFIX, column.name, kind).unwrap();
+            write!(self.fout, "# HELP {}{} {}\n", Self::PREFIX, column.name, column.help).unwrap();
+            if let Some(samples) = self.samples.remove(&i) {
+                self.fout.write_all(samples.as_bytes()).unwrap();
+            }
+        }
+        write!(self.fout, "# EOF\n").unwrap();
+        self.fout.flush().unwrap();
+    }
+}
+
+///////////////////////////////////////// DownsamplingSink /////////////////////////////////////////
+
+/// Replaces rows of each window (k * window, (k + 1) * window] with one row at time (k + 1) * window.
+/// Each column is replaced by its min, max and mean over the window.
+pub struct DownsamplingSink {
+    inner: Box<dyn MetricsSink>,
+    window: f64,
+
+    window_index: u64,
+    count: u64,
+    min: Vec<f64>,
+    max: Vec<f64>,
+    sum: Vec<f64>,
+}
+
+impl DownsamplingSink {
+    /// Inner sink must be created with columns returned by DownsamplingSink::columns.
+    pub fn new(inner: Box<dyn MetricsSink>, window: f64, column_count: usize) -> Self {
+        Self {
+            inner,
+            window,
+            window_index: 0,
+            count: 0,
+            min: vec![f64::INFINITY; column_count],
+            max: vec![f64::NEG_INFINITY; column_count],
+            sum: vec![0.0; column_count],
+        }
+    }
+
+    /// Columns <name>_min, <name>_max and <name>_mean for each column. All of them are gauges.
+    pub fn columns(columns: &Vec<MetricsColumn>) -> Vec<MetricsColumn> {
+        let mut result: Vec<MetricsColumn> = Vec::new();
+        for column in columns.iter() {
+            for (stat, is_integer) in [("min", column.is_integer), ("max", column.is_integer), ("mean", false)] {
+                result.push(MetricsColumn {
+                    name: format!("{}_{}", column.name, stat),
+                    kind: MetricKind::Gauge,
+                    is_integer,
+                    help: format!("{} ({} over window)", column.help, stat),
+                });
+            }
+        }
+        return result;
+    }
+
+    fn flush_window(&mut self) {
+        if self.count == 0 {
+            return;
+        }
+
+        let mut values: Vec<f64> = Vec::new();
+        for i in 0..self.sum.len() {
+            values.push(self.min[i]);
+            values.push(self.max[i]);
+            values.push(self.sum[i] / self.count as f64);
+        }
+        self.inner.write_row(self.window_index as f64 * self.window, &values);
+
+        self.count = 0;
+        self.min.fill(f64::INFINITY);
+        self.max.fill(f64::NEG_INFINITY);
+        self.sum.fill(0.0);
+    }
+}
+
+impl MetricsSink for DownsamplingSink {
+    fn write_row(&mut self, time: f64, values: &[f64]) {
+        let window_index = (time / self.window).ceil() as u64;
+        if window_index != self.window_index {
+            self.flush_window();
+            self.window_index = window_index;
+        }
+
+        self.count += 1;
+        for (i, &value) in values.iter().enumerate() {
+            self.min[i] = self.min[i].min(value);
+            self.max[i] = self.max[i].max(value);
+            self.sum[i] += value;
+        }
+    }
+
+    fn finish(&mut self) {
+        self.flush_window();
+        self.inner.finish();
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Output shared with the test after sink is boxed.
+    #[derive(Clone, Default)]
+    struct SharedOut(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedOut {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn write_rows(format: MetricsFormat) -> String {
+        let out = SharedOut::default();
+        let mut sink = format.make_sink(out.clone(), MetricsRow::columns());
+        let row = MetricsRow { time: 1.0, nodes: 2, succeed: 3, queue_wait_time: 0.5, ..Default::default() };
+        sink.write_row(row.time, &row.values());
+        let row = MetricsRow { time: 2.0, nodes: 1, succeed: 4, queue_wait_time: 1.5, ..Default::default() };
+        sink.write_row(row.time, &row.values());
+        sink.finish();
+        let result = String::from_utf8(out.0.borrow().clone()).unwrap();
+        return result;
+    }
+
+    #[test]
+    fn test_csv_sink() {
+        let out = write_rows(MetricsFormat::Csv);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "time,nodes,total_cpu,total_memory,scheduler_used_cpu,scheduler_used_memory,kubelets_used_cpu,kubelets_used_memory,pending,running,succeed,failed,evicted,removed,preempted,gang_wait_time,disruptions_blocked,scheduled,queue_wait_time,cost,power,energy");
+        assert_eq!(lines[1], "1.0,2,0,0,0,0,0,0,0,0,3,0,0,0,0,0.0,0,0,0.5,0.0,0.0,0.0");
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn test_json_lines_sink() {
+        let out = write_rows(MetricsFormat::JsonLines);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("{\"time\":2.0,\"nodes\":1,\"total_cpu\":0,"));
+        let row: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(row["succeed"], 4);
+        assert_eq!(row["queue_wait_time"], 1.5);
+    }
+
+    #[test]
+    fn test_open_metrics_sink() {
+        let out = write_rows(MetricsFormat::OpenMetrics);
+        let lines: Vec<&str> = out.lines().collect();
+
+        // Families are not interleaved
+        let nodes = lines.iter().position(|line| *line == "# TYPE kuber_sim_nodes gauge").unwrap();
+        assert_eq!(lines[nodes + 1], "# HELP kuber_sim_nodes Nodes in cluster.");
+        assert_eq!(lines[nodes + 2], "kuber_sim_nodes 2 1.0");
+        assert_eq!(lines[nodes + 3], "kuber_sim_nodes 1 2.0");
+        assert!(lines.contains(&"# TYPE kuber_sim_succeed counter"));
+        assert!(lines.contains(&"kuber_sim_succeed_total 4 2.0"));
+        assert_eq!(*lines.last().unwrap(), "# EOF");
+    }
+
+    #[test]
+    fn test_downsampling_sink() {
+        let columns = vec![MetricsColumn { name: "pods".to_string(), kind: MetricKind::Counter, is_integer: true, help: String::new() }];
+        let out = SharedOut::default();
+        let inner = MetricsFormat::Csv.make_sink(out.clone(), DownsamplingSink::columns(&columns));
+        let mut sink = DownsamplingSink::new(inner, 10.0, columns.len());
+
+        // Windows (0, 10], (10, 20], (30, 40]
+        for (time, value) in [(0.5, 1.0), (5.0, 4.0), (10.0, 2.0), (10.5, 3.0), (35.0, 7.0), (36.0, 8.0)] {
+            sink.write_row(time, &[value]);
+        }
+        sink.finish();
+
+        let out = String::from_utf8(out.0.borrow().clone()).unwrap();
+        assert_eq!(out.lines().collect::<Vec<&str>>(), vec![
+            "time,pods_min,pods_max,pods_mean",
+            "10.0,1,4,2.3333333333333335",
+            "20.0,3,3,3.0",
+            "40.0,7,8,7.5",
+        ]);
+    }
+}
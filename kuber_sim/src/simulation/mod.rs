@@ -1,7 +1,16 @@
+pub mod decision_log;
 pub mod experiment;
 pub mod init_config;
 pub mod init_nodes;
 pub mod init_trace;
+pub mod metrics_sink;
 pub mod monitoring;
+pub mod node_cost;
+pub mod node_energy;
+pub mod node_utilization;
 pub mod pipeline_config;
+pub mod pod_group_metrics;
+pub mod pod_lifecycle;
+pub mod run_summary;
 pub mod simulation;
+pub mod uid_allocator;
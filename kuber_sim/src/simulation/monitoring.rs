@@ -1,11 +1,34 @@
 use super::super::common_imports::*;
 use crate::api_server::events::*;
 use crate::objects::node::Node;
+use crate::objects::pod::PodPhase;
+use crate::objects::power_model::PowerModel;
+use crate::scheduler::features::taints_tolerations::Taint;
+use crate::simulation::decision_log::{DecisionLog, SchedulingDecision};
 use crate::simulation::init_config::InitConfig;
+use crate::simulation::metrics_sink::{DownsamplingSink, MetricsRow, MetricsSink};
+use crate::simulation::node_cost::NodeCost;
+use crate::simulation::node_energy::NodeEnergy;
+use crate::simulation::node_utilization::NodeUtilization;
+use crate::simulation::pod_group_metrics::PodGroupMetrics;
+use crate::simulation::pod_lifecycle::PodLifecycles;
+use crate::simulation::run_summary::{RunSummary, RunSummaryCollector, STRANDED_FREE_FRACTION};
 use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::rc::Rc;
 
+/// Files all statistics are streamed to in streaming mode. All of them have the same counter.
+struct MonitoringStream {
+    counter: usize,
+    sink: Box<dyn MetricsSink>,
+    pods: BufWriter<File>,
+    pod_groups: BufWriter<File>,
+    /// Only if node utilization is sampled.
+    nodes: Option<(BufWriter<File>, BufWriter<File>)>, // Option<(nodes, node_groups)>
+}
+
 pub struct Monitoring {
     pub ctx: dsc::SimulationContext,
     pub self_update_enabled: bool,
@@ -13,43 +36,43 @@ pub struct Monitoring {
     pub print_enabled: bool,
     pub init_config: Rc<RefCell<InitConfig>>,
 
-    time_record: Vec<f64>,
-
     node_counter: u64,
 
-    node_counter_record: Vec<u64>,
-
     total_installed_cpu: i64,
     total_installed_memory: i64,
 
-    total_installed_cpu_record: Vec<i64>,
-    total_installed_memory_record: Vec<i64>,
-
     scheduler_used_cpu: i64,
     scheduler_used_memory: i64,
     kubelets_used_cpu: i64,
     kubelets_used_memory: i64,
 
-    scheduler_used_cpu_record: Vec<i64>,
-    scheduler_used_memory_record: Vec<i64>,
-    kubelets_used_cpu_record: Vec<i64>,
-    kubelets_used_memory_record: Vec<i64>,
-
     pending_pod_counter: usize,
+    pending_pod_counter_by_scheduler: HashMap<String, usize>, // HashMap<scheduler_name, pending_pod_count>
     running_pod_counter: usize,
     succeed_pod_counter: u64,
     failed_pod_counter: u64,
     evicted_pod_counter: u64,
     removed_pod_counter: u64,
     preempted_pod_counter: u64,
-
-    pending_pod_counter_record: Vec<usize>,
-    running_pod_counter_record: Vec<usize>,
-    succeed_pod_counter_record: Vec<u64>,
-    failed_pod_counter_record: Vec<u64>,
-    evicted_pod_counter_record: Vec<u64>,
-    removed_pod_counter_record: Vec<u64>,
-    preempted_pod_counter_record: Vec<u64>,
+    /// Total time pods of gangs have spent waiting on permit barrier.
+    gang_wait_time: f64,
+    /// Disruptions of pods which were not done because of PodDisruptionBudgets.
+    disruption_blocked_counter: u64,
+    /// Pods bound by schedulers and total time they have spent pending before binding.
+    scheduled_pod_counter: u64,
+    queue_wait_time: f64,
+
+    /// Rows of statistics since the last clear, if they are not streamed.
+    records: Vec<MetricsRow>,
+    stream: Option<MonitoringStream>,
+
+    decision_log: DecisionLog,
+    pod_lifecycles: PodLifecycles,
+    node_utilization: NodeUtilization,
+    pod_group_metrics: PodGroupMetrics,
+    node_cost: NodeCost,
+    node_energy: NodeEnergy,
+    run_summary: RunSummaryCollector,
 
     out_path_prefix: String,
 }
@@ -64,34 +87,33 @@ impl Monitoring {
             init_config,
             total_installed_cpu: 0,
             total_installed_memory: 0,
-            total_installed_cpu_record: vec![],
-            total_installed_memory_record: vec![],
             scheduler_used_cpu: 0,
             scheduler_used_memory: 0,
             kubelets_used_cpu: 0,
             kubelets_used_memory: 0,
-            scheduler_used_cpu_record: vec![],
-            scheduler_used_memory_record: vec![],
-            kubelets_used_cpu_record: vec![],
             succeed_pod_counter: 0,
             pending_pod_counter: 0,
+            pending_pod_counter_by_scheduler: HashMap::new(),
             preempted_pod_counter: 0,
-            pending_pod_counter_record: vec![],
-            running_pod_counter_record: vec![],
-            succeed_pod_counter_record: vec![],
-            failed_pod_counter_record: vec![],
-            evicted_pod_counter_record: vec![],
-            removed_pod_counter_record: vec![],
+            gang_wait_time: 0.0,
+            disruption_blocked_counter: 0,
+            scheduled_pod_counter: 0,
+            queue_wait_time: 0.0,
             failed_pod_counter: 0,
             running_pod_counter: 0,
             evicted_pod_counter: 0,
             removed_pod_counter: 0,
             node_counter: 0,
             out_path_prefix: out_path_prefix.clone(),
-            node_counter_record: vec![],
-            kubelets_used_memory_record: vec![],
-            preempted_pod_counter_record: vec![],
-            time_record: vec![],
+            records: vec![],
+            stream: None,
+            decision_log: DecisionLog::new(),
+            pod_lifecycles: PodLifecycles::new(),
+            node_utilization: NodeUtilization::new(),
+            pod_group_metrics: PodGroupMetrics::new(),
+            node_cost: NodeCost::new(),
+            node_energy: NodeEnergy::new(),
+            run_summary: RunSummaryCollector::new(),
         }
     }
 
@@ -102,6 +124,16 @@ impl Monitoring {
                 EventSelfUpdate {},
                 self.init_config.borrow().monitoring.self_update_period,
             );
+
+            // Pods are not kept after they finish in streaming mode
+            if self.init_config.borrow().monitoring.streaming {
+                self.pod_lifecycles.enable_streaming();
+            }
+
+            // Per-node utilization is sampled independently of cluster statistics
+            if let Some(period) = self.init_config.borrow().monitoring.node_utilization_period {
+                self.ctx.emit_self(EventSampleNodeUtilization {}, period);
+            }
         }
     }
 
@@ -125,34 +157,29 @@ impl Monitoring {
         self.print_enabled = false;
     }
 
-    pub fn clear_records(&mut self) {
-        self.time_record.clear();
-
-        self.node_counter_record.clear();
-
-        self.total_installed_cpu_record.clear();
-        self.total_installed_memory_record.clear();
+    /// Scheduling decisions are written to <out_path_prefix>_decisions.jsonl with top_k best scored nodes.
+    pub fn enable_decision_log(&mut self, top_k: usize) {
+        self.decision_log
+            .enable(&(self.out_path_prefix.clone() + "_decisions.jsonl"), top_k);
+    }
 
-        self.scheduler_used_cpu_record.clear();
-        self.scheduler_used_memory_record.clear();
-        self.kubelets_used_cpu_record.clear();
-        self.kubelets_used_memory_record.clear();
+    pub fn disable_decision_log(&mut self) {
+        self.decision_log.disable();
+    }
 
-        self.pending_pod_counter_record.clear();
-        self.running_pod_counter_record.clear();
-        self.succeed_pod_counter_record.clear();
-        self.failed_pod_counter_record.clear();
-        self.evicted_pod_counter_record.clear();
-        self.removed_pod_counter_record.clear();
-        self.preempted_pod_counter_record.clear();
+    pub fn clear_records(&mut self) {
+        self.records.clear();
+        self.node_utilization.clear_records();
+        self.pod_group_metrics.clear_records();
     }
 
     ////////////////////////////////////////////////////////////////////////////////////////////////
 
     #[inline(always)]
-    pub fn scheduler_on_node_consume(&mut self, cpu: i64, memory: i64) {
+    pub fn scheduler_on_node_consume(&mut self, node_uid: u64, cpu: i64, memory: i64) {
         self.scheduler_used_cpu += cpu;
         self.scheduler_used_memory += memory;
+        self.node_utilization.on_pod_requested(node_uid, cpu, memory, 1);
 
         if self.dynamic_update_enabled {
             self.print_statistics();
@@ -160,12 +187,13 @@ impl Monitoring {
     }
 
     #[inline(always)]
-    pub fn scheduler_on_node_restore(&mut self, cpu: i64, memory: i64) {
+    pub fn scheduler_on_node_restore(&mut self, node_uid: u64, cpu: i64, memory: i64) {
         assert!(self.scheduler_used_cpu >= cpu);
         assert!(self.scheduler_used_memory >= memory);
 
         self.scheduler_used_cpu -= cpu;
         self.scheduler_used_memory -= memory;
+        self.node_utilization.on_pod_requested(node_uid, cpu, memory, -1);
 
         if self.dynamic_update_enabled {
             self.print_statistics();
@@ -180,6 +208,10 @@ impl Monitoring {
         self.total_installed_memory += node.spec.installed_memory;
 
         self.node_counter += 1;
+        self.node_utilization.on_node_added(node);
+        self.node_cost
+            .on_node_requested(node.metadata.uid, node.metadata.group_uid, self.ctx.time());
+        self.node_energy.on_node_added(node, self.ctx.time());
 
         if self.dynamic_update_enabled {
             self.print_statistics();
@@ -187,7 +219,12 @@ impl Monitoring {
     }
 
     pub fn scheduler_on_node_removed(&mut self, node: &Node) {
+        // Node is forgotten first, so releasing all its resources at once is not counted as a pod
+        self.node_utilization.on_node_removed(node.metadata.uid);
+        self.node_cost.on_node_removed(node.metadata.uid, self.ctx.time());
+        self.node_energy.on_node_removed(node.metadata.uid, self.ctx.time());
         self.scheduler_on_node_restore(
+            node.metadata.uid,
             node.spec.installed_cpu - node.spec.available_cpu,
             node.spec.installed_memory - node.spec.available_memory,
         );
@@ -206,15 +243,43 @@ impl Monitoring {
         }
     }
 
+    /// Nodes of group cost cost_per_hour from their request until removal.
+    pub fn set_node_group_cost(&mut self, group_uid: u64, cost_per_hour: f64) {
+        self.node_cost.set_group_cost(group_uid, cost_per_hour);
+    }
+
+    /// Nodes of group consume power by model while they are in cluster.
+    pub fn set_node_group_power_model(&mut self, group_uid: u64, model: &PowerModel) {
+        self.node_energy.set_group_power_model(group_uid, model.clone());
+    }
+
+    /// CA requested node which is added to cluster after ISP delay. It is billed since now.
+    pub fn ca_on_node_requested(&mut self, node: &Node) {
+        self.node_cost
+            .on_node_requested(node.metadata.uid, node.metadata.group_uid, self.ctx.time());
+    }
+
+    pub fn scheduler_on_node_taints_changed(&mut self, node_uid: u64, taints: &Vec<Taint>) {
+        self.node_utilization.on_taints_changed(node_uid, taints);
+    }
+
     #[inline(always)]
-    pub fn scheduler_update_pending_pod_count(&mut self, count: usize) {
-        self.pending_pod_counter = count;
+    pub fn scheduler_update_pending_pod_count(&mut self, scheduler_name: &String, count: usize) {
+        // Each scheduler has its own pending pods
+        self.pending_pod_counter_by_scheduler
+            .insert(scheduler_name.clone(), count);
+        self.pending_pod_counter = self.pending_pod_counter_by_scheduler.values().sum();
 
         if self.dynamic_update_enabled {
             self.print_statistics();
         }
     }
 
+    /// Called at the end of scheduling cycle with pending pod count of each group.
+    pub fn scheduler_update_pending_pods_by_group(&mut self, scheduler_name: &String, counts: BTreeMap<u64, u64>) {
+        self.pod_group_metrics.on_pending_pods(scheduler_name, counts);
+    }
+
     #[inline(always)]
     pub fn scheduler_update_running_pod_count(&mut self, count: usize) {
         self.running_pod_counter = count;
@@ -225,8 +290,11 @@ impl Monitoring {
     }
 
     #[inline(always)]
-    pub fn scheduler_on_pod_succeed(&mut self) {
+    pub fn scheduler_on_pod_succeed(&mut self, pod_uid: u64) {
         self.succeed_pod_counter += 1;
+        self.pod_lifecycles
+            .on_finished(pod_uid, PodPhase::Succeeded, self.ctx.time());
+        self.decision_log.forget(pod_uid);
 
         if self.dynamic_update_enabled {
             self.print_statistics();
@@ -234,8 +302,11 @@ impl Monitoring {
     }
 
     #[inline(always)]
-    pub fn scheduler_on_pod_failed(&mut self) {
+    pub fn scheduler_on_pod_failed(&mut self, pod_uid: u64) {
         self.failed_pod_counter += 1;
+        self.pod_lifecycles
+            .on_finished(pod_uid, PodPhase::Failed, self.ctx.time());
+        self.decision_log.forget(pod_uid);
 
         if self.dynamic_update_enabled {
             self.print_statistics();
@@ -243,8 +314,11 @@ impl Monitoring {
     }
 
     #[inline(always)]
-    pub fn scheduler_on_pod_removed(&mut self) {
+    pub fn scheduler_on_pod_removed(&mut self, pod_uid: u64) {
         self.removed_pod_counter += 1;
+        self.pod_lifecycles
+            .on_finished(pod_uid, PodPhase::Removed, self.ctx.time());
+        self.decision_log.forget(pod_uid);
 
         if self.dynamic_update_enabled {
             self.print_statistics();
@@ -252,8 +326,9 @@ impl Monitoring {
     }
 
     #[inline(always)]
-    pub fn scheduler_on_pod_evicted(&mut self) {
+    pub fn scheduler_on_pod_evicted(&mut self, pod_uid: u64) {
         self.evicted_pod_counter += 1;
+        self.pod_lifecycles.on_evicted(pod_uid);
 
         if self.dynamic_update_enabled {
             self.print_statistics();
@@ -261,30 +336,134 @@ impl Monitoring {
     }
 
     #[inline(always)]
-    pub fn scheduler_on_pod_preempted(&mut self) {
+    pub fn scheduler_on_pod_preempted(&mut self, pod_uid: u64) {
         self.preempted_pod_counter += 1;
+        self.pod_lifecycles.on_preempted(pod_uid);
 
         if self.dynamic_update_enabled {
             self.print_statistics();
         }
     }
 
+    #[inline(always)]
+    pub fn scheduler_on_gang_wait_finished(&mut self, wait_time: f64) {
+        self.gang_wait_time += wait_time;
+
+        if self.dynamic_update_enabled {
+            self.print_statistics();
+        }
+    }
+
+    /// Called when pod is bound. Queue wait time is measured from the last moment pod entered ActiveQ,
+    /// time in BackoffQ and UnschedulableQ is not counted (it is a part of pod scheduling latency).
+    #[inline(always)]
+    pub fn scheduler_on_pod_scheduled(&mut self, pod_uid: u64, node_uid: u64, time: f64, queue_wait_time: f64) {
+        self.scheduled_pod_counter += 1;
+        self.queue_wait_time += queue_wait_time;
+        self.pod_lifecycles.on_bound(pod_uid, node_uid, time);
+
+        if self.dynamic_update_enabled {
+            self.print_statistics();
+        }
+    }
+
+    /// Called when scheduler pops pod from ActiveQ to try to schedule it.
+    pub fn scheduler_on_pod_attempt(&mut self, pod_uid: u64, time: f64) {
+        self.pod_lifecycles.on_attempt(pod_uid, time);
+    }
+
     ////////////////////////////////////////////////////////////////////////////////////////////////
 
+    pub fn api_on_pod_submitted(&mut self, pod_uid: u64, group_uid: u64) {
+        self.pod_lifecycles.on_submitted(pod_uid, group_uid, self.ctx.time());
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Called by CA or VPA when PodDisruptionBudget starts to block disruption of node or pod.
     #[inline(always)]
-    pub fn kubelet_on_pod_placed(&mut self, cpu: i64, memory: i64) {
+    pub fn on_disruption_blocked(&mut self) {
+        self.disruption_blocked_counter += 1;
+
+        if self.dynamic_update_enabled {
+            self.print_statistics();
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Called by HPA for each managed group when it makes decisions.
+    pub fn hpa_on_group_metrics(
+        &mut self,
+        group_uid: u64,
+        replicas: u64,
+        mean_cpu: Option<f64>,
+        mean_memory: Option<f64>,
+    ) {
+        self.pod_group_metrics
+            .on_hpa_metrics(group_uid, replicas, mean_cpu, mean_memory);
+    }
+
+    /// Called by VPA when it recreates pod of group with recommended resources.
+    pub fn vpa_on_recommendation(
+        &mut self,
+        group_uid: u64,
+        request_cpu: i64,
+        request_memory: i64,
+        limit_cpu: i64,
+        limit_memory: i64,
+    ) {
+        self.pod_group_metrics
+            .on_vpa_recommendation(group_uid, request_cpu, request_memory, limit_cpu, limit_memory);
+    }
+
+    /// Called by HPA and VPA when they stop managing group.
+    pub fn autoscaler_on_group_removed(&mut self, group_uid: u64) {
+        self.pod_group_metrics.on_group_removed(group_uid);
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+
+    /// Number of best scored nodes to describe in decision. None if decision log is disabled.
+    pub fn scheduler_decision_top_k(&self) -> Option<usize> {
+        if !self.decision_log.is_enabled() {
+            return None;
+        }
+        return Some(self.decision_log.top_k());
+    }
+
+    pub fn scheduler_on_decision(&mut self, decision: &SchedulingDecision) {
+        self.decision_log.record(decision);
+    }
+
+    pub fn last_failure_reason(&self, pod_uid: u64) -> Option<String> {
+        return self.decision_log.last_failure_reason(pod_uid);
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////////////////////
+
+    #[inline(always)]
+    pub fn kubelet_on_pod_placed(&mut self, node_uid: u64, cpu: i64, memory: i64) {
         self.kubelets_used_cpu += cpu;
         self.kubelets_used_memory += memory;
+        self.node_utilization.on_pod_used(node_uid, cpu, memory, 1);
+        self.node_energy.on_cpu_used(node_uid, cpu, 1, self.ctx.time());
 
         if self.dynamic_update_enabled {
             self.print_statistics();
         }
     }
 
+    pub fn kubelet_on_pod_started(&mut self, pod_uid: u64) {
+        self.pod_lifecycles.on_started(pod_uid, self.ctx.time());
+    }
+
     #[inline(always)]
-    pub fn kubelet_on_pod_unplaced(&mut self, cpu: i64, memory: i64) {
+    pub fn kubelet_on_pod_unplaced(&mut self, node_uid: u64, cpu: i64, memory: i64) {
         self.kubelets_used_cpu -= cpu;
         self.kubelets_used_memory -= memory;
+        self.node_utilization.on_pod_used(node_uid, cpu, memory, -1);
+        self.node_energy.on_cpu_used(node_uid, cpu, -1, self.ctx.time());
 
         if self.dynamic_update_enabled {
             self.print_statistics();
@@ -293,30 +472,53 @@ impl Monitoring {
 
     ////////////////////////////////////////////////////////////////////////////////////////////////
 
-    pub fn print_statistics(&mut self) {
-        self.time_record.push(self.ctx.time());
-
-        self.node_counter_record.push(self.node_counter);
-
-        self.total_installed_cpu_record.push(self.total_installed_cpu);
-        self.total_installed_memory_record.push(self.total_installed_memory);
+    pub fn current_row(&self) -> MetricsRow {
+        return MetricsRow {
+            time: self.ctx.time(),
+            nodes: self.node_counter,
+            total_cpu: self.total_installed_cpu,
+            total_memory: self.total_installed_memory,
+            scheduler_used_cpu: self.scheduler_used_cpu,
+            scheduler_used_memory: self.scheduler_used_memory,
+            kubelets_used_cpu: self.kubelets_used_cpu,
+            kubelets_used_memory: self.kubelets_used_memory,
+            pending: self.pending_pod_counter,
+            running: self.running_pod_counter,
+            succeed: self.succeed_pod_counter,
+            failed: self.failed_pod_counter,
+            evicted: self.evicted_pod_counter,
+            removed: self.removed_pod_counter,
+            preempted: self.preempted_pod_counter,
+            gang_wait_time: self.gang_wait_time,
+            disruptions_blocked: self.disruption_blocked_counter,
+            scheduled: self.scheduled_pod_counter,
+            queue_wait_time: self.queue_wait_time,
+            cost: self.node_cost.total_cost(self.ctx.time()),
+            power: self.node_energy.power(),
+            energy: self.node_energy.total_energy(self.ctx.time()),
+        };
+    }
 
-        self.scheduler_used_cpu_record.push(self.scheduler_used_cpu);
-        self.scheduler_used_memory_record.push(self.scheduler_used_memory);
-        self.kubelets_used_cpu_record.push(self.kubelets_used_cpu);
-        self.kubelets_used_memory_record.push(self.kubelets_used_memory);
+    /// Summary of the whole run so far. It is not affected by clear_records.
+    pub fn run_summary(&self) -> RunSummary {
+        return self.run_summary.summary(
+            &self.current_row(),
+            &self.pod_lifecycles,
+            self.node_cost.cost_by_group(self.ctx.time()),
+            self.node_energy.energy_by_node(self.ctx.time()),
+        );
+    }
 
-        self.pending_pod_counter_record.push(self.pending_pod_counter);
-        self.running_pod_counter_record.push(self.running_pod_counter);
-        self.succeed_pod_counter_record.push(self.succeed_pod_counter);
-        self.failed_pod_counter_record.push(self.failed_pod_counter);
-        self.evicted_pod_counter_record.push(self.evicted_pod_counter);
-        self.removed_pod_counter_record.push(self.removed_pod_counter);
-        self.preempted_pod_counter_record.push(self.preempted_pod_counter);
+    pub fn print_statistics(&mut self) {
+        let row = self.current_row();
+        match self.stream_mut() {
+            Some(stream) => stream.sink.write_row(row.time, &row.values()),
+            None => self.records.push(row),
+        }
 
         if self.print_enabled {
             print!(
-                "{:>6.3}  CPU: {:7.3}% / {:7.3}%  Memory: {:7.3}% / {:7.3}%  Nodes:{:<9} Pending:{:<9} Running:{:<9} Succeed:{:<9} Failed:{:<9} Removed:{:<9} Evicted:{:<9} Preempted:{:<9}\n",
+                "{:>6.3}  CPU: {:7.3}% / {:7.3}%  Memory: {:7.3}% / {:7.3}%  Nodes:{:<9} Pending:{:<9} Running:{:<9} Succeed:{:<9} Failed:{:<9} Removed:{:<9} Evicted:{:<9} Preempted:{:<9} GangWait:{:<9.3} Blocked:{:<9} Scheduled:{:<9} QueueWait:{:<9.3} Cost:{:<9.3} Energy:{:<9.3}\n",
                 self.ctx.time(),
                 (self.kubelets_used_cpu as f64) / (self.total_installed_cpu as f64) * 100.0f64,
                 (self.scheduler_used_cpu as f64) / (self.total_installed_cpu as f64) * 100.0f64,
@@ -330,47 +532,137 @@ impl Monitoring {
                 self.removed_pod_counter,
                 self.evicted_pod_counter,
                 self.preempted_pod_counter,
+                self.gang_wait_time,
+                self.disruption_blocked_counter,
+                self.scheduled_pod_counter,
+                self.queue_wait_time,
+                self.node_cost.total_cost(self.ctx.time()),
+                self.node_energy.total_energy(self.ctx.time()),
             );
         }
     }
 
-    pub fn dump_statistics(&self) {
-        let mut file = None;
+    /// Creates <out_path_prefix><suffix>_<counter>.<extension> with the first free counter.
+    fn create_numbered_file(&self, suffix: &str, extension: &str) -> (usize, File) {
         let mut counter: usize = 0;
-        while file.is_none() {
-            match std::fs::File::create_new(self.out_path_prefix.clone() + "_" + &*counter.to_string() + ".csv") {
-                Ok(new_file) => file = Some(new_file),
+        loop {
+            let path = format!("{}{}_{}.{}", self.out_path_prefix, suffix, counter, extension);
+            match File::create_new(path) {
+                Ok(file) => return (counter, file),
                 Err(_) => counter += 1,
             }
         }
-        let mut fout = BufWriter::new(file.unwrap());
-
-        write!(
-            fout,
-            "time,nodes,total_cpu,total_memory,scheduler_used_cpu,scheduler_used_memory,kubelets_used_cpu,kubelets_used_memory,pending,running,succeed,failed,evicted,removed,preempted\n"
-        ).unwrap();
-
-        for i in 0..self.time_record.len() {
-            write!(
-                fout,
-                "{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?}\n",
-                self.time_record[i],
-                self.node_counter_record[i],
-                self.total_installed_cpu_record[i],
-                self.total_installed_memory_record[i],
-                self.scheduler_used_cpu_record[i],
-                self.scheduler_used_memory_record[i],
-                self.kubelets_used_cpu_record[i],
-                self.kubelets_used_memory_record[i],
-                self.pending_pod_counter_record[i],
-                self.running_pod_counter_record[i],
-                self.succeed_pod_counter_record[i],
-                self.failed_pod_counter_record[i],
-                self.evicted_pod_counter_record[i],
-                self.removed_pod_counter_record[i],
-                self.preempted_pod_counter_record[i],
-            )
-            .unwrap();
+    }
+
+    /// Creates <out_path_prefix><suffix>_<counter>.csv for the counter of main statistics file.
+    fn create_csv_file(&self, suffix: &str, counter: usize) -> BufWriter<File> {
+        let path = format!("{}{}_{}.csv", self.out_path_prefix, suffix, counter);
+        let file = sim_ok!(
+            File::create(&path),
+            format!("Monitoring. Can not create file {}.", path)
+        );
+        return BufWriter::new(file);
+    }
+
+    /// Sink for cluster-wide statistics in configured format, downsampled if configured.
+    fn make_sink(&self, fout: BufWriter<File>) -> Box<dyn MetricsSink> {
+        let config = self.init_config.borrow().monitoring.clone();
+        let columns = MetricsRow::columns();
+        return match config.downsampling_window {
+            Some(window) => {
+                let inner = config.format.make_sink(fout, DownsamplingSink::columns(&columns));
+                Box::new(DownsamplingSink::new(inner, window, columns.len()))
+            }
+            None => config.format.make_sink(fout, columns),
+        };
+    }
+
+    /// Current stream in streaming mode. It is opened on the first use, so statistics produced before
+    /// (e.g. before the previous dump) are not written to it.
+    fn stream_mut(&mut self) -> Option<&mut MonitoringStream> {
+        if self.stream.is_none() && self.init_config.borrow().monitoring.streaming {
+            let extension = self.init_config.borrow().monitoring.format.extension();
+            let (counter, file) = self.create_numbered_file("", extension);
+            let sink = self.make_sink(BufWriter::new(file));
+
+            let mut pods = self.create_csv_file("_pods", counter);
+            PodLifecycles::write_csv_header(&mut pods);
+            let mut pod_groups = self.create_csv_file("_pod_groups", counter);
+            PodGroupMetrics::write_csv_header(&mut pod_groups);
+
+            let mut nodes = None;
+            if self.init_config.borrow().monitoring.node_utilization_period.is_some() {
+                let mut nodes_file = self.create_csv_file("_nodes", counter);
+                NodeUtilization::write_nodes_csv_header(&mut nodes_file);
+                let mut node_groups_file = self.create_csv_file("_node_groups", counter);
+                NodeUtilization::write_node_groups_csv_header(&mut node_groups_file);
+                nodes = Some((nodes_file, node_groups_file));
+            }
+
+            self.stream = Some(MonitoringStream {
+                counter,
+                sink,
+                pods,
+                pod_groups,
+                nodes,
+            });
+        }
+        return self.stream.as_mut();
+    }
+
+    /// Writes statistics rows and other collected data to files with the same counter.
+    /// In streaming mode the current stream is finished with rows of not finished pods
+    /// and the next statistics go to new files.
+    pub fn dump_statistics(&mut self) {
+        let counter = match self.stream.take() {
+            Some(mut stream) => {
+                stream.sink.finish();
+                self.pod_lifecycles.write_finished_csv(&mut stream.pods);
+                self.pod_lifecycles.write_csv_records(&mut stream.pods);
+                stream.pods.flush().unwrap();
+                stream.pod_groups.flush().unwrap();
+                if let Some((mut nodes, mut node_groups)) = stream.nodes {
+                    nodes.flush().unwrap();
+                    node_groups.flush().unwrap();
+                }
+                stream.counter
+            }
+            None => {
+                let extension = self.init_config.borrow().monitoring.format.extension();
+                let (counter, file) = self.create_numbered_file("", extension);
+                let mut sink = self.make_sink(BufWriter::new(file));
+                for row in self.records.iter() {
+                    sink.write_row(row.time, &row.values());
+                }
+                sink.finish();
+
+                self.pod_lifecycles
+                    .write_csv(&mut self.create_csv_file("_pods", counter));
+                self.pod_group_metrics
+                    .write_csv(&mut self.create_csv_file("_pod_groups", counter));
+
+                if self.init_config.borrow().monitoring.node_utilization_period.is_some() {
+                    self.node_utilization
+                        .write_nodes_csv(&mut self.create_csv_file("_nodes", counter));
+                    self.node_utilization
+                        .write_node_groups_csv(&mut self.create_csv_file("_node_groups", counter));
+                }
+                counter
+            }
+        };
+
+        let summary_path = format!("{}_summary_{}.json", self.out_path_prefix, counter);
+        let summary_file = sim_ok!(
+            File::create(&summary_path),
+            format!("Monitoring. Can not create file {}.", summary_path)
+        );
+        sim_ok!(
+            serde_json::to_writer_pretty(summary_file, &self.run_summary()),
+            format!("Monitoring. Can not write file {}.", summary_path)
+        );
+
+        for line in self.pod_lifecycles.summary() {
+            println!("{}", line);
         }
     }
 }
@@ -380,6 +672,17 @@ impl dsc::EventHandler for Monitoring {
         dsc::cast!(match event.data {
             EventSelfUpdate {} => {
                 self.print_statistics();
+                self.pod_group_metrics.sample(self.ctx.time());
+                let (stranded_cpu, stranded_memory) = self.node_utilization.stranded(STRANDED_FREE_FRACTION);
+                self.run_summary
+                    .on_sample(&self.current_row(), stranded_cpu, stranded_memory);
+
+                // Streamed records are not kept
+                if let Some(stream) = self.stream.as_mut() {
+                    self.pod_group_metrics.write_csv_records(&mut stream.pod_groups);
+                    self.pod_group_metrics.clear_records();
+                    self.pod_lifecycles.write_finished_csv(&mut stream.pods);
+                }
 
                 if self.self_update_enabled {
                     self.ctx.emit_self(
@@ -388,6 +691,22 @@ impl dsc::EventHandler for Monitoring {
                     );
                 }
             }
+
+            EventSampleNodeUtilization {} => {
+                self.node_utilization.sample(self.ctx.time());
+
+                self.stream_mut();
+                if let Some((nodes, node_groups)) = self.stream.as_mut().and_then(|stream| stream.nodes.as_mut()) {
+                    self.node_utilization.write_nodes_csv_records(nodes);
+                    self.node_utilization.write_node_groups_csv_records(node_groups);
+                    self.node_utilization.clear_records();
+                }
+
+                if self.self_update_enabled {
+                    let period = self.init_config.borrow().monitoring.node_utilization_period.unwrap();
+                    self.ctx.emit_self(EventSampleNodeUtilization {}, period);
+                }
+            }
         });
     }
 }
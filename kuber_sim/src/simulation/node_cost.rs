@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+/// Cost of nodes by NodeGroup.cost_per_hour, simulation time is in seconds.
+/// Node is billed from its request (so CA nodes are billed during ISP delay) until its removal.
+#[derive(Debug, Default)]
+pub struct NodeCost {
+    cost_per_hour: BTreeMap<u64, f64>,       // BTreeMap<group_uid, cost_per_hour>
+    billed_nodes: BTreeMap<u64, (u64, f64)>, // BTreeMap<node_uid, (group_uid, billing_start_time)>
+    /// Cost of nodes which are not billed any more.
+    closed_cost: BTreeMap<u64, f64>, // BTreeMap<group_uid, cost>
+
+    /// Cost per hour of billed nodes and their cost up to last_update_time,
+    /// so total cost is not summed over nodes on each query.
+    cost_rate: f64,
+    cost: f64,
+    last_update_time: f64,
+}
+
+impl NodeCost {
+    pub fn new() -> Self {
+        Self {
+            cost_per_hour: BTreeMap::new(),
+            billed_nodes: BTreeMap::new(),
+            closed_cost: BTreeMap::new(),
+            cost_rate: 0.0,
+            cost: 0.0,
+            last_update_time: 0.0,
+        }
+    }
+
+    fn update(&mut self, time: f64) {
+        self.cost += self.cost_rate * (time - self.last_update_time) / 3600.0;
+        self.last_update_time = time;
+    }
+
+    fn group_cost_per_hour(&self, group_uid: u64) -> f64 {
+        return self.cost_per_hour.get(&group_uid).copied().unwrap_or(0.0);
+    }
+
+    /// Nodes of groups without cost are free.
+    pub fn set_group_cost(&mut self, group_uid: u64, cost_per_hour: f64) {
+        self.cost_per_hour.insert(group_uid, cost_per_hour);
+    }
+
+    /// Starts billing of node. Node which is already billed is not affected.
+    pub fn on_node_requested(&mut self, node_uid: u64, group_uid: u64, time: f64) {
+        if self.billed_nodes.contains_key(&node_uid) {
+            return;
+        }
+        self.update(time);
+        self.cost_rate += self.group_cost_per_hour(group_uid);
+        self.billed_nodes.insert(node_uid, (group_uid, time));
+    }
+
+    pub fn on_node_removed(&mut self, node_uid: u64, time: f64) {
+        if let Some((group_uid, start_time)) = self.billed_nodes.remove(&node_uid) {
+            self.update(time);
+            self.cost_rate -= self.group_cost_per_hour(group_uid);
+            let cost = self.node_cost(group_uid, start_time, time);
+            *self.closed_cost.entry(group_uid).or_default() += cost;
+        }
+    }
+
+    fn node_cost(&self, group_uid: u64, start_time: f64, time: f64) -> f64 {
+        return self.group_cost_per_hour(group_uid) * (time - start_time) / 3600.0;
+    }
+
+    /// Cost of each group with billed nodes up to time.
+    pub fn cost_by_group(&self, time: f64) -> BTreeMap<u64, f64> {
+        let mut result = self.closed_cost.clone();
+        for &(group_uid, start_time) in self.billed_nodes.values() {
+            *result.entry(group_uid).or_default() += self.node_cost(group_uid, start_time, time);
+        }
+        return result;
+    }
+
+    /// Cost of all nodes up to time.
+    pub fn total_cost(&self, time: f64) -> f64 {
+        return self.cost + self.cost_rate * (time - self.last_update_time) / 3600.0;
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_cost() {
+        let mut cost = NodeCost::new();
+        cost.set_group_cost(1, 3.6);
+        cost.set_group_cost(2, 36.0);
+
+        cost.on_node_requested(10, 1, 0.0);
+        cost.on_node_requested(20, 2, 100.0);
+        // Node added to cluster after ISP delay is billed since request
+        cost.on_node_requested(20, 2, 150.0);
+        // Group without cost
+        cost.on_node_requested(30, 3, 0.0);
+        cost.on_node_removed(20, 200.0);
+        // Unknown node is ignored
+        cost.on_node_removed(40, 200.0);
+
+        assert_eq!(cost.cost_by_group(1000.0), BTreeMap::from([(1, 1.0), (2, 1.0), (3, 0.0)]));
+        // Running total is accumulated, so it may differ from the sum in the last digits
+        assert!((cost.total_cost(1000.0) - 2.0).abs() < 1e-9);
+        assert!((cost.total_cost(2000.0) - 3.0).abs() < 1e-9);
+    }
+}
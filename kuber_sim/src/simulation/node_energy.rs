@@ -0,0 +1,163 @@
+use crate::objects::node::Node;
+use crate::objects::power_model::PowerModel;
+use std::collections::BTreeMap;
+
+/// Node with power model while it is in cluster.
+#[derive(Debug, Clone)]
+struct PoweredNode {
+    model: PowerModel,
+    installed_cpu: i64,
+    used_cpu: i64,
+    power: f64,
+    /// Energy up to last_update_time.
+    energy: f64,
+    last_update_time: f64,
+}
+
+impl PoweredNode {
+    fn update(&mut self, time: f64) {
+        self.energy += self.power * (time - self.last_update_time) / 3600.0;
+        self.last_update_time = time;
+    }
+}
+
+/// Energy (Wh) consumed by nodes by PowerModel of their NodeGroups, simulation time is in seconds.
+/// Node consumes power while it is in cluster. Nodes of groups without power model are not counted.
+#[derive(Debug, Default)]
+pub struct NodeEnergy {
+    power_models: BTreeMap<u64, PowerModel>, // BTreeMap<group_uid, power_model>
+    nodes: BTreeMap<u64, PoweredNode>,       // BTreeMap<node_uid, node>
+    /// Energy of nodes removed from cluster.
+    removed_energy: BTreeMap<u64, f64>, // BTreeMap<node_uid, energy>
+
+    /// Cluster power and energy up to last_update_time, so they are not summed over nodes on each query.
+    power: f64,
+    energy: f64,
+    last_update_time: f64,
+}
+
+impl NodeEnergy {
+    pub fn new() -> Self {
+        Self {
+            power_models: BTreeMap::new(),
+            nodes: BTreeMap::new(),
+            removed_energy: BTreeMap::new(),
+            power: 0.0,
+            energy: 0.0,
+            last_update_time: 0.0,
+        }
+    }
+
+    pub fn set_group_power_model(&mut self, group_uid: u64, model: PowerModel) {
+        self.power_models.insert(group_uid, model);
+    }
+
+    fn update(&mut self, time: f64) {
+        self.energy += self.power * (time - self.last_update_time) / 3600.0;
+        self.last_update_time = time;
+    }
+
+    pub fn on_node_added(&mut self, node: &Node, time: f64) {
+        let model = match self.power_models.get(&node.metadata.group_uid) {
+            Some(model) => model.clone(),
+            None => return,
+        };
+
+        self.update(time);
+        let power = model.power(0.0);
+        self.power += power;
+        self.nodes.insert(
+            node.metadata.uid,
+            PoweredNode {
+                model,
+                installed_cpu: node.spec.installed_cpu,
+                used_cpu: 0,
+                power,
+                energy: 0.0,
+                last_update_time: time,
+            },
+        );
+    }
+
+    pub fn on_node_removed(&mut self, node_uid: u64, time: f64) {
+        if let Some(mut node) = self.nodes.remove(&node_uid) {
+            self.update(time);
+            self.power -= node.power;
+            node.update(time);
+            *self.removed_energy.entry(node_uid).or_default() += node.energy;
+        }
+    }
+
+    /// Actual CPU use of pod on kubelet is placed (sign = 1) or unplaced (sign = -1).
+    pub fn on_cpu_used(&mut self, node_uid: u64, cpu: i64, sign: i64, time: f64) {
+        if !self.nodes.contains_key(&node_uid) {
+            return;
+        }
+        self.update(time);
+
+        let node = self.nodes.get_mut(&node_uid).unwrap();
+        node.update(time);
+        node.used_cpu += sign * cpu;
+        let power = node.model.power(node.used_cpu as f64 / node.installed_cpu as f64);
+        self.power += power - node.power;
+        node.power = power;
+    }
+
+    /// Current power of cluster, W.
+    pub fn power(&self) -> f64 {
+        return self.power;
+    }
+
+    /// Energy of cluster up to time, Wh.
+    pub fn total_energy(&self, time: f64) -> f64 {
+        return self.energy + self.power * (time - self.last_update_time) / 3600.0;
+    }
+
+    /// Energy of each node with power model up to time, including removed nodes, Wh.
+    pub fn energy_by_node(&self, time: f64) -> BTreeMap<u64, f64> {
+        let mut result = self.removed_energy.clone();
+        for (&node_uid, node) in self.nodes.iter() {
+            let energy = node.energy + node.power * (time - node.last_update_time) / 3600.0;
+            *result.entry(node_uid).or_default() += energy;
+        }
+        return result;
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_node(uid: u64, group_uid: u64) -> Node {
+        let mut node = Node::default();
+        node.metadata.uid = uid;
+        node.metadata.group_uid = group_uid;
+        node.spec.installed_cpu = 100;
+        return node;
+    }
+
+    #[test]
+    fn test_node_energy() {
+        let mut energy = NodeEnergy::new();
+        energy.set_group_power_model(1, PowerModel { idle_watts: 100.0, max_watts: 200.0, points: vec![] });
+
+        energy.on_node_added(&make_node(1, 1), 0.0);
+        energy.on_node_added(&make_node(2, 1), 0.0);
+        // Group without power model
+        energy.on_node_added(&make_node(3, 2), 0.0);
+        energy.on_cpu_used(3, 100, 1, 0.0);
+        assert_eq!(energy.power(), 200.0);
+
+        energy.on_cpu_used(1, 50, 1, 3600.0);
+        assert_eq!(energy.power(), 250.0);
+        energy.on_node_removed(2, 7200.0);
+        energy.on_cpu_used(1, 50, -1, 10800.0);
+
+        assert_eq!(energy.power(), 100.0);
+        assert_eq!(energy.total_energy(14400.0), 200.0 + 250.0 + 150.0 + 100.0);
+        assert_eq!(energy.energy_by_node(14400.0), BTreeMap::from([(1, 100.0 + 150.0 + 150.0 + 100.0), (2, 200.0)]));
+    }
+}
@@ -0,0 +1,297 @@
+use crate::objects::node::Node;
+use crate::scheduler::features::taints_tolerations::Taint;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Resources of one node: requested by pods bound by schedulers vs actually used by pods on kubelet.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeUsage {
+    pub group_uid: u64,
+    pub installed_cpu: i64,
+    pub installed_memory: i64,
+    pub requested_cpu: i64,
+    pub requested_memory: i64,
+    pub used_cpu: i64,
+    pub used_memory: i64,
+    /// Pods bound to node.
+    pub pods: u64,
+    pub taints: Vec<Taint>,
+}
+
+/// Sum of NodeUsage over nodes of one NodeGroup.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeGroupUsage {
+    pub nodes: u64,
+    pub installed_cpu: i64,
+    pub installed_memory: i64,
+    pub requested_cpu: i64,
+    pub requested_memory: i64,
+    pub used_cpu: i64,
+    pub used_memory: i64,
+    pub pods: u64,
+}
+
+/// Current usage of each node in cluster and its samples taken every sampling period.
+/// Updates of unknown (not added or already removed) nodes are ignored.
+#[derive(Debug, Default)]
+pub struct NodeUtilization {
+    nodes: BTreeMap<u64, NodeUsage>, // BTreeMap<node_uid, usage>
+
+    node_records: Vec<(f64, u64, NodeUsage)>, // Vec<(time, node_uid, usage)>
+    node_group_records: Vec<(f64, u64, NodeGroupUsage)>, // Vec<(time, group_uid, usage)>
+}
+
+impl NodeUtilization {
+    pub fn new() -> Self {
+        Self {
+            nodes: BTreeMap::new(),
+            node_records: Vec::new(),
+            node_group_records: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, node_uid: u64) -> Option<&NodeUsage> {
+        return self.nodes.get(&node_uid);
+    }
+
+    ////////////////// Updates //////////////////
+
+    pub fn on_node_added(&mut self, node: &Node) {
+        self.nodes.insert(
+            node.metadata.uid,
+            NodeUsage {
+                group_uid: node.metadata.group_uid,
+                installed_cpu: node.spec.installed_cpu,
+                installed_memory: node.spec.installed_memory,
+                taints: node.spec.taints.clone(),
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn on_node_removed(&mut self, node_uid: u64) {
+        self.nodes.remove(&node_uid);
+    }
+
+    pub fn on_taints_changed(&mut self, node_uid: u64, taints: &Vec<Taint>) {
+        if let Some(usage) = self.nodes.get_mut(&node_uid) {
+            usage.taints = taints.clone();
+        }
+    }
+
+    /// Pod with given requests is bound to node (sign = 1) or removed from it (sign = -1).
+    pub fn on_pod_requested(&mut self, node_uid: u64, cpu: i64, memory: i64, sign: i64) {
+        if let Some(usage) = self.nodes.get_mut(&node_uid) {
+            usage.requested_cpu += sign * cpu;
+            usage.requested_memory += sign * memory;
+            usage.pods = usage.pods.checked_add_signed(sign).unwrap();
+        }
+    }
+
+    /// Actual usage of pod on kubelet is placed (sign = 1) or unplaced (sign = -1).
+    pub fn on_pod_used(&mut self, node_uid: u64, cpu: i64, memory: i64, sign: i64) {
+        if let Some(usage) = self.nodes.get_mut(&node_uid) {
+            usage.used_cpu += sign * cpu;
+            usage.used_memory += sign * memory;
+        }
+    }
+
+    /// Free (not requested) CPU on nodes whose free memory is at most free_fraction of installed,
+    /// and free memory on nodes whose free CPU is at most free_fraction of installed.
+    /// Such resources can hardly be allocated to any pod.
+    pub fn stranded(&self, free_fraction: f64) -> (i64, i64) {
+        let mut stranded_cpu: i64 = 0;
+        let mut stranded_memory: i64 = 0;
+        for usage in self.nodes.values() {
+            let free_cpu = usage.installed_cpu - usage.requested_cpu;
+            let free_memory = usage.installed_memory - usage.requested_memory;
+            if (free_memory as f64) <= free_fraction * usage.installed_memory as f64 {
+                stranded_cpu += free_cpu.max(0);
+            }
+            if (free_cpu as f64) <= free_fraction * usage.installed_cpu as f64 {
+                stranded_memory += free_memory.max(0);
+            }
+        }
+        return (stranded_cpu, stranded_memory);
+    }
+
+    ////////////////// Records //////////////////
+
+    pub fn sample(&mut self, time: f64) {
+        let mut groups: BTreeMap<u64, NodeGroupUsage> = BTreeMap::new();
+        for (&node_uid, usage) in self.nodes.iter() {
+            self.node_records.push((time, node_uid, usage.clone()));
+
+            let group = groups.entry(usage.group_uid).or_default();
+            group.nodes += 1;
+            group.installed_cpu += usage.installed_cpu;
+            group.installed_memory += usage.installed_memory;
+            group.requested_cpu += usage.requested_cpu;
+            group.requested_memory += usage.requested_memory;
+            group.used_cpu += usage.used_cpu;
+            group.used_memory += usage.used_memory;
+            group.pods += usage.pods;
+        }
+
+        for (group_uid, group) in groups {
+            self.node_group_records.push((time, group_uid, group));
+        }
+    }
+
+    pub fn clear_records(&mut self) {
+        self.node_records.clear();
+        self.node_group_records.clear();
+    }
+
+    pub fn write_nodes_csv<W: Write>(&self, fout: &mut W) {
+        Self::write_nodes_csv_header(fout);
+        self.write_nodes_csv_records(fout);
+    }
+
+    pub fn write_nodes_csv_header<W: Write>(fout: &mut W) {
+        write!(
+            fout,
+            "time,node_uid,group_uid,installed_cpu,installed_memory,requested_cpu,requested_memory,used_cpu,used_memory,pods,taints\n"
+        )
+        .unwrap();
+    }
+
+    /// Writes node records without header.
+    pub fn write_nodes_csv_records<W: Write>(&self, fout: &mut W) {
+        for (time, node_uid, usage) in self.node_records.iter() {
+            // Taints are written as "key=value:Effect" separated by spaces
+            let taints: Vec<String> = usage
+                .taints
+                .iter()
+                .map(|taint| format!("{}={}:{:?}", taint.key, taint.value, taint.effect))
+                .collect();
+            write!(
+                fout,
+                "{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{}\n",
+                time,
+                node_uid,
+                usage.group_uid,
+                usage.installed_cpu,
+                usage.installed_memory,
+                usage.requested_cpu,
+                usage.requested_memory,
+                usage.used_cpu,
+                usage.used_memory,
+                usage.pods,
+                taints.join(" "),
+            )
+            .unwrap();
+        }
+    }
+
+    pub fn write_node_groups_csv<W: Write>(&self, fout: &mut W) {
+        Self::write_node_groups_csv_header(fout);
+        self.write_node_groups_csv_records(fout);
+    }
+
+    pub fn write_node_groups_csv_header<W: Write>(fout: &mut W) {
+        write!(
+            fout,
+            "time,group_uid,nodes,installed_cpu,installed_memory,requested_cpu,requested_memory,used_cpu,used_memory,pods\n"
+        )
+        .unwrap();
+    }
+
+    /// Writes node group records without header.
+    pub fn write_node_groups_csv_records<W: Write>(&self, fout: &mut W) {
+        for (time, group_uid, group) in self.node_group_records.iter() {
+            write!(
+                fout,
+                "{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?},{:?}\n",
+                time,
+                group_uid,
+                group.nodes,
+                group.installed_cpu,
+                group.installed_memory,
+                group.requested_cpu,
+                group.requested_memory,
+                group.used_cpu,
+                group.used_memory,
+                group.pods,
+            )
+            .unwrap();
+        }
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::features::taints_tolerations::TaintTolerationEffect;
+
+    fn make_node(uid: u64, group_uid: u64) -> Node {
+        let mut node = Node::default();
+        node.metadata.uid = uid;
+        node.metadata.group_uid = group_uid;
+        node.spec.installed_cpu = 100;
+        node.spec.installed_memory = 200;
+        return node;
+    }
+
+    #[test]
+    fn test_node_utilization() {
+        let mut utilization = NodeUtilization::new();
+        utilization.on_node_added(&make_node(1, 10));
+        utilization.on_node_added(&make_node(2, 10));
+        utilization.on_node_added(&make_node(3, 20));
+
+        utilization.on_pod_requested(1, 30, 40, 1);
+        utilization.on_pod_requested(2, 20, 20, 1);
+        utilization.on_pod_requested(2, 10, 10, 1);
+        utilization.on_pod_used(1, 25, 50, 1);
+        utilization.on_pod_used(1, 25, 50, -1);
+        utilization.on_pod_used(1, 15, 35, 1);
+        utilization.on_pod_requested(2, 10, 10, -1);
+        utilization.on_taints_changed(3, &vec![Taint { key: "k".to_string(), value: "v".to_string(), effect: TaintTolerationEffect::NoSchedule }]);
+
+        // Unknown nodes are ignored
+        utilization.on_pod_requested(4, 10, 10, 1);
+        assert!(utilization.get(4).is_none());
+
+        let usage = utilization.get(1).unwrap();
+        assert_eq!((usage.requested_cpu, usage.requested_memory, usage.used_cpu, usage.used_memory, usage.pods), (30, 40, 15, 35, 1));
+        assert_eq!(utilization.get(2).unwrap().pods, 1);
+
+        // Node 1 has 70 free CPU and 160 free memory, nodes 2 and 3 are empty
+        assert_eq!(utilization.stranded(0.05), (0, 0));
+        utilization.on_pod_requested(1, 0, 160, 1);
+        assert_eq!(utilization.stranded(0.05), (70, 0));
+        utilization.on_pod_requested(1, 0, 160, -1);
+
+        utilization.sample(5.0);
+        utilization.on_node_removed(3);
+        utilization.sample(10.0);
+
+        let mut out: Vec<u8> = Vec::new();
+        utilization.write_nodes_csv(&mut out);
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[1], "5.0,1,10,100,200,30,40,15,35,1,");
+        assert_eq!(lines[3], "5.0,3,20,100,200,0,0,0,0,0,k=v:NoSchedule");
+
+        let mut out: Vec<u8> = Vec::new();
+        utilization.write_node_groups_csv(&mut out);
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines, vec![
+            "time,group_uid,nodes,installed_cpu,installed_memory,requested_cpu,requested_memory,used_cpu,used_memory,pods",
+            "5.0,10,2,200,400,50,60,15,35,2",
+            "5.0,20,1,100,200,0,0,0,0,0",
+            "10.0,10,2,200,400,50,60,15,35,2",
+        ]);
+
+        utilization.clear_records();
+        let mut out: Vec<u8> = Vec::new();
+        utilization.write_node_groups_csv(&mut out);
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 1);
+    }
+}
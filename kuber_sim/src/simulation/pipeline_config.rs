@@ -1,9 +1,93 @@
-use crate::scheduler::pipeline::filter::IFilterPlugin;
+use crate::error::KuberSimError;
+use crate::scheduler::pipeline::filter::{FilterCoscheduling, IFilterPlugin};
+use crate::scheduler::pipeline::permit::{IPermitPlugin, PermitCoscheduling};
+use crate::scheduler::pipeline::pre_bind::IPreBindPlugin;
+use crate::scheduler::pipeline::registry::{PluginArgs, PluginRegistry};
+use crate::scheduler::pipeline::reserve::IReservePlugin;
 use crate::scheduler::pipeline::score::IScorePlugin;
 use crate::scheduler::pipeline::score_normalize::IScoreNormalizePlugin;
 use crate::scheduler::queues::active_queue::IActiveQ;
 use crate::scheduler::queues::backoff_queue::IBackOffQ;
 
+/////////////////////////////////////////// PluginSpec /////////////////////////////////////////////
+
+/// Reference to a registered plugin with its parameters.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PluginSpec {
+    pub name: String,
+    #[serde(default)]
+    pub args: PluginArgs,
+}
+
+/// Analog of score plugin weight of kube-scheduler profile
+fn scorer_weight_default() -> i64 {
+    1
+}
+fn scorer_normalizer_default() -> PluginSpec {
+    PluginSpec {
+        name: "ScoreNormalizeSkip".to_string(),
+        args: PluginArgs::default(),
+    }
+}
+
+/// Reference to a registered score plugin with its weight and normalizer.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ScorerSpec {
+    pub name: String,
+    #[serde(default)]
+    pub args: PluginArgs,
+    #[serde(default = "scorer_weight_default")]
+    pub weight: i64,
+    #[serde(default = "scorer_normalizer_default")]
+    pub normalizer: PluginSpec,
+}
+
+//////////////////////////////////////// SchedulerProfile //////////////////////////////////////////
+
+/// Analog of queueSort plugin of kube-scheduler profile
+fn profile_active_queue_default() -> PluginSpec {
+    PluginSpec {
+        name: "ActiveQDefault".to_string(),
+        args: PluginArgs::default(),
+    }
+}
+/// Analog of podInitialBackoffSeconds and podMaxBackoffSeconds of kube-scheduler
+fn profile_backoff_queue_default() -> PluginSpec {
+    PluginSpec {
+        name: "BackOffQDefault".to_string(),
+        args: PluginArgs::default(),
+    }
+}
+
+/// Analog of kube-scheduler profile. Plugins are referenced by their names in PluginRegistry.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SchedulerProfile {
+    #[serde(default = "profile_active_queue_default")]
+    pub active_queue: PluginSpec,
+    #[serde(default = "profile_backoff_queue_default")]
+    pub backoff_queue: PluginSpec,
+
+    #[serde(default)]
+    pub filters: Vec<PluginSpec>,
+    #[serde(default)]
+    pub post_filters: Vec<PluginSpec>,
+    #[serde(default)]
+    pub scorers: Vec<ScorerSpec>,
+    #[serde(default)]
+    pub reserves: Vec<PluginSpec>,
+    #[serde(default)]
+    pub permits: Vec<PluginSpec>,
+    #[serde(default)]
+    pub pre_binds: Vec<PluginSpec>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct SchedulerProfileFile {
+    scheduler_profile: SchedulerProfile,
+}
+
+///////////////////////////////////////// PipelineConfig ///////////////////////////////////////////
+
 pub struct PipelineConfig {
     pub active_queue: Box<dyn IActiveQ + Send>,
     pub backoff_queue: Box<dyn IBackOffQ + Send>,
@@ -13,6 +97,11 @@ pub struct PipelineConfig {
     pub scorers: Vec<Box<dyn IScorePlugin + Send>>,
     pub score_normalizers: Vec<Box<dyn IScoreNormalizePlugin + Send>>,
     pub scorer_weights: Vec<i64>,
+
+    // Optional extension points. Empty after new(), fill them directly if necessary.
+    pub reserves: Vec<Box<dyn IReservePlugin + Send>>,
+    pub permits: Vec<Box<dyn IPermitPlugin + Send>>,
+    pub pre_binds: Vec<Box<dyn IPreBindPlugin + Send>>,
 }
 
 impl PipelineConfig {
@@ -34,7 +123,94 @@ impl PipelineConfig {
             scorers,
             score_normalizers,
             scorer_weights,
+            reserves: Vec::new(),
+            permits: Vec::new(),
+            pre_binds: Vec::new(),
+        }
+    }
+
+    /// Turns on gang scheduling of pod groups with min_member > 0.
+    /// Reserved pods of a gang wait for the rest of it at most timeout seconds.
+    pub fn enable_coscheduling(&mut self, timeout: f64) -> Result<(), KuberSimError> {
+        let permit = PermitCoscheduling::new(timeout)?;
+        self.filters.insert(0, Box::new(FilterCoscheduling));
+        self.permits.push(Box::new(permit));
+        return Ok(());
+    }
+
+    /// Reads `scheduler_profile` section of yaml file and builds it with built-in plugins.
+    pub fn from_yaml(path: &String) -> Result<Self, KuberSimError> {
+        return PipelineConfig::from_yaml_with_registry(path, &PluginRegistry::default());
+    }
+
+    /// Reads `scheduler_profile` section of yaml file and builds it with plugins from registry.
+    pub fn from_yaml_with_registry(path: &String, registry: &PluginRegistry) -> Result<Self, KuberSimError> {
+        // Read file to string
+        let s: String = std::fs::read_to_string(path).map_err(|e| KuberSimError::from_io(path, e))?;
+        // Build struct from string
+        let profile_file: SchedulerProfileFile =
+            serde_yaml::from_str(s.as_str()).map_err(|e| KuberSimError::from_yaml(path, e))?;
+
+        return PipelineConfig::from_profile(&profile_file.scheduler_profile, registry).map_err(|e| e.with_file(path));
+    }
+
+    pub fn from_profile(profile: &SchedulerProfile, registry: &PluginRegistry) -> Result<Self, KuberSimError> {
+        // Errors refer to plugins by their place in profile, e.g. "filters[1].PluginSpec.name"
+        fn at<T>(result: Result<T, KuberSimError>, list: &str, i: usize) -> Result<T, KuberSimError> {
+            return result.map_err(|e| e.with_parent(&format!("{}[{}]", list, i)));
+        }
+
+        let active_queue = registry
+            .make_active_queue(&profile.active_queue.name, &profile.active_queue.args)
+            .map_err(|e| e.with_parent("active_queue"))?;
+        let backoff_queue = registry
+            .make_backoff_queue(&profile.backoff_queue.name, &profile.backoff_queue.args)
+            .map_err(|e| e.with_parent("backoff_queue"))?;
+
+        let mut filters = Vec::new();
+        for (i, spec) in profile.filters.iter().enumerate() {
+            filters.push(at(registry.make_filter(&spec.name, &spec.args), "filters", i)?);
+        }
+        let mut post_filters = Vec::new();
+        for (i, spec) in profile.post_filters.iter().enumerate() {
+            post_filters.push(at(registry.make_filter(&spec.name, &spec.args), "post_filters", i)?);
+        }
+
+        let (mut scorers, mut score_normalizers, mut scorer_weights) = (Vec::new(), Vec::new(), Vec::new());
+        for (i, spec) in profile.scorers.iter().enumerate() {
+            scorers.push(at(registry.make_scorer(&spec.name, &spec.args), "scorers", i)?);
+            let normalizer = registry
+                .make_score_normalizer(&spec.normalizer.name, &spec.normalizer.args)
+                .map_err(|e| e.with_parent("normalizer"));
+            score_normalizers.push(at(normalizer, "scorers", i)?);
+            scorer_weights.push(spec.weight);
+        }
+
+        let mut pipeline_config = PipelineConfig::new(
+            active_queue,
+            backoff_queue,
+            filters,
+            post_filters,
+            scorers,
+            score_normalizers,
+            scorer_weights,
+        );
+
+        // Optional extension points
+        for (i, spec) in profile.reserves.iter().enumerate() {
+            let plugin = at(registry.make_reserve(&spec.name, &spec.args), "reserves", i)?;
+            pipeline_config.reserves.push(plugin);
+        }
+        for (i, spec) in profile.permits.iter().enumerate() {
+            let plugin = at(registry.make_permit(&spec.name, &spec.args), "permits", i)?;
+            pipeline_config.permits.push(plugin);
         }
+        for (i, spec) in profile.pre_binds.iter().enumerate() {
+            let plugin = at(registry.make_pre_bind(&spec.name, &spec.args), "pre_binds", i)?;
+            pipeline_config.pre_binds.push(plugin);
+        }
+
+        return Ok(pipeline_config);
     }
 }
 
@@ -48,6 +224,68 @@ impl Clone for PipelineConfig {
             scorers: self.scorers.iter().map(|x| (*x).clone()).collect(),
             score_normalizers: self.score_normalizers.iter().map(|x| (*x).clone()).collect(),
             scorer_weights: self.scorer_weights.clone(),
+            reserves: self.reserves.iter().map(|x| (*x).clone()).collect(),
+            permits: self.permits.iter().map(|x| (*x).clone()).collect(),
+            pre_binds: self.pre_binds.iter().map(|x| (*x).clone()).collect(),
         }
     }
 }
+
+///////////////////////////////////////////// Test /////////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_profile() {
+        let profile: SchedulerProfile = serde_yaml::from_str("
+active_queue:
+  name: ActiveMaxQ<ActiveQCmpUid>
+backoff_queue:
+  name: BackOffQConstant
+  args:
+    backoff_delay: 3.0
+filters:
+  - name: FilterRequestedResourcesAvailable
+  - name: FilterTaintsTolerations
+post_filters:
+  - name: FilterPreemption
+scorers:
+  - name: ScoreRequestedToCapacityRatio
+    weight: 2
+    args:
+      shape: [[0, 10], [100, 0]]
+      memory_weight: 3
+  - name: ScoreTaintsTolerations
+    normalizer:
+      name: ScoreNormalizeMinMax
+").unwrap();
+
+        let pipeline_config = PipelineConfig::from_profile(&profile, &PluginRegistry::default()).unwrap();
+        assert_eq!(pipeline_config.filters.iter().map(|x| x.name()).collect::<Vec<String>>(), vec!["FilterRequestedResourcesAvailable", "FilterTaintsTolerations"]);
+        assert_eq!(pipeline_config.post_filters.iter().map(|x| x.name()).collect::<Vec<String>>(), vec!["FilterPreemption"]);
+        assert_eq!(pipeline_config.scorers.iter().map(|x| x.name()).collect::<Vec<String>>(), vec!["ScoreRequestedToCapacityRatio", "ScoreTaintsTolerations"]);
+        assert_eq!(pipeline_config.score_normalizers.iter().map(|x| x.name()).collect::<Vec<String>>(), vec!["ScoreNormalizeSkip", "ScoreNormalizeMinMax"]);
+        assert_eq!(pipeline_config.scorer_weights, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_registry_default() {
+        let registry = PluginRegistry::default();
+        assert_eq!(registry.active_queues.len(), 5);
+        assert_eq!(registry.backoff_queues.len(), 3);
+        assert_eq!(registry.filters.len(), 10);
+        assert_eq!(registry.scorers.len(), 12);
+        assert_eq!(registry.score_normalizers.len(), 5);
+        assert_eq!(registry.permits.len(), 1);
+        assert_eq!(registry.make_scorer("ScoreTetris", &PluginArgs::default()).unwrap().name(), "ScoreTetris");
+
+        // Invalid arguments are reported as errors
+        let args: PluginArgs = serde_yaml::from_str("{cpu_weight: heavy}").unwrap();
+        assert_eq!(registry.make_scorer("ScoreLeastAllocated", &args).err().unwrap().field, Some("PluginArgs.cpu_weight".to_string()));
+        let args: PluginArgs = serde_yaml::from_str("{timeout: 0.0}").unwrap();
+        assert_eq!(registry.make_permit("PermitCoscheduling", &args).err().unwrap().field, Some("PermitCoscheduling.timeout".to_string()));
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// What autoscalers and schedulers know about a pod group. None values are not reported (yet).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PodGroupState {
+    /// Not finished pods of group, as HPA counts them.
+    pub replicas: Option<u64>,
+    /// Mean utilization of running pods (usage / request) seen by HPA. None if no pod is running.
+    pub mean_cpu: Option<f64>,
+    pub mean_memory: Option<f64>,
+    /// The last recommendation applied by VPA.
+    pub request_cpu: Option<i64>,
+    pub request_memory: Option<i64>,
+    pub limit_cpu: Option<i64>,
+    pub limit_memory: Option<i64>,
+    /// Pending pods of group in all schedulers.
+    pub pending: u64,
+}
+
+/// Current state of pod groups and its samples taken every monitoring update.
+#[derive(Debug, Default)]
+pub struct PodGroupMetrics {
+    groups: BTreeMap<u64, PodGroupState>, // BTreeMap<group_uid, state>
+    pending_by_scheduler: BTreeMap<String, BTreeMap<u64, u64>>, // BTreeMap<scheduler_name, BTreeMap<group_uid, count>>
+
+    records: Vec<(f64, u64, PodGroupState)>, // Vec<(time, group_uid, state)>
+}
+
+impl PodGroupMetrics {
+    pub fn new() -> Self {
+        Self {
+            groups: BTreeMap::new(),
+            pending_by_scheduler: BTreeMap::new(),
+            records: Vec::new(),
+        }
+    }
+
+    ////////////////// Updates //////////////////
+
+    pub fn on_hpa_metrics(&mut self, group_uid: u64, replicas: u64, mean_cpu: Option<f64>, mean_memory: Option<f64>) {
+        let state = self.groups.entry(group_uid).or_default();
+        state.replicas = Some(replicas);
+        state.mean_cpu = mean_cpu;
+        state.mean_memory = mean_memory;
+    }
+
+    pub fn on_vpa_recommendation(
+        &mut self,
+        group_uid: u64,
+        request_cpu: i64,
+        request_memory: i64,
+        limit_cpu: i64,
+        limit_memory: i64,
+    ) {
+        let state = self.groups.entry(group_uid).or_default();
+        state.request_cpu = Some(request_cpu);
+        state.request_memory = Some(request_memory);
+        state.limit_cpu = Some(limit_cpu);
+        state.limit_memory = Some(limit_memory);
+    }
+
+    /// Pending pods of each group in scheduler. Groups without pending pods may be omitted.
+    pub fn on_pending_pods(&mut self, scheduler_name: &String, counts: BTreeMap<u64, u64>) {
+        self.pending_by_scheduler.insert(scheduler_name.clone(), counts);
+    }
+
+    pub fn on_group_removed(&mut self, group_uid: u64) {
+        self.groups.remove(&group_uid);
+    }
+
+    ////////////////// Records //////////////////
+
+    /// Records groups known to autoscalers and groups with pending pods.
+    pub fn sample(&mut self, time: f64) {
+        let mut states = self.groups.clone();
+        for counts in self.pending_by_scheduler.values() {
+            for (&group_uid, &count) in counts.iter() {
+                states.entry(group_uid).or_default().pending += count;
+            }
+        }
+
+        for (group_uid, state) in states {
+            self.records.push((time, group_uid, state));
+        }
+    }
+
+    pub fn clear_records(&mut self) {
+        self.records.clear();
+    }
+
+    pub fn write_csv<W: Write>(&self, fout: &mut W) {
+        Self::write_csv_header(fout);
+        self.write_csv_records(fout);
+    }
+
+    pub fn write_csv_header<W: Write>(fout: &mut W) {
+        write!(
+            fout,
+            "time,group_uid,replicas,hpa_mean_cpu,hpa_mean_memory,vpa_request_cpu,vpa_request_memory,vpa_limit_cpu,vpa_limit_memory,pending\n"
+        )
+        .unwrap();
+    }
+
+    /// Writes records without header.
+    pub fn write_csv_records<W: Write>(&self, fout: &mut W) {
+        // Absent values are left empty
+        let opt = |value: Option<String>| value.unwrap_or_default();
+        for (time, group_uid, state) in self.records.iter() {
+            write!(
+                fout,
+                "{:?},{:?},{},{},{},{},{},{},{},{:?}\n",
+                time,
+                group_uid,
+                opt(state.replicas.map(|x| format!("{:?}", x))),
+                opt(state.mean_cpu.map(|x| format!("{:?}", x))),
+                opt(state.mean_memory.map(|x| format!("{:?}", x))),
+                opt(state.request_cpu.map(|x| format!("{:?}", x))),
+                opt(state.request_memory.map(|x| format!("{:?}", x))),
+                opt(state.limit_cpu.map(|x| format!("{:?}", x))),
+                opt(state.limit_memory.map(|x| format!("{:?}", x))),
+                state.pending,
+            )
+            .unwrap();
+        }
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pod_group_metrics() {
+        let mut metrics = PodGroupMetrics::new();
+        metrics.on_hpa_metrics(1, 3, Some(0.5), Some(0.25));
+        metrics.on_vpa_recommendation(2, 100, 200, 150, 300);
+        metrics.on_pending_pods(&"a".to_string(), BTreeMap::from([(1, 1), (3, 2)]));
+        metrics.on_pending_pods(&"b".to_string(), BTreeMap::from([(3, 1)]));
+        metrics.sample(1.0);
+
+        // Scheduler report replaces the previous one, removed group is not recorded any more
+        metrics.on_pending_pods(&"a".to_string(), BTreeMap::new());
+        metrics.on_hpa_metrics(1, 4, None, None);
+        metrics.on_group_removed(2);
+        metrics.sample(2.0);
+
+        let mut out: Vec<u8> = Vec::new();
+        metrics.write_csv(&mut out);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().skip(1).collect::<Vec<&str>>(), vec![
+            "1.0,1,3,0.5,0.25,,,,,1",
+            "1.0,2,,,,100,200,150,300,0",
+            "1.0,3,,,,,,,,3",
+            "2.0,1,4,,,,,,,0",
+            "2.0,3,,,,,,,,1",
+        ]);
+
+        metrics.clear_records();
+        let mut out: Vec<u8> = Vec::new();
+        metrics.write_csv(&mut out);
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 1);
+    }
+}
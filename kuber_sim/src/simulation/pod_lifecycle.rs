@@ -0,0 +1,415 @@
+use crate::objects::pod::PodPhase;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Lifecycle of one pod. Times are None until the corresponding event happens.
+/// Bind and start times are the first ones, so they are not moved by rescheduling after eviction or preemption.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PodLifecycle {
+    pub pod_uid: u64,
+    pub group_uid: u64,
+    pub submit_time: f64,
+    pub first_attempt_time: Option<f64>,
+    pub bind_time: Option<f64>,
+    pub start_time: Option<f64>,
+    pub end_time: Option<f64>,
+    /// Succeeded, Failed or Removed.
+    pub end_phase: Option<PodPhase>,
+    /// The last node pod was bound to.
+    pub node_uid: Option<u64>,
+    pub attempts: u64,
+    pub evictions: u64,
+    pub preemptions: u64,
+}
+
+/// Relative width of buckets of Durations. Percentiles estimated by buckets exceed exact ones by at most 1%.
+pub const DURATION_BUCKET_RATIO: f64 = 1.01;
+
+/// Durations of all pods for percentiles. Values are kept exactly until buckets are enabled,
+/// then they are counted in buckets (r^(k-1), r^k] with r = DURATION_BUCKET_RATIO,
+/// so memory does not grow with pod count.
+#[derive(Debug, Default)]
+pub struct Durations {
+    values: Vec<f64>,
+    buckets: Option<BTreeMap<i64, u64>>, // BTreeMap<k, count>, zero durations have k = i64::MIN
+    count: usize,
+}
+
+impl Durations {
+    pub fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            buckets: None,
+            count: 0,
+        }
+    }
+
+    /// Values pushed so far are moved to buckets too.
+    pub fn enable_buckets(&mut self) {
+        if self.buckets.is_none() {
+            self.buckets = Some(BTreeMap::new());
+            for value in std::mem::take(&mut self.values) {
+                self.count -= 1;
+                self.push(value);
+            }
+        }
+    }
+
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        match self.buckets.as_mut() {
+            Some(buckets) => {
+                let k = match value > 0.0 {
+                    true => (value.ln() / DURATION_BUCKET_RATIO.ln()).ceil() as i64,
+                    false => i64::MIN,
+                };
+                *buckets.entry(k).or_default() += 1;
+            }
+            None => {
+                self.values.push(value);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        return self.count;
+    }
+
+    /// Nearest-rank percentile (0 < p <= 100). With buckets it is the upper bound of bucket. None for no values.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        let buckets = match self.buckets.as_ref() {
+            Some(buckets) => buckets,
+            None => return PodLifecycles::percentile(&self.values, p),
+        };
+        if self.count == 0 {
+            return None;
+        }
+
+        let rank = (((p / 100.0) * self.count as f64).ceil() as u64).clamp(1, self.count as u64);
+        let mut seen: u64 = 0;
+        for (&k, &count) in buckets.iter() {
+            seen += count;
+            if seen >= rank {
+                return match k {
+                    i64::MIN => Some(0.0),
+                    _ => Some(DURATION_BUCKET_RATIO.powi(k as i32)),
+                };
+            }
+        }
+        return None;
+    }
+}
+
+/// Lifecycle rows of submitted pods, ordered by pod uid. Durations for summary are kept for all pods.
+/// In streaming mode rows of finished pods are taken out by write_finished_csv, and durations are counted
+/// in buckets, so memory does not grow with finished pods.
+#[derive(Debug, Default)]
+pub struct PodLifecycles {
+    pods: BTreeMap<u64, PodLifecycle>, // BTreeMap<pod_uid, lifecycle>
+    /// Finished pods which rows are not yet written in streaming mode.
+    finished: Vec<u64>,
+    is_streaming: bool,
+
+    submitted: u64,
+    first_submit_time: Option<f64>,
+    last_end_time: Option<f64>,
+    /// Durations since submit to the first bind, the first start and the end of pods.
+    scheduling_latencies: Durations,
+    startup_latencies: Durations,
+    completion_times: Durations,
+}
+
+impl PodLifecycles {
+    pub fn new() -> Self {
+        Self {
+            pods: BTreeMap::new(),
+            finished: Vec::new(),
+            is_streaming: false,
+            submitted: 0,
+            first_submit_time: None,
+            last_end_time: None,
+            scheduling_latencies: Durations::new(),
+            startup_latencies: Durations::new(),
+            completion_times: Durations::new(),
+        }
+    }
+
+    /// Called before any pod is submitted.
+    pub fn enable_streaming(&mut self) {
+        self.is_streaming = true;
+        self.scheduling_latencies.enable_buckets();
+        self.startup_latencies.enable_buckets();
+        self.completion_times.enable_buckets();
+    }
+
+    pub fn get(&self, pod_uid: u64) -> Option<&PodLifecycle> {
+        return self.pods.get(&pod_uid);
+    }
+
+    /// Pods submitted during simulation.
+    pub fn submitted(&self) -> u64 {
+        return self.submitted;
+    }
+
+    /// Time from the first submit to the last end of pod. None if no pod has ended.
+    pub fn makespan(&self) -> Option<f64> {
+        return Some(self.last_end_time? - self.first_submit_time?);
+    }
+
+    /// Durations since submit to the first bind of bound pods.
+    pub fn scheduling_latencies(&self) -> &Durations {
+        return &self.scheduling_latencies;
+    }
+
+    pub fn on_submitted(&mut self, pod_uid: u64, group_uid: u64, time: f64) {
+        self.submitted += 1;
+        self.first_submit_time.get_or_insert(time);
+        self.pods.insert(
+            pod_uid,
+            PodLifecycle {
+                pod_uid,
+                group_uid,
+                submit_time: time,
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn on_attempt(&mut self, pod_uid: u64, time: f64) {
+        if let Some(row) = self.pods.get_mut(&pod_uid) {
+            row.first_attempt_time.get_or_insert(time);
+            row.attempts += 1;
+        }
+    }
+
+    pub fn on_bound(&mut self, pod_uid: u64, node_uid: u64, time: f64) {
+        if let Some(row) = self.pods.get_mut(&pod_uid) {
+            if row.bind_time.is_none() {
+                row.bind_time = Some(time);
+                self.scheduling_latencies.push(time - row.submit_time);
+            }
+            row.node_uid = Some(node_uid);
+        }
+    }
+
+    pub fn on_started(&mut self, pod_uid: u64, time: f64) {
+        if let Some(row) = self.pods.get_mut(&pod_uid) {
+            if row.start_time.is_none() {
+                row.start_time = Some(time);
+                self.startup_latencies.push(time - row.submit_time);
+            }
+        }
+    }
+
+    pub fn on_evicted(&mut self, pod_uid: u64) {
+        if let Some(row) = self.pods.get_mut(&pod_uid) {
+            row.evictions += 1;
+        }
+    }
+
+    pub fn on_preempted(&mut self, pod_uid: u64) {
+        if let Some(row) = self.pods.get_mut(&pod_uid) {
+            row.preemptions += 1;
+        }
+    }
+
+    pub fn on_finished(&mut self, pod_uid: u64, phase: PodPhase, time: f64) {
+        if let Some(row) = self.pods.get_mut(&pod_uid) {
+            if row.end_time.is_none() {
+                row.end_time = Some(time);
+                row.end_phase = Some(phase);
+                self.completion_times.push(time - row.submit_time);
+                self.last_end_time = Some(self.last_end_time.unwrap_or(time).max(time));
+                if self.is_streaming {
+                    self.finished.push(pod_uid);
+                }
+            }
+        }
+    }
+
+    pub fn write_csv<W: Write>(&self, fout: &mut W) {
+        Self::write_csv_header(fout);
+        self.write_csv_records(fout);
+    }
+
+    pub fn write_csv_header<W: Write>(fout: &mut W) {
+        write!(
+            fout,
+            "pod_uid,group_uid,submit_time,first_attempt_time,bind_time,start_time,end_time,end_phase,node_uid,attempts,evictions,preemptions\n"
+        )
+        .unwrap();
+    }
+
+    /// Writes rows of all pods without header.
+    pub fn write_csv_records<W: Write>(&self, fout: &mut W) {
+        for row in self.pods.values() {
+            Self::write_csv_row(fout, row);
+        }
+    }
+
+    fn write_csv_row<W: Write>(fout: &mut W, row: &PodLifecycle) {
+        // Absent values are left empty
+        let opt = |value: Option<String>| value.unwrap_or_default();
+        write!(
+            fout,
+            "{:?},{:?},{:?},{},{},{},{},{},{},{:?},{:?},{:?}\n",
+            row.pod_uid,
+            row.group_uid,
+            row.submit_time,
+            opt(row.first_attempt_time.map(|x| format!("{:?}", x))),
+            opt(row.bind_time.map(|x| format!("{:?}", x))),
+            opt(row.start_time.map(|x| format!("{:?}", x))),
+            opt(row.end_time.map(|x| format!("{:?}", x))),
+            opt(row.end_phase.as_ref().map(|x| format!("{:?}", x))),
+            opt(row.node_uid.map(|x| format!("{:?}", x))),
+            row.attempts,
+            row.evictions,
+            row.preemptions,
+        )
+        .unwrap();
+    }
+
+    /// Writes rows of pods finished since the previous call without header and forgets them (streaming mode only).
+    /// Later events of these pods are ignored.
+    pub fn write_finished_csv<W: Write>(&mut self, fout: &mut W) {
+        let mut finished = std::mem::take(&mut self.finished);
+        finished.sort();
+        for pod_uid in finished {
+            let row = self.pods.remove(&pod_uid).unwrap();
+            Self::write_csv_row(fout, &row);
+        }
+    }
+
+    /// Nearest-rank percentile (0 < p <= 100). None for empty values.
+    pub fn percentile(values: &Vec<f64>, p: f64) -> Option<f64> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        return Some(sorted[rank.clamp(1, sorted.len()) - 1]);
+    }
+
+    /// Lines "<name>: p50 p90 p99 (count)" for scheduling, startup and completion latencies since submit.
+    pub fn summary(&self) -> Vec<String> {
+        let metrics = [
+            ("Scheduling latency", &self.scheduling_latencies),
+            ("Startup latency", &self.startup_latencies),
+            ("Completion time", &self.completion_times),
+        ];
+
+        let mut lines: Vec<String> = Vec::new();
+        for (name, values) in metrics.iter() {
+            let p = |percent: f64| match values.percentile(percent) {
+                Some(value) => format!("{:.3}", value),
+                None => "-".to_string(),
+            };
+            lines.push(format!(
+                "{:<20} p50:{:<9} p90:{:<9} p99:{:<9} pods:{}",
+                name,
+                p(50.0),
+                p(90.0),
+                p(99.0),
+                values.len()
+            ));
+        }
+        return lines;
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pod_lifecycles() {
+        let mut lifecycles = PodLifecycles::new();
+        lifecycles.on_submitted(2, 1, 1.0);
+        lifecycles.on_submitted(3, 1, 1.0);
+
+        // Rescheduled pod keeps the first bind and start, but counts attempts and disruptions
+        lifecycles.on_attempt(2, 1.5);
+        lifecycles.on_attempt(2, 2.0);
+        lifecycles.on_bound(2, 10, 2.0);
+        lifecycles.on_started(2, 2.5);
+        lifecycles.on_preempted(2);
+        lifecycles.on_attempt(2, 3.0);
+        lifecycles.on_bound(2, 11, 3.0);
+        lifecycles.on_started(2, 3.5);
+        lifecycles.on_finished(2, PodPhase::Succeeded, 5.0);
+        lifecycles.on_finished(2, PodPhase::Removed, 6.0);
+
+        let row = lifecycles.get(2).unwrap();
+        assert_eq!((row.first_attempt_time, row.bind_time, row.start_time), (Some(1.5), Some(2.0), Some(2.5)));
+        assert_eq!((row.end_time, row.end_phase.clone(), row.node_uid), (Some(5.0), Some(PodPhase::Succeeded), Some(11)));
+        assert_eq!((row.attempts, row.evictions, row.preemptions), (3, 0, 1));
+        assert_eq!((lifecycles.submitted(), lifecycles.makespan()), (2, Some(4.0)));
+        assert_eq!(lifecycles.scheduling_latencies().percentile(50.0), Some(1.0));
+
+        // Unknown pods are ignored
+        lifecycles.on_attempt(4, 1.0);
+        assert!(lifecycles.get(4).is_none());
+
+        let mut out: Vec<u8> = Vec::new();
+        lifecycles.write_csv(&mut out);
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "2,1,1.0,1.5,2.0,2.5,5.0,Succeeded,11,3,0,1");
+        assert_eq!(lines[2], "3,1,1.0,,,,,,,0,0,0");
+    }
+
+    #[test]
+    fn test_pod_lifecycles_streaming() {
+        let mut lifecycles = PodLifecycles::new();
+        lifecycles.enable_streaming();
+        for uid in 1..=3 {
+            lifecycles.on_submitted(uid, 1, 1.0);
+        }
+        lifecycles.on_finished(3, PodPhase::Removed, 2.0);
+        lifecycles.on_finished(1, PodPhase::Removed, 3.0);
+
+        // Finished pods are written once in uid order, but still counted in summary
+        let mut out: Vec<u8> = Vec::new();
+        lifecycles.write_finished_csv(&mut out);
+        lifecycles.write_finished_csv(&mut out);
+        assert_eq!(String::from_utf8(out).unwrap(), "1,1,1.0,,,,3.0,Removed,,0,0,0\n3,1,1.0,,,,2.0,Removed,,0,0,0\n");
+        assert!(lifecycles.get(1).is_none());
+        assert!(lifecycles.get(2).is_some());
+        assert!(lifecycles.summary()[2].ends_with("pods:2"));
+    }
+
+    #[test]
+    fn test_durations() {
+        let mut durations = Durations::new();
+        for value in [0.0, 10.0, 100.0] {
+            durations.push(value);
+        }
+        assert_eq!(durations.percentile(50.0), Some(10.0));
+
+        // Buckets overestimate by at most DURATION_BUCKET_RATIO
+        durations.enable_buckets();
+        durations.push(1000.0);
+        assert_eq!(durations.len(), 4);
+        assert_eq!(durations.percentile(25.0), Some(0.0));
+        for (p, exact) in [(50.0, 10.0), (75.0, 100.0), (100.0, 1000.0)] {
+            let value = durations.percentile(p).unwrap();
+            assert!(exact <= value && value <= exact * DURATION_BUCKET_RATIO, "{} {}", p, value);
+        }
+        assert_eq!(Durations::new().percentile(50.0), None);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<f64> = (1..=100).rev().map(|x| x as f64).collect();
+        assert_eq!(PodLifecycles::percentile(&values, 50.0), Some(50.0));
+        assert_eq!(PodLifecycles::percentile(&values, 90.0), Some(90.0));
+        assert_eq!(PodLifecycles::percentile(&values, 99.0), Some(99.0));
+        assert_eq!(PodLifecycles::percentile(&vec![3.0], 99.0), Some(3.0));
+        assert_eq!(PodLifecycles::percentile(&vec![], 50.0), None);
+    }
+}
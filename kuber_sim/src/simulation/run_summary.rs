@@ -0,0 +1,217 @@
+use crate::simulation::metrics_sink::MetricsRow;
+use crate::simulation::pod_lifecycle::PodLifecycles;
+use std::collections::BTreeMap;
+
+/// Free resource of node is stranded if the other resource of node has at most this fraction free.
+pub const STRANDED_FREE_FRACTION: f64 = 0.05;
+
+/// Headline numbers of a run to compare experiments.
+/// Resource values are fractions of installed resources of the cluster, averaged over monitoring samples.
+/// Rates are per submitted pod.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct RunSummary {
+    pub time: f64,
+    pub samples: u64,
+
+    /// Requested by pods bound by schedulers.
+    pub avg_allocated_cpu: f64,
+    pub avg_allocated_memory: f64,
+    pub peak_allocated_cpu: f64,
+    pub peak_allocated_memory: f64,
+    /// Actually used by pods on kubelets.
+    pub avg_used_cpu: f64,
+    pub avg_used_memory: f64,
+    pub peak_used_cpu: f64,
+    pub peak_used_memory: f64,
+    /// Allocated but not used.
+    pub avg_allocation_gap_cpu: f64,
+    pub avg_allocation_gap_memory: f64,
+    /// Free CPU on nodes without free memory and the reverse, see STRANDED_FREE_FRACTION.
+    pub avg_stranded_cpu: f64,
+    pub avg_stranded_memory: f64,
+    pub peak_stranded_cpu: f64,
+    pub peak_stranded_memory: f64,
+
+    /// Time from the first submit to the last end of pod.
+    pub makespan: Option<f64>,
+    /// Time from submit to the first bind of pods.
+    pub pending_time_p50: Option<f64>,
+    pub pending_time_p90: Option<f64>,
+    pub pending_time_p99: Option<f64>,
+
+    pub submitted: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub evicted: u64,
+    pub preempted: u64,
+    pub failure_rate: f64,
+    pub eviction_rate: f64,
+    pub preemption_rate: f64,
+
+    /// Cost of nodes by NodeGroup.cost_per_hour.
+    pub cost: f64,
+    pub cost_by_node_group: BTreeMap<u64, f64>, // BTreeMap<group_uid, cost>
+    /// Energy consumed by nodes by NodeGroup.power_model, Wh.
+    pub energy: f64,
+    pub energy_by_node: BTreeMap<u64, f64>, // BTreeMap<node_uid, energy>
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct FractionStats {
+    sum: f64,
+    peak: f64,
+}
+
+impl FractionStats {
+    fn add(&mut self, value: f64) {
+        self.sum += value;
+        self.peak = self.peak.max(value);
+    }
+
+    fn mean(&self, samples: u64) -> f64 {
+        return ratio(self.sum, samples as f64);
+    }
+}
+
+/// Zero for zero denominator (e.g. cluster without nodes).
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator == 0.0 {
+        return 0.0;
+    }
+    return numerator / denominator;
+}
+
+/// Accumulates periodic monitoring samples for RunSummary.
+#[derive(Debug, Default)]
+pub struct RunSummaryCollector {
+    samples: u64,
+    allocated_cpu: FractionStats,
+    allocated_memory: FractionStats,
+    used_cpu: FractionStats,
+    used_memory: FractionStats,
+    gap_cpu: FractionStats,
+    gap_memory: FractionStats,
+    stranded_cpu: FractionStats,
+    stranded_memory: FractionStats,
+}
+
+impl RunSummaryCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on_sample(&mut self, row: &MetricsRow, stranded_cpu: i64, stranded_memory: i64) {
+        let cpu = |value: i64| ratio(value as f64, row.total_cpu as f64);
+        let memory = |value: i64| ratio(value as f64, row.total_memory as f64);
+
+        self.samples += 1;
+        self.allocated_cpu.add(cpu(row.scheduler_used_cpu));
+        self.allocated_memory.add(memory(row.scheduler_used_memory));
+        self.used_cpu.add(cpu(row.kubelets_used_cpu));
+        self.used_memory.add(memory(row.kubelets_used_memory));
+        self.gap_cpu.add(cpu(row.scheduler_used_cpu - row.kubelets_used_cpu));
+        self.gap_memory
+            .add(memory(row.scheduler_used_memory - row.kubelets_used_memory));
+        self.stranded_cpu.add(cpu(stranded_cpu));
+        self.stranded_memory.add(memory(stranded_memory));
+    }
+
+    /// Summary of samples so far. Counters are taken from the current row.
+    pub fn summary(
+        &self,
+        row: &MetricsRow,
+        pod_lifecycles: &PodLifecycles,
+        cost_by_node_group: BTreeMap<u64, f64>,
+        energy_by_node: BTreeMap<u64, f64>,
+    ) -> RunSummary {
+        let submitted = pod_lifecycles.submitted();
+        let pending_times = pod_lifecycles.scheduling_latencies();
+        return RunSummary {
+            time: row.time,
+            samples: self.samples,
+            avg_allocated_cpu: self.allocated_cpu.mean(self.samples),
+            avg_allocated_memory: self.allocated_memory.mean(self.samples),
+            peak_allocated_cpu: self.allocated_cpu.peak,
+            peak_allocated_memory: self.allocated_memory.peak,
+            avg_used_cpu: self.used_cpu.mean(self.samples),
+            avg_used_memory: self.used_memory.mean(self.samples),
+            peak_used_cpu: self.used_cpu.peak,
+            peak_used_memory: self.used_memory.peak,
+            avg_allocation_gap_cpu: self.gap_cpu.mean(self.samples),
+            avg_allocation_gap_memory: self.gap_memory.mean(self.samples),
+            avg_stranded_cpu: self.stranded_cpu.mean(self.samples),
+            avg_stranded_memory: self.stranded_memory.mean(self.samples),
+            peak_stranded_cpu: self.stranded_cpu.peak,
+            peak_stranded_memory: self.stranded_memory.peak,
+            makespan: pod_lifecycles.makespan(),
+            pending_time_p50: pending_times.percentile(50.0),
+            pending_time_p90: pending_times.percentile(90.0),
+            pending_time_p99: pending_times.percentile(99.0),
+            submitted,
+            succeeded: row.succeed,
+            failed: row.failed,
+            evicted: row.evicted,
+            preempted: row.preempted,
+            failure_rate: ratio(row.failed as f64, submitted as f64),
+            eviction_rate: ratio(row.evicted as f64, submitted as f64),
+            preemption_rate: ratio(row.preempted as f64, submitted as f64),
+            cost: row.cost,
+            cost_by_node_group,
+            energy: row.energy,
+            energy_by_node,
+        };
+    }
+}
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::pod::PodPhase;
+
+    #[test]
+    fn test_run_summary() {
+        let mut collector = RunSummaryCollector::new();
+        let mut row = MetricsRow { time: 1.0, total_cpu: 100, total_memory: 200, ..Default::default() };
+        row.scheduler_used_cpu = 50;
+        row.scheduler_used_memory = 100;
+        row.kubelets_used_cpu = 30;
+        row.kubelets_used_memory = 100;
+        collector.on_sample(&row, 10, 0);
+
+        row.time = 2.0;
+        row.scheduler_used_cpu = 100;
+        row.kubelets_used_cpu = 70;
+        row.failed = 1;
+        row.evicted = 2;
+        collector.on_sample(&row, 0, 20);
+
+        // Samples of empty cluster count as zero
+        row.time = 3.0;
+        row.total_cpu = 0;
+        collector.on_sample(&row, 0, 0);
+
+        let mut lifecycles = PodLifecycles::new();
+        for uid in 1..=4 {
+            lifecycles.on_submitted(uid, 1, 1.0);
+        }
+        lifecycles.on_bound(1, 1, 2.0);
+        lifecycles.on_finished(1, PodPhase::Failed, 4.0);
+
+        row.cost = 2.5;
+        row.energy = 10.0;
+        let summary = collector.summary(&row, &lifecycles, BTreeMap::from([(1, 2.0), (2, 0.5)]), BTreeMap::from([(1, 10.0)]));
+        assert_eq!(summary.samples, 3);
+        assert_eq!((summary.avg_allocated_cpu, summary.peak_allocated_cpu), (0.5, 1.0));
+        assert_eq!((summary.avg_used_cpu, summary.peak_used_cpu), (1.0 / 3.0, 0.7));
+        assert_eq!(summary.avg_allocation_gap_cpu, (0.2 + 0.3) / 3.0);
+        assert_eq!(summary.avg_allocation_gap_memory, 0.0);
+        assert_eq!((summary.peak_stranded_cpu, summary.peak_stranded_memory), (0.1, 0.1));
+        assert_eq!((summary.makespan, summary.pending_time_p50), (Some(3.0), Some(1.0)));
+        assert_eq!((summary.submitted, summary.failure_rate, summary.eviction_rate, summary.preemption_rate), (4, 0.25, 0.5, 0.0));
+        assert_eq!((summary.cost, summary.cost_by_node_group.len()), (2.5, 2));
+        assert_eq!((summary.energy, summary.energy_by_node.len()), (10.0, 1));
+    }
+}
@@ -1,15 +1,21 @@
 use crate::api_server::api::APIServer;
+use crate::api_server::disruption_budgets::DisruptionBudgets;
 use crate::autoscaler::ca::ca::CA;
 use crate::autoscaler::hpa::hpa::HPA;
 use crate::autoscaler::vpa::vpa::VPA;
 use crate::common_imports::*;
+use crate::error::KuberSimError;
+use crate::scheduler::cache::SchedulerCache;
 use crate::scheduler::scheduler::Scheduler;
 use crate::simulation::init_config::InitConfig;
 use crate::simulation::init_nodes::InitNodes;
 use crate::simulation::init_trace::InitTrace;
 use crate::simulation::monitoring::Monitoring;
 use crate::simulation::pipeline_config::PipelineConfig;
+use crate::simulation::run_summary::RunSummary;
+use crate::simulation::uid_allocator::UidAllocator;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 
 pub struct Simulation {
@@ -23,12 +29,46 @@ impl Simulation {
         init_config: &InitConfig,
         init_nodes: &InitNodes,
         init_trace: &InitTrace,
-        pipeline_config: &PipelineConfig,
+        pipeline_configs: &[(&str, &PipelineConfig)], // &[(scheduler_name, pipeline_config)]
         seed: u64,
         flag_add_ca: bool,
         flag_add_hpa: bool,
         flag_add_vpa: bool,
-    ) -> Self {
+    ) -> Result<Self, KuberSimError> {
+        // Check input before any component is created
+        sim_ensure!(
+            !pipeline_configs.is_empty(),
+            "PipelineConfig",
+            "At least one PipelineConfig is required"
+        );
+        for (i, &(scheduler_name, pipeline_config)) in pipeline_configs.iter().enumerate() {
+            sim_ensure!(
+                pipeline_configs[..i].iter().all(|&(other, _)| other != scheduler_name),
+                "PipelineConfig",
+                format!("Scheduler name {} is used twice", scheduler_name)
+            );
+            sim_ensure!(
+                pipeline_config.scorers.len() == pipeline_config.score_normalizers.len(),
+                format!("PipelineConfig({}).scorers", scheduler_name),
+                "len() must be == PipelineConfig.score_normalizers.len()"
+            );
+            sim_ensure!(
+                pipeline_config.scorers.len() == pipeline_config.scorer_weights.len(),
+                format!("PipelineConfig({}).scorers", scheduler_name),
+                "len() must be == PipelineConfig.scorer_weights.len()"
+            );
+        }
+        let scheduler_names: Vec<&str> = pipeline_configs.iter().map(|&(name, _)| name).collect();
+
+        // Uids of groups in input and of pods and nodes created during simulation
+        let mut uids = UidAllocator::new();
+        let mut init_nodes = init_nodes.clone();
+        init_nodes.prepare(&mut uids)?;
+        let mut init_trace = init_trace.clone();
+        init_trace.prepare(&mut uids)?;
+        init_trace.resolve_references(init_config, &scheduler_names)?;
+        let uids = Rc::new(RefCell::new(uids));
+
         // DSLab core
         let mut sim = dsc::Simulation::new(seed);
 
@@ -37,12 +77,8 @@ impl Simulation {
         // Init nodes to shared_ptr
         let init_nodes_ptr = Rc::new(RefCell::new(init_nodes.clone()));
 
-        // Api-server component
-        let api = Rc::new(RefCell::new(APIServer::new(
-            sim.create_context("api_server"),
-            init_config_ptr.clone(),
-        )));
-        let api_id = sim.add_handler("api_server", api.clone());
+        // PodDisruptionBudgets are tracked by api-server and shared with disrupting components
+        let disruption_budgets = Rc::new(RefCell::new(DisruptionBudgets::new()));
 
         // Monitoring component
         let monitoring = Rc::new(RefCell::new(Monitoring::new(
@@ -51,33 +87,80 @@ impl Simulation {
             &output_file_path,
         )));
         let _ = sim.add_handler("monitoring", monitoring.clone());
+        for group in init_nodes.nodes.iter().chain(init_nodes.ca_nodes.iter()) {
+            if let Some(cost_per_hour) = group.cost_per_hour {
+                monitoring
+                    .borrow_mut()
+                    .set_node_group_cost(group.group_uid, cost_per_hour);
+            }
+            if let Some(power_model) = group.power_model.as_ref() {
+                monitoring
+                    .borrow_mut()
+                    .set_node_group_power_model(group.group_uid, power_model);
+            }
+        }
 
-        // Copy scheduler pipeline config
-        let pconf = pipeline_config.clone();
-
-        sim_assert!(
-            pconf.scorers.len() == pconf.score_normalizers.len(),
-            "PipelineConfig.scorers.len() must be == PipelineConfig.score_normalizers.len()"
-        );
-        sim_assert!(
-            pconf.scorers.len() == pconf.scorer_weights.len(),
-            "PipelineConfig.scorers.len() must be == PipelineConfig.scorer_weights.len()"
-        );
-
-        let scheduler = Rc::new(RefCell::new(Scheduler::new(
-            sim.create_context("scheduler"),
+        // Api-server component
+        let api = Rc::new(RefCell::new(APIServer::new(
+            sim.create_context("api_server"),
             init_config_ptr.clone(),
+            disruption_budgets.clone(),
             monitoring.clone(),
-            api_id,
-            pconf.active_queue,
-            pconf.backoff_queue,
-            pconf.filters,
-            pconf.post_filters,
-            pconf.scorers,
-            pconf.score_normalizers,
-            pconf.scorer_weights,
+            uids.clone(),
         )));
-        let scheduler_id = sim.add_handler("scheduler", scheduler.clone());
+        let api_id = sim.add_handler("api_server", api.clone());
+
+        // Node cache shared by all schedulers
+        let scheduler_cache = Rc::new(RefCell::new(SchedulerCache::new()));
+
+        // Add scheduler for each profile
+        let mut schedulers: Vec<Rc<RefCell<Scheduler>>> = Vec::new();
+        let mut scheduler_ids: BTreeMap<String, dsc::Id> = BTreeMap::new();
+        for &(scheduler_name, pipeline_config) in pipeline_configs.iter() {
+            // Copy scheduler pipeline config
+            let pconf = pipeline_config.clone();
+
+            // Scores of different ranges can not be weighted against each other sensibly
+            let combined_count = pconf.scorer_weights.iter().filter(|&&weight| weight != 0).count();
+            if combined_count > 1 {
+                for (i, scorer) in pconf.scorers.iter().enumerate() {
+                    let normalizer = &pconf.score_normalizers[i];
+                    let is_in_range = scorer.is_normalized() || normalizer.maps_to_node_score_range();
+                    if pconf.scorer_weights[i] != 0 && !is_in_range {
+                        sim_warn!(format!(
+                            "PipelineConfig. Scorer {} is combined with others, but {} does not map it to [0, 100].",
+                            scorer.name(),
+                            normalizer.name()
+                        ));
+                    }
+                }
+            }
+
+            let scheduler_ctx_name = "scheduler_".to_owned() + scheduler_name;
+            let scheduler = Rc::new(RefCell::new(Scheduler::new(
+                scheduler_name.to_string(),
+                sim.create_context(scheduler_ctx_name.clone()),
+                init_config_ptr.clone(),
+                monitoring.clone(),
+                scheduler_cache.clone(),
+                disruption_budgets.clone(),
+                api_id,
+                pconf.active_queue,
+                pconf.backoff_queue,
+                pconf.filters,
+                pconf.post_filters,
+                pconf.scorers,
+                pconf.score_normalizers,
+                pconf.scorer_weights,
+                pconf.reserves,
+                pconf.permits,
+                pconf.pre_binds,
+            )));
+            let scheduler_id = sim.add_handler(scheduler_ctx_name, scheduler.clone());
+
+            schedulers.push(scheduler);
+            scheduler_ids.insert(scheduler_name.to_string(), scheduler_id);
+        }
 
         // Add CA if needed
         let mut ca_id = None;
@@ -88,8 +171,11 @@ impl Simulation {
                 ca_ctx,
                 init_config_ptr.clone(),
                 init_nodes_ptr.clone(),
-                scheduler.clone(),
+                schedulers.clone(),
+                scheduler_cache.clone(),
+                disruption_budgets.clone(),
                 monitoring.clone(),
+                uids.clone(),
                 api_id,
             ))));
             ca_id = Some(sim.add_handler("ca", ca.clone().unwrap()));
@@ -105,6 +191,8 @@ impl Simulation {
                 sim.create_context("hpa"),
                 init_config_ptr.clone(),
                 api_id,
+                monitoring.clone(),
+                uids.clone(),
             ))));
             hpa_id = Some(sim.add_handler("hpa", hpa.clone().unwrap()));
 
@@ -119,6 +207,9 @@ impl Simulation {
                 sim.create_context("vpa"),
                 init_config_ptr.clone(),
                 api_id,
+                disruption_budgets.clone(),
+                monitoring.clone(),
+                uids.clone(),
             ))));
             vpa_id = Some(sim.add_handler("vpa", vpa.clone().unwrap()));
 
@@ -127,7 +218,7 @@ impl Simulation {
         }
 
         // Prepare components
-        api.borrow_mut().prepare(scheduler_id, ca_id, hpa_id, vpa_id);
+        api.borrow_mut().prepare(scheduler_ids, ca_id, hpa_id, vpa_id);
         monitoring.borrow_mut().prepare();
 
         // Prepare cluster with nodes
@@ -136,16 +227,22 @@ impl Simulation {
             &api.borrow().ctx,
             init_config_ptr.clone(),
             monitoring.clone(),
+            uids.clone(),
             api_id,
         );
         // Prepare cluster with trace
         init_trace.submit(&api.borrow().ctx, api_id);
 
-        Self { sim, monitoring }
+        Ok(Self { sim, monitoring })
     }
 
     pub fn dump_stats(&self) {
-        self.monitoring.borrow().dump_statistics();
+        self.monitoring.borrow_mut().dump_statistics();
+    }
+
+    /// Headline numbers of the run so far, computed from monitoring samples.
+    pub fn run_summary(&self) -> RunSummary {
+        return self.monitoring.borrow().run_summary();
     }
 
     pub fn enable_dynamic_update(&self) {
@@ -164,6 +261,20 @@ impl Simulation {
         self.monitoring.borrow_mut().print_enabled = false;
     }
 
+    /// Writes every scheduling attempt to <output_file_path>_decisions.jsonl with top_k best scored nodes.
+    pub fn enable_decision_log(&mut self, top_k: usize) {
+        self.monitoring.borrow_mut().enable_decision_log(top_k);
+    }
+
+    pub fn disable_decision_log(&mut self) {
+        self.monitoring.borrow_mut().disable_decision_log();
+    }
+
+    /// Why the last scheduling attempt of pod failed. None if pod is bound, finished, removed or was never tried.
+    pub fn last_failure_reason(&self, pod_uid: u64) -> Option<String> {
+        return self.monitoring.borrow().last_failure_reason(pod_uid);
+    }
+
     pub fn clear_records(&mut self) {
         self.monitoring.borrow_mut().clear_records();
     }
@@ -184,3 +295,84 @@ impl Simulation {
         self.sim.step_until_time(time);
     }
 }
+
+/////////////////////////////////////////////// Test ///////////////////////////////////////////////
+
+#[rustfmt::skip]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::queues::active_queue::ActiveQDefault;
+    use crate::scheduler::queues::backoff_queue::BackOffQConstant;
+    use crate::scheduler::scheduler::DEFAULT_SCHEDULER_NAME;
+
+    const INPUT: &str = "
+monitoring:
+  self_update_period: 1
+scheduler:
+  self_update_period: 1
+  unschedulable_queue_backoff_delay: 5
+ca:
+  self_update_period: 5
+nodes:
+  - amount: 1
+    node:
+      spec:
+        installed_cpu: 100
+        installed_memory: 100
+ca_nodes:
+  - amount: 2
+    node:
+      spec:
+        installed_cpu: 100
+        installed_memory: 100
+trace:
+  - submit_time: 1
+    event:
+      !AddPodGroup
+      pod_count: 6
+      pod:
+        spec:
+          request_cpu: 40
+          request_memory: 40
+          load:
+            !Constant
+            cpu: 40
+            memory: 40
+            duration: 20
+";
+
+    /// Runs simulation and returns its statistics, pod lifecycles and summary files.
+    fn run(out_path_prefix: String) -> Vec<String> {
+        let mut init_config: InitConfig = serde_yaml::from_str(INPUT).unwrap();
+        let init_nodes: InitNodes = serde_yaml::from_str(INPUT).unwrap();
+        let init_trace: InitTrace = serde_yaml::from_str(INPUT).unwrap();
+        init_config.prepare().unwrap();
+
+        let pipeline_config = PipelineConfig::new(Box::new(ActiveQDefault::default()), Box::new(BackOffQConstant::new(1.0)), vec![], vec![], vec![], vec![], vec![]);
+        let mut sim = Simulation::new(out_path_prefix.clone(), &init_config, &init_nodes, &init_trace, &[(DEFAULT_SCHEDULER_NAME, &pipeline_config)], 123, true, false, false).unwrap();
+        sim.disable_print();
+        sim.step_for_duration(50.0);
+        sim.dump_stats();
+
+        let mut outputs: Vec<String> = Vec::new();
+        for suffix in ["_0.csv", "_pods_0.csv", "_summary_0.json"] {
+            let path = out_path_prefix.clone() + suffix;
+            outputs.push(std::fs::read_to_string(&path).unwrap());
+            std::fs::remove_file(&path).unwrap();
+        }
+        std::fs::remove_file(out_path_prefix.clone() + "_pod_groups_0.csv").unwrap();
+        return outputs;
+    }
+
+    #[test]
+    fn test_identical_runs_in_one_process() {
+        let prefix = std::env::temp_dir().join(format!("kuber_sim_{}_run", std::process::id())).to_string_lossy().to_string();
+        let first = run(prefix.clone() + "1");
+        let second = run(prefix.clone() + "2");
+
+        // Uids of the second run start from the beginning too
+        assert!(first[1].lines().nth(1).unwrap().starts_with("1,1,"));
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,36 @@
+/// Source of object uids. Each Simulation has its own allocator, so uids depend only on the simulation
+/// inputs, and identical simulations give identical outputs even if they share a process.
+/// Each kind of object has its own sequence of uids starting from 1.
+#[derive(Debug, Clone, Default)]
+pub struct UidAllocator {
+    last_pod_uid: u64,
+    last_node_uid: u64,
+    last_pod_group_uid: u64,
+    last_node_group_uid: u64,
+}
+
+impl UidAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn next_pod_uid(&mut self) -> u64 {
+        self.last_pod_uid += 1;
+        return self.last_pod_uid;
+    }
+
+    pub fn next_node_uid(&mut self) -> u64 {
+        self.last_node_uid += 1;
+        return self.last_node_uid;
+    }
+
+    pub fn next_pod_group_uid(&mut self) -> u64 {
+        self.last_pod_group_uid += 1;
+        return self.last_pod_group_uid;
+    }
+
+    pub fn next_node_group_uid(&mut self) -> u64 {
+        self.last_node_group_uid += 1;
+        return self.last_node_group_uid;
+    }
+}